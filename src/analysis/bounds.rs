@@ -1,7 +1,7 @@
 use crate::{
     analysis::{
         function_parameters::{async_param_to_remove, CParameter},
-        functions::{find_function, find_index_to_ignore, finish_function_name},
+        functions::{find_function, find_index_to_ignore, finish_function_c_identifier},
         imports::Imports,
         out_parameters::use_function_return_for_result,
         rust_type::{bounds_rust_type, rust_type, rust_type_nullable, rust_type_with_scope},
@@ -93,10 +93,12 @@ impl Bounds {
         if !par.instance_parameter && par.direction != ParameterDirection::Out {
             if let Some(bound_type) = Bounds::type_for(env, par.typ, par.nullable) {
                 ret = Some(Bounds::get_to_glib_extra(&bound_type));
-                if r#async && (par.name == "callback" || par.name.ends_with("_callback")) {
-                    let func_name = func.c_identifier.as_ref().unwrap();
-                    let finish_func_name = finish_function_name(func_name);
-                    if let Some(function) = find_function(env, &finish_func_name) {
+                if r#async
+                    && par.scope.is_async()
+                    && (par.name == "callback" || par.name.ends_with("_callback"))
+                {
+                    let finish_func_name = finish_function_c_identifier(env, func);
+                    if let Some((_, function)) = find_function(env, &finish_func_name) {
                         let mut out_parameters = find_out_parameters(env, function);
                         if use_function_return_for_result(
                             env,
@@ -76,6 +76,8 @@ impl ConversionType {
             List(_) => ConversionType::Pointer,
             SList(_) => ConversionType::Pointer,
             PtrArray(_) => ConversionType::Pointer,
+            HashTable(_, _) => ConversionType::Pointer,
+            Typed(_) => ConversionType::Pointer,
             Function(super::library::Function { ref name, .. }) if name == "AsyncReadyCallback" => {
                 ConversionType::Direct
             }
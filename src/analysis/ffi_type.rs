@@ -4,6 +4,7 @@ use crate::{
         is_gpointer,
         rust_type::{Result, TypeError},
     },
+    config::data_model,
     env::Env,
     library::*,
     nameutil::{use_glib_if_needed, use_glib_type},
@@ -83,24 +84,16 @@ fn ffi_inner(env: &Env, tid: TypeId, inner: &str) -> Result {
                 UInt32 => "u32",
                 Int64 => "i64",
                 UInt64 => "u64",
-                Char => "libc::c_char",
-                UChar => "libc::c_uchar",
-                Short => "libc::c_short",
-                UShort => "libc::c_ushort",
-                Int => "libc::c_int",
-                UInt => "libc::c_uint",
-                Long => "libc::c_long",
-                ULong => "libc::c_ulong",
-                Size => "libc::size_t",
-                SSize => "libc::ssize_t",
+                Char | UChar | Short | UShort | Int | UInt | Long | ULong | Size | SSize
+                | IntPtr | UIntPtr => {
+                    return Ok(data_model::ffi_type(&env.config.data_models, fund).unwrap())
+                }
                 Float => "libc::c_float",
                 Double => "libc::c_double",
                 UniChar => "u32",
                 Utf8 => "libc::c_char",
                 Filename => "libc::c_char",
                 Type => return Ok(use_glib_if_needed(env, "ffi::GType")),
-                IntPtr => "libc::intptr_t",
-                UIntPtr => "libc::uintptr_t",
                 _ => return Err(TypeError::Unimplemented(inner.into())),
             };
             Ok(inner.into())
@@ -123,6 +116,7 @@ fn ffi_inner(env: &Env, tid: TypeId, inner: &str) -> Result {
             fix_name(env, tid, inner)
         }
         Type::CArray(inner_tid) => ffi_inner(env, inner_tid, inner),
+        Type::Typed(ref typed) => ffi_inner(env, typed.typ, inner),
         Type::FixedArray(inner_tid, size, _) => {
             ffi_inner(env, inner_tid, inner).map_any(|s| format!("[{}; {}]", s, size))
         }
@@ -0,0 +1,178 @@
+use crate::{
+    analysis::{
+        conversion_type::ConversionType, functions, imports::Imports, is_gpointer,
+        record_type::RecordType, rust_type::*, types::IsIncomplete,
+    },
+    config::{matchable::Matchable, GObject, PropertyGenerateFlags},
+    env::Env,
+    library::{self, Type},
+    nameutil,
+    version::Version,
+};
+use log::info;
+
+/// How the pointer to the C struct holding the fields is obtained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Owner {
+    /// Boxed record: read through `*const`, written through `to_glib_none_mut`.
+    Boxed,
+    /// Reference counted record: read-only.
+    Shared,
+    /// Class instance struct.
+    Object,
+    /// Record wrapped by value, accessors are always generated.
+    Direct,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    /// Copied as is, e.g. numbers or fixed-size arrays of numbers.
+    Direct,
+    /// Converted with `from_glib` and `to_glib`.
+    Scalar,
+    /// Pointer to a string, an object or a record, only readable.
+    Pointer,
+    /// Record embedded by value, only readable.
+    Embedded,
+}
+
+#[derive(Debug)]
+pub struct FieldAccessor {
+    pub field_name: String,
+    pub func_name: String,
+    pub typ: library::TypeId,
+    pub kind: FieldKind,
+    pub is_get: bool,
+    pub owner: Owner,
+    /// `ffi` type of the struct holding the field.
+    pub owner_ffi_type: String,
+    pub version: Option<Version>,
+    pub doc_hidden: bool,
+}
+
+impl FieldAccessor {
+    /// Whether the owner is a value with unique access to the struct,
+    /// so that setters take `&mut self`.
+    pub fn is_owned_by_value(&self) -> bool {
+        matches!(self.owner, Owner::Boxed | Owner::Direct)
+    }
+}
+
+pub fn analyze(
+    env: &Env,
+    fields: &[library::Field],
+    owner: Owner,
+    owner_c_type: &str,
+    obj: &GObject,
+    functions: &[functions::Info],
+    imports: &mut Imports,
+) -> Vec<FieldAccessor> {
+    let mut accessors = Vec::new();
+    if !obj.generate_field_accessors && owner != Owner::Direct {
+        return accessors;
+    }
+
+    for field in fields {
+        // The `ffi` struct is truncated at the first bit field or incomplete type
+        if field.bits.is_some() || field.is_incomplete(&env.library) {
+            break;
+        }
+        if field.private {
+            continue;
+        }
+        let configured_fields = obj.fields.matched(&field.name);
+        if !configured_fields.iter().all(|f| f.status.need_generate()) {
+            continue;
+        }
+        let kind = match field_kind(env, field) {
+            Some(kind) => kind,
+            None => {
+                info!(
+                    "Field `{}.{}` has unsupported type, skipping accessors",
+                    obj.name, field.name
+                );
+                continue;
+            }
+        };
+        if rust_type(env, field.typ).is_err() {
+            continue;
+        }
+
+        let version = configured_fields.iter().filter_map(|f| f.version).min();
+        let doc_hidden = configured_fields.iter().any(|f| f.doc_hidden);
+        let generate = configured_fields
+            .iter()
+            .filter_map(|f| f.generate)
+            .next()
+            .unwrap_or_else(PropertyGenerateFlags::all);
+        let writable =
+            owner != Owner::Shared && (kind == FieldKind::Direct || kind == FieldKind::Scalar);
+
+        let field_name = nameutil::mangle_keywords(&*field.name).into_owned();
+        for &is_get in &[true, false] {
+            if is_get && !generate.contains(PropertyGenerateFlags::GET)
+                || !is_get && (!writable || !generate.contains(PropertyGenerateFlags::SET))
+            {
+                continue;
+            }
+            let func_name = format!("{}_{}", if is_get { "get" } else { "set" }, field.name);
+            // Functions and properties of the type win over the accessors
+            if functions.iter().any(|f| f.codegen_name() == func_name) {
+                continue;
+            }
+            accessors.push(FieldAccessor {
+                field_name: field_name.clone(),
+                func_name,
+                typ: field.typ,
+                kind,
+                is_get,
+                owner,
+                owner_ffi_type: format!("{}::{}", env.main_sys_crate_name(), owner_c_type),
+                version,
+                doc_hidden,
+            });
+        }
+    }
+
+    if !accessors.is_empty() {
+        imports.add("glib::translate::*");
+        for accessor in &accessors {
+            if let Ok(s) = used_rust_type(env, accessor.typ, !accessor.is_get) {
+                imports.add_used_type_with_version(&s, accessor.version);
+            }
+        }
+    }
+
+    accessors
+}
+
+fn field_kind(env: &Env, field: &library::Field) -> Option<FieldKind> {
+    let c_type = field.c_type.as_ref()?;
+    let is_ptr = c_type.contains('*') || is_gpointer(c_type);
+    match *env.library.type_(field.typ) {
+        Type::Fundamental(library::Fundamental::Utf8)
+        | Type::Fundamental(library::Fundamental::Filename)
+        | Type::Class(..)
+        | Type::Interface(..)
+            if is_ptr =>
+        {
+            Some(FieldKind::Pointer)
+        }
+        Type::Record(..) if is_ptr => Some(FieldKind::Pointer),
+        Type::Record(ref record) if RecordType::of(record) != RecordType::Refcounted => {
+            Some(FieldKind::Embedded)
+        }
+        Type::Record(..) | Type::Function(..) => None,
+        Type::FixedArray(inner_tid, ..)
+            if ConversionType::of(env, inner_tid) == ConversionType::Direct =>
+        {
+            Some(FieldKind::Direct)
+        }
+        _ if is_ptr => None,
+        _ => match ConversionType::of(env, field.typ) {
+            ConversionType::Direct => Some(FieldKind::Direct),
+            ConversionType::Scalar => Some(FieldKind::Scalar),
+            _ => None,
+        },
+    }
+}
@@ -1,13 +1,18 @@
 use super::{
-    conversion_type::ConversionType, out_parameters::can_as_return,
-    override_string_type::override_string_type_parameter, ref_mode::RefMode, rust_type::rust_type,
+    c_type::is_mut_ptr,
+    conversion_type::ConversionType,
+    functions::{is_carray_with_record_values, is_carray_with_scalar_elements},
+    out_parameters::can_as_return,
+    override_string_type::override_string_type_parameter,
+    ref_mode::RefMode,
+    rust_type::rust_type,
 };
 use crate::{
     config::{self, parameter_matchable::ParameterMatchable},
     env::Env,
     library::{self, ParameterScope, TypeId},
     nameutil,
-    traits::IntoString,
+    traits::{IntoString, MaybeRefAs},
 };
 use std::collections::HashMap;
 
@@ -62,6 +67,33 @@ pub enum TransformationType {
         in_trait: bool,
         nullable: bool,
     },
+    ToGlibScalarArray {
+        name: String,
+    },
+    /// Copies the records into a temporary `Vec` of their C structs.
+    ToGlibRecordArray {
+        name: String,
+        nullable: bool,
+    },
+    /// The slice length is checked against `size` before the call.
+    ToGlibFixedArray {
+        name: String,
+    },
+    /// Builds a temporary `GHashTable` from a `HashMap` before the call.
+    ToGlibHashTable {
+        name: String,
+        key: TypeId,
+        value: TypeId,
+        transfer: library::Transfer,
+        nullable: bool,
+    },
+    ToGlibTyped {
+        name: String,
+        kind: library::TypedKind,
+        transfer: library::Transfer,
+        nullable: bool,
+        mut_ptr: bool,
+    },
     ToGlibBorrow,
     ToGlibUnknown {
         name: String,
@@ -78,14 +110,21 @@ pub enum TransformationType {
 impl TransformationType {
     pub fn is_to_glib(&self) -> bool {
         use self::TransformationType::*;
-        matches!(*self,
+        matches!(
+            *self,
             ToGlibDirect { .. }
-            | ToGlibScalar { .. }
-            | ToGlibPointer { .. }
-            | ToGlibBorrow
-            | ToGlibUnknown { .. }
-            | ToSome(_)
-            | IntoRaw(_))
+                | ToGlibScalar { .. }
+                | ToGlibPointer { .. }
+                | ToGlibScalarArray { .. }
+                | ToGlibRecordArray { .. }
+                | ToGlibFixedArray { .. }
+                | ToGlibHashTable { .. }
+                | ToGlibTyped { .. }
+                | ToGlibBorrow
+                | ToGlibUnknown { .. }
+                | ToSome(_)
+                | IntoRaw(_)
+        )
     }
 
     pub fn set_to_glib_extra(&mut self, to_glib_extra_: &str) {
@@ -276,7 +315,23 @@ pub fn analyze(
                 }
             };
 
+        let typed_kind = env
+            .library
+            .type_(typ)
+            .maybe_ref_as::<library::Typed>()
+            .map(|typed| typed.kind);
+        let hash_table = match *env.library.type_(typ) {
+            library::Type::HashTable(key, value) => Some((key, value)),
+            _ => None,
+        };
         let transformation_type = match ConversionType::of(env, typ) {
+            ConversionType::Pointer if typed_kind.is_some() => TransformationType::ToGlibTyped {
+                name,
+                kind: typed_kind.unwrap(),
+                transfer,
+                nullable: *nullable,
+                mut_ptr: is_mut_ptr(&par.c_type),
+            },
             ConversionType::Direct => {
                 if par.c_type != "GLib.Pid" {
                     TransformationType::ToGlibDirect { name }
@@ -285,6 +340,39 @@ pub fn analyze(
                 }
             }
             ConversionType::Scalar => TransformationType::ToGlibScalar { name, nullable },
+            ConversionType::Pointer
+                if par.direction == library::ParameterDirection::In
+                    && is_carray_with_scalar_elements(env, typ) =>
+            {
+                TransformationType::ToGlibScalarArray { name }
+            }
+            ConversionType::Pointer
+                if par.direction == library::ParameterDirection::In
+                    && is_carray_with_record_values(env, typ, &par.c_type, par.direction) =>
+            {
+                TransformationType::ToGlibRecordArray {
+                    name,
+                    nullable: *nullable,
+                }
+            }
+            ConversionType::Pointer
+                if par.direction == library::ParameterDirection::In
+                    && matches!(env.library.type_(typ), library::Type::FixedArray(..)) =>
+            {
+                TransformationType::ToGlibFixedArray { name }
+            }
+            ConversionType::Pointer
+                if par.direction == library::ParameterDirection::In && hash_table.is_some() =>
+            {
+                let (key, value) = hash_table.unwrap();
+                TransformationType::ToGlibHashTable {
+                    name,
+                    key,
+                    value,
+                    transfer,
+                    nullable: *nullable,
+                }
+            }
             ConversionType::Pointer => TransformationType::ToGlibPointer {
                 name,
                 instance_parameter: par.instance_parameter,
@@ -1,6 +1,5 @@
 /*
  * TODO: better heuristic (https://bugzilla.gnome.org/show_bug.cgi?id=623635#c5)
- * TODO: ProgressCallback types (not specific to async).
  * TODO: add annotation for methods like g_file_replace_contents_bytes_async where the finish
  * method has a different prefix.
  */
@@ -8,21 +7,25 @@
 use crate::{
     analysis::{
         bounds::{Bounds, CallbackInfo},
+        c_type::rustify_pointers,
+        conversion_type::ConversionType,
         function_parameters::{self, CParameter, Parameters, Transformation, TransformationType},
         imports::Imports,
-        is_gpointer,
+        is_gpointer, namespaces,
         out_parameters::{self, use_function_return_for_result},
+        record_type::RecordType,
         ref_mode::RefMode,
         return_value,
         rust_type::*,
         safety_assertion_mode::SafetyAssertionMode,
         signatures::{Signature, Signatures},
         trampolines::Trampoline,
+        variant_type,
     },
     config::{self, gobjects::GStatus},
     env::Env,
     library::{self, Function, FunctionKind, Nullable, Parameter, ParameterScope, Transfer, Type},
-    nameutil,
+    nameutil::{self, use_glib_type},
     traits::*,
     version::Version,
 };
@@ -66,6 +69,36 @@ pub struct AsyncFuture {
     pub success_parameters: String,
     pub error_parameters: String,
     pub assertion: SafetyAssertionMode,
+    /// Extra callback (like a progress one) forwarded to a `Stream` returned alongside the
+    /// future.
+    pub stream: Option<AsyncStream>,
+    /// Extra nullable callbacks which are just given `None` in the future variant.
+    pub ignored_callbacks: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AsyncStream {
+    pub callback_name: String,
+    pub nullable: bool,
+    /// Closure arguments, sent as is through the stream.
+    pub arguments: Vec<String>,
+    pub item_type: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum IteratorKind {
+    /// Calls the function until it returns `None`.
+    Next,
+    /// Calls the function with each index lower than the value returned by `length_func`.
+    Indexed { length_func: String },
+    /// Calls the future variant of the function until it returns `None` or an empty list.
+    Stream,
+}
+
+#[derive(Clone, Debug)]
+pub struct IteratorAdapter {
+    pub name: String,
+    pub kind: IteratorKind,
 }
 
 #[derive(Debug)]
@@ -94,6 +127,9 @@ pub struct Info {
     pub destroys: Vec<Trampoline>,
     pub remove_params: Vec<usize>,
     pub async_future: Option<AsyncFuture>,
+    /// `true` if this function finishes an async function (`glib:async-func`).
+    pub is_finish_func: bool,
+    pub iterator: Option<IteratorAdapter>,
 }
 
 impl Info {
@@ -107,7 +143,19 @@ impl Info {
             .rust_parameters
             .iter()
             .any(|param| param.typ.full_name(&env.library) == "Gio.AsyncResult");
-        self.name.ends_with("_finish") && has_async_result
+        (self.is_finish_func || self.name.ends_with("_finish")) && has_async_result
+    }
+
+    /// The struct returned by this function if it is generated.
+    pub fn generated_out_struct(&self, env: &Env) -> Option<&out_parameters::OutStruct> {
+        match self.visibility {
+            Visibility::Public | Visibility::Private
+                if self.status.need_generate() && !self.is_async_finish(env) =>
+            {
+                self.outs.out_struct.as_ref()
+            }
+            _ => None,
+        }
     }
 }
 
@@ -173,12 +221,180 @@ pub fn analyze<F: Borrow<library::Function>>(
             imports,
         );
         info.not_version = not_version;
+        info.iterator = analyze_iterator(env, type_tid, func, &info, &configured_functions);
+        if info.iterator.is_some() {
+            imports.add("std::boxed::Box as Box_");
+        }
         funcs.push(info);
     }
 
+    resolve_iterator_lengths(type_tid, &mut funcs);
+
     funcs
 }
 
+fn analyze_iterator(
+    env: &Env,
+    type_tid: library::TypeId,
+    func: &library::Function,
+    info: &Info,
+    configured_functions: &[&config::functions::Function],
+) -> Option<IteratorAdapter> {
+    let func_name = func.c_identifier.as_ref().unwrap_or(&func.name);
+    let stream = configured_functions.iter().any(|f| f.stream);
+    let iterator = configured_functions.iter().any(|f| f.iterator);
+    if !stream && !iterator {
+        return None;
+    }
+    if !info.status.need_generate() || info.visibility != Visibility::Public {
+        return None;
+    }
+    if stream && iterator {
+        warn_main!(
+            type_tid,
+            "`{}`: can't generate both an iterator and a stream",
+            func_name
+        );
+        return None;
+    }
+    // The adapters call the function repeatedly, so the parameters have to be copied into them.
+    let is_copy = |par: &&CParameter| {
+        par.instance_parameter
+            || par.name == "cancellable"
+            || matches!(
+                ConversionType::of(env, par.typ),
+                ConversionType::Direct | ConversionType::Scalar
+            )
+    };
+
+    if stream {
+        let async_future = match info.async_future {
+            Some(ref async_future) if async_future.stream.is_none() => async_future,
+            _ => {
+                warn_main!(
+                    type_tid,
+                    "`{}`: streams can only be generated for async functions with a future",
+                    func_name
+                );
+                return None;
+            }
+        };
+        let all_copy = info
+            .parameters
+            .rust_parameters
+            .iter()
+            .map(|par| &info.parameters.c_parameters[par.ind_c])
+            .filter(|par| {
+                par.name != "callback" && !async_future.ignored_callbacks.contains(&par.name)
+            })
+            .all(|par| is_copy(&par));
+        if !all_copy {
+            warn_main!(
+                type_tid,
+                "`{}`: streams are only supported for functions with plain parameters",
+                func_name
+            );
+            return None;
+        }
+        return Some(IteratorAdapter {
+            name: format!("{}_stream", func.name.trim_end_matches("_async")),
+            kind: IteratorKind::Stream,
+        });
+    }
+
+    let kind = match configured_functions
+        .iter()
+        .find_map(|f| f.iterator_length.as_ref())
+    {
+        Some(length_func) => IteratorKind::Indexed {
+            length_func: length_func.clone(),
+        },
+        None => IteratorKind::Next,
+    };
+    let non_instance_parameters = info
+        .parameters
+        .rust_parameters
+        .iter()
+        .map(|par| &info.parameters.c_parameters[par.ind_c])
+        .filter(|par| !par.instance_parameter)
+        .collect::<Vec<_>>();
+    let supported = func.kind == library::FunctionKind::Method
+        && !info.r#async
+        && match kind {
+            IteratorKind::Indexed { .. } => {
+                non_instance_parameters.len() == 1
+                    && matches!(
+                        *env.library.type_(non_instance_parameters[0].typ),
+                        Type::Fundamental(fund) if !fund.requires_conversion()
+                    )
+            }
+            _ => non_instance_parameters.iter().all(is_copy),
+        };
+    if !supported {
+        warn_main!(
+            type_tid,
+            "`{}`: iterators are only supported for sync methods with plain parameters",
+            func_name
+        );
+        return None;
+    }
+    Some(IteratorAdapter {
+        name: format!("{}_iter", info.codegen_name()),
+        kind,
+    })
+}
+
+/// Replaces the length function names of the indexed iterators by the name of their bindings.
+fn resolve_iterator_lengths(type_tid: library::TypeId, funcs: &mut [Info]) {
+    let lengths = funcs
+        .iter()
+        .map(|info| {
+            if let Some(IteratorAdapter {
+                kind: IteratorKind::Indexed { ref length_func },
+                ..
+            }) = info.iterator
+            {
+                let name = nameutil::mangle_keywords(&**length_func);
+                Some(
+                    funcs
+                        .iter()
+                        .find(|f| f.name == name && f.status.need_generate())
+                        .map(|f| f.codegen_name().to_owned())
+                        .ok_or_else(|| length_func.clone()),
+                )
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    for (info, length) in funcs.iter_mut().zip(lengths) {
+        match length {
+            Some(Ok(length)) => {
+                if let Some(IteratorAdapter {
+                    kind:
+                        IteratorKind::Indexed {
+                            ref mut length_func,
+                        },
+                    ..
+                }) = info.iterator
+                {
+                    *length_func = length;
+                }
+            }
+            Some(Err(length)) => {
+                warn_main!(
+                    type_tid,
+                    "`{}`: length function `{}` of the iterator not found",
+                    info.glib_name,
+                    length
+                );
+                info.iterator = None;
+            }
+            None => {}
+        }
+    }
+}
+
 fn fixup_gpointer_parameter(
     env: &Env,
     type_tid: library::TypeId,
@@ -310,9 +526,7 @@ fn analyze_callbacks(
                 "Wrong instance parameter in {}",
                 func.c_identifier.as_ref().unwrap()
             );
-            if let Ok(s) = used_rust_type(env, par.typ, !par.direction.is_out()) {
-                used_types.push(s);
-            }
+            used_types.extend(used_rust_types(env, par.typ, !par.direction.is_out()));
             let rust_type = env.library.type_(par.typ);
             let callback_info = if !*par.nullable || !rust_type.is_function() {
                 let (to_glib_extra, callback_info) = bounds.add_for_parameter(
@@ -479,6 +693,181 @@ fn analyze_callbacks(
     }
 }
 
+fn is_async_callback(par: &library::Parameter) -> bool {
+    par.scope == ParameterScope::Async && par.c_type == "GAsyncReadyCallback"
+}
+
+/// Handles the callbacks of an async function which aren't its `GAsyncReadyCallback`, like
+/// progress callbacks. Their user data is owned by the async callback's one, so they can be
+/// called until the async operation completes.
+fn analyze_async_callbacks(
+    env: &Env,
+    func: &library::Function,
+    type_tid: library::TypeId,
+    extra_callbacks: &[(usize, Option<CallbackInfo>)],
+    ret: &return_value::Info,
+    parameters: &mut Parameters,
+    params: &mut Vec<Parameter>,
+    to_glib_extras: &mut HashMap<usize, String>,
+    imports: &mut Imports,
+    callbacks: &mut Vec<Trampoline>,
+    async_future: &mut Option<AsyncFuture>,
+    configured_functions: &[&config::functions::Function],
+    disable_length_detect: bool,
+    in_trait: bool,
+    commented: &mut bool,
+) {
+    let func_name = func.c_identifier.as_ref().unwrap_or(&func.name);
+    let c_parameters = parameters
+        .c_parameters
+        .iter()
+        .enumerate()
+        .filter(|(_, par)| !par.instance_parameter)
+        .map(|(pos, par)| (par, pos))
+        .collect::<Vec<_>>();
+
+    let mut to_remove = Vec::new();
+    for (pos, callback_info) in extra_callbacks {
+        let par = &parameters.c_parameters[*pos];
+        if par.c_type == "GDestroyNotify" || par.destroy_index.is_some() {
+            warn_main!(
+                type_tid,
+                "`{}`: destroy notify callbacks aren't supported in async functions",
+                func_name
+            );
+            *commented = true;
+            return;
+        }
+        if par.scope.is_call() {
+            warn_main!(
+                type_tid,
+                "`{}`: callback `{}` can't be called during an async operation with a call scope",
+                func_name,
+                par.name
+            );
+            *commented = true;
+            return;
+        }
+        if par.scope.is_async() {
+            // Its trampoline would free the boxed closure owned by the async callback's user data
+            warn_main!(
+                type_tid,
+                "`{}`: only the `GAsyncReadyCallback` can have an async scope, not callback `{}`",
+                func_name,
+                par.name
+            );
+            *commented = true;
+            return;
+        }
+        let rust_type = env.library.type_(par.typ);
+        let callback = match analyze_callback(
+            func_name,
+            type_tid,
+            env,
+            par,
+            callback_info,
+            commented,
+            imports,
+            &c_parameters,
+            rust_type,
+        ) {
+            Some((callback, _)) => callback,
+            None => {
+                *commented = true;
+                return;
+            }
+        };
+        if callbacks
+            .iter()
+            .any(|c| c.user_data_index == callback.user_data_index)
+        {
+            warn_main!(
+                type_tid,
+                "`{}`: callbacks sharing the same user data aren't supported in async functions",
+                func_name
+            );
+            *commented = true;
+            return;
+        }
+        to_remove.push(callback.user_data_index);
+        callbacks.push(callback);
+    }
+
+    to_remove.sort_unstable();
+    for pos in to_remove.iter().rev() {
+        params.remove(*pos);
+    }
+    let extras = std::mem::take(to_glib_extras);
+    for (pos, extra) in extras {
+        if !to_remove.contains(&pos) {
+            let shift = to_remove.iter().filter(|removed| **removed < pos).count();
+            to_glib_extras.insert(pos - shift, extra);
+        }
+    }
+    *parameters = function_parameters::analyze(
+        env,
+        params,
+        configured_functions,
+        disable_length_detect,
+        true,
+        in_trait,
+    );
+    parameters.analyze_return(env, &ret.parameter);
+
+    if let Some(mut future) = async_future.take() {
+        for callback in callbacks.iter() {
+            let arguments = callback
+                .parameters
+                .transformations
+                .iter()
+                .filter(|par| {
+                    par.name != "this"
+                        && !callback.parameters.c_parameters[par.ind_c].is_real_gpointer(env)
+                })
+                .collect::<Vec<_>>();
+            let item_types = arguments
+                .iter()
+                .map(|par| match *env.library.type_(par.typ) {
+                    Type::Fundamental(fund)
+                        if !fund.requires_conversion()
+                            || fund == library::Fundamental::Boolean
+                            || fund == library::Fundamental::UniChar =>
+                    {
+                        rust_type(env, par.typ).ok()
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            match item_types {
+                Some(item_types) if future.stream.is_none() && callback.ret.c_type == "void" => {
+                    let item_type = if item_types.len() == 1 {
+                        item_types[0].clone()
+                    } else {
+                        format!("({})", item_types.join(", "))
+                    };
+                    future.stream = Some(AsyncStream {
+                        callback_name: callback.name.clone(),
+                        nullable: *callback.nullable,
+                        arguments: arguments.iter().map(|par| par.name.clone()).collect(),
+                        item_type,
+                    });
+                }
+                _ if *callback.nullable => future.ignored_callbacks.push(callback.name.clone()),
+                _ => {
+                    warn_main!(
+                        type_tid,
+                        "`{}`: no future generated, callback `{}` can't be provided from it",
+                        func_name,
+                        callback.name
+                    );
+                    return;
+                }
+            }
+        }
+        *async_future = Some(future);
+    }
+}
+
 fn analyze_function(
     env: &Env,
     obj: &config::gobjects::GObject,
@@ -491,14 +880,11 @@ fn analyze_function(
     configured_functions: &[&config::functions::Function],
     imports: &mut Imports,
 ) -> Info {
-    let r#async = func.parameters.iter().any(|parameter| {
-        parameter.scope == ParameterScope::Async && parameter.c_type == "GAsyncReadyCallback"
+    let r#async = func.parameters.iter().any(is_async_callback);
+    // For async functions, the `GAsyncReadyCallback` isn't taken into account here.
+    let has_callback_parameter = func.parameters.iter().any(|par| {
+        env.library.type_(par.typ).is_function() && !(r#async && is_async_callback(par))
     });
-    let has_callback_parameter = !r#async
-        && func
-            .parameters
-            .iter()
-            .any(|par| env.library.type_(par.typ).is_function());
     let concurrency = match env.library.type_(type_tid) {
         library::Type::Class(_) | library::Type::Interface(_) | library::Type::Record(_) => {
             obj.concurrency
@@ -606,7 +992,9 @@ fn analyze_function(
     let mut user_data_indexes: HashSet<usize> = HashSet::new();
 
     if status.need_generate() {
-        if !has_callback_parameter {
+        if r#async || !has_callback_parameter {
+            // Callbacks given alongside the async one, with their position.
+            let mut extra_callbacks = Vec::new();
             for (pos, par) in parameters.c_parameters.iter().enumerate() {
                 // FIXME: It'd be better if we assumed that user data wasn't gpointer all the time so
                 //        we could handle it more generically.
@@ -618,7 +1006,7 @@ fn analyze_function(
                     "Wrong instance parameter in {}",
                     func.c_identifier.as_ref().unwrap()
                 );
-                if let Ok(s) = used_rust_type(env, par.typ, !par.direction.is_out()) {
+                for s in used_rust_types(env, par.typ, !par.direction.is_out()) {
                     if !s.ends_with("GString") || par.c_type == "gchar***" {
                         used_types.push(s);
                     }
@@ -635,6 +1023,25 @@ fn analyze_function(
                     to_glib_extras.insert(pos, to_glib_extra);
                 }
 
+                if r#async
+                    && par.c_type != "GAsyncReadyCallback"
+                    && env.library.type_(par.typ).is_function()
+                {
+                    commented |= parameter_rust_type(
+                        env,
+                        par.typ,
+                        par.direction,
+                        Nullable(false),
+                        RefMode::None,
+                        par.scope,
+                    )
+                    .is_err();
+                    // Nullable callbacks don't get a bound.
+                    let callback_info = if *par.nullable { None } else { callback_info };
+                    extra_callbacks.push((pos, callback_info));
+                    continue;
+                }
+
                 analyze_async(
                     env,
                     func,
@@ -666,6 +1073,25 @@ fn analyze_function(
             if r#async && trampoline.is_none() {
                 commented = true;
             }
+            if !extra_callbacks.is_empty() {
+                analyze_async_callbacks(
+                    env,
+                    func,
+                    type_tid,
+                    &extra_callbacks,
+                    &ret,
+                    &mut parameters,
+                    &mut params,
+                    &mut to_glib_extras,
+                    imports,
+                    &mut callbacks,
+                    &mut async_future,
+                    configured_functions,
+                    disable_length_detect,
+                    in_trait,
+                    &mut commented,
+                );
+            }
         } else {
             analyze_callbacks(
                 env,
@@ -691,7 +1117,7 @@ fn analyze_function(
     }
 
     for par in &parameters.rust_parameters {
-        // Disallow fundamental arrays without length
+        // Disallow arrays without length that need one
         let is_len_for_par = |t: &&Transformation| {
             if let TransformationType::Length { ref array_name, .. } = t.transformation_type {
                 array_name == &par.name
@@ -699,7 +1125,15 @@ fn analyze_function(
                 false
             }
         };
-        if is_carray_with_direct_elements(env, par.typ)
+        let c_par = &parameters.c_parameters[par.ind_c];
+        if (is_carray_with_record_values(env, c_par.typ, &c_par.c_type, c_par.direction)
+            || is_carray_with_scalar_elements(env, c_par.typ))
+            && c_par.transfer != library::Transfer::None
+        {
+            // The array is passed as a temporary `Vec` which can't be given away
+            commented = true;
+        }
+        if is_carray_needing_length(env, c_par.typ, &c_par.c_type, c_par.direction)
             && parameters
                 .transformations
                 .iter()
@@ -710,7 +1144,20 @@ fn analyze_function(
         }
     }
 
-    let (outs, unsupported_outs) = out_parameters::analyze(
+    // The caller can't keep ownership of the entries of a table it gives away.
+    if parameters.transformations.iter().any(|trans| {
+        matches!(
+            trans.transformation_type,
+            TransformationType::ToGlibHashTable {
+                transfer: library::Transfer::Container,
+                ..
+            }
+        )
+    }) {
+        commented = true;
+    }
+
+    let (mut outs, unsupported_outs) = out_parameters::analyze(
         env,
         func,
         &parameters.c_parameters,
@@ -718,15 +1165,34 @@ fn analyze_function(
         configured_functions,
     );
     if unsupported_outs {
-        warn_main!(
-            type_tid,
-            "Function {} has unsupported outs",
-            func.c_identifier.as_ref().unwrap_or(&func.name)
-        );
+        let func_name = func.c_identifier.as_ref().unwrap_or(&func.name);
+        for par in func
+            .parameters
+            .iter()
+            .filter(|par| par.direction == library::ParameterDirection::Out && par.caller_allocates)
+        {
+            if let Some(error) = out_parameters::caller_allocation_error(env, par.typ) {
+                warn_main!(
+                    type_tid,
+                    "Function {} has unsupported caller-allocated out `{}`: {}",
+                    func_name,
+                    par.name,
+                    error
+                );
+            }
+        }
+        warn_main!(type_tid, "Function {} has unsupported outs", func_name);
         commented = true;
     } else if status.need_generate() && !commented {
         if !outs.is_empty() {
             out_parameters::analyze_imports(env, &func.parameters, imports);
+            outs.out_struct = out_parameters::analyze_out_struct(
+                env,
+                func,
+                type_tid,
+                &outs,
+                configured_functions,
+            );
         }
         if let Some(AsyncTrampoline {
             ref output_params, ..
@@ -743,14 +1209,10 @@ fn analyze_function(
 
         if let Some(ref trampoline) = trampoline {
             for par in &trampoline.output_params {
-                if let Ok(s) = used_rust_type(env, par.typ, false) {
-                    used_types.push(s);
-                }
+                used_types.extend(used_rust_types(env, par.typ, false));
             }
             if let Some(ref par) = trampoline.ffi_ret {
-                if let Ok(s) = used_rust_type(env, par.typ, false) {
-                    used_types.push(s);
-                }
+                used_types.extend(used_rust_types(env, par.typ, false));
             }
         }
     }
@@ -771,6 +1233,20 @@ fn analyze_function(
         }
 
         imports.add_used_types(&used_types);
+        if func
+            .parameters
+            .iter()
+            .map(|p| p.typ)
+            .chain(std::iter::once(func.ret.typ))
+            .any(|tid| matches!(env.library.type_(tid), library::Type::HashTable(..)))
+        {
+            imports.add("std::collections::HashMap");
+        }
+        for par in func.parameters.iter().chain(std::iter::once(&func.ret)) {
+            if let library::Type::Typed(ref typed) = *env.library.type_(par.typ) {
+                add_typed_imports(env, typed, par.direction, imports);
+            }
+        }
         if ret.base_tid.is_some() {
             imports.add("glib::object::Cast");
         }
@@ -815,6 +1291,28 @@ fn analyze_function(
         callbacks,
         destroys,
         remove_params: cross_user_data_check.values().cloned().collect::<Vec<_>>(),
+        is_finish_func: is_finish_function(env, func),
+        iterator: None,
+    }
+}
+
+/// Imports the traits and types used by the conversion of a typed `GVariant` or `GValue`.
+fn add_typed_imports(
+    env: &Env,
+    typed: &library::Typed,
+    direction: library::ParameterDirection,
+    imports: &mut Imports,
+) {
+    let (wrapper, to_trait) = match typed.kind {
+        library::TypedKind::Variant => ("Variant", "ToVariant"),
+        library::TypedKind::Value => ("Value", "ToValue"),
+    };
+    if direction == library::ParameterDirection::In {
+        imports.add(&use_glib_type(env, to_trait));
+    }
+    imports.add_used_type(&use_glib_type(env, wrapper));
+    if variant_type::typed_rust_type(typed, "Variant").contains("HashMap<") {
+        imports.add("std::collections::HashMap");
     }
 }
 
@@ -835,6 +1333,67 @@ pub fn is_carray_with_direct_elements(env: &Env, typ: library::TypeId) -> bool {
     }
 }
 
+pub fn is_carray_with_scalar_elements(env: &Env, typ: library::TypeId) -> bool {
+    match *env.library.type_(typ) {
+        Type::CArray(inner_tid) => ConversionType::of(env, inner_tid) == ConversionType::Scalar,
+        _ => false,
+    }
+}
+
+/// Checks if a C array of records holds the records themselves instead of
+/// pointers to them, e.g. a returned `GdkRGBA*` or an out `GdkRGBA**`.
+pub fn is_carray_with_record_values(
+    env: &Env,
+    typ: library::TypeId,
+    c_type: &str,
+    direction: library::ParameterDirection,
+) -> bool {
+    match *env.library.type_(typ) {
+        Type::CArray(inner_tid) if matches!(env.library.type_(inner_tid), Type::Record(..)) => {
+            let depth = rustify_pointers(c_type).0.split_whitespace().count();
+            depth == if direction.is_out() { 2 } else { 1 }
+        }
+        _ => false,
+    }
+}
+
+/// Checks if a C array of record values gives away the ownership of its
+/// elements. Only plain C structs can be taken out of such an array, the
+/// others would have to be freed in place.
+pub fn is_carray_with_owned_record_values(
+    env: &Env,
+    typ: library::TypeId,
+    c_type: &str,
+    direction: library::ParameterDirection,
+    transfer: library::Transfer,
+) -> bool {
+    match *env.library.type_(typ) {
+        Type::CArray(inner_tid) if transfer == library::Transfer::Full => {
+            match *env.library.type_(inner_tid) {
+                Type::Record(ref record) => {
+                    RecordType::of(record) != RecordType::Direct
+                        && is_carray_with_record_values(env, typ, c_type, direction)
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Checks if a C array is converted element by element and so can't be
+/// handled without a known length.
+pub fn is_carray_needing_length(
+    env: &Env,
+    typ: library::TypeId,
+    c_type: &str,
+    direction: library::ParameterDirection,
+) -> bool {
+    is_carray_with_direct_elements(env, typ)
+        || is_carray_with_scalar_elements(env, typ)
+        || is_carray_with_record_values(env, typ, c_type, direction)
+}
+
 fn analyze_async(
     env: &Env,
     func: &library::Function,
@@ -856,11 +1415,12 @@ fn analyze_async(
     {
         // Checks for /*Ignored*/ or other error comments
         *commented |= callback_type.contains("/*");
-        let func_name = func.c_identifier.as_ref().unwrap();
-        let finish_func_name = finish_function_name(func_name);
+        let finish_func_name = finish_function_c_identifier(env, func);
         let mut output_params = vec![];
         let mut ffi_ret = None;
-        if let Some(function) = find_function(env, &finish_func_name) {
+        let mut finish_func_path = ffi_function_path(env, namespaces::MAIN, &finish_func_name);
+        if let Some((ns_id, function)) = find_function(env, &finish_func_name) {
+            finish_func_path = ffi_function_path(env, ns_id, &finish_func_name);
             if use_function_return_for_result(
                 env,
                 function.ret.typ,
@@ -876,6 +1436,13 @@ fn analyze_async(
                     param.name = nameutil::mangle_keywords(&*param.name).into_owned();
                 }
             }
+        } else if let Some(ref finish_func) = func.finish_func {
+            warn_main!(
+                type_tid,
+                "Finish function `{}` of async function `{}` not found",
+                finish_func,
+                func.name
+            );
         }
         if trampoline.is_some() || async_future.is_some() {
             warn_main!(
@@ -909,7 +1476,7 @@ fn analyze_async(
         *trampoline = Some(AsyncTrampoline {
             is_method,
             name: format!("{}_trampoline", func.name),
-            finish_func_name: format!("{}::{}", env.main_sys_crate_name(), finish_func_name),
+            finish_func_name: finish_func_path,
             callback_type,
             bound_name,
             output_params,
@@ -928,6 +1495,8 @@ fn analyze_async(
                     // need to do it twice.
                     _ => SafetyAssertionMode::Skip,
                 },
+                stream: None,
+                ignored_callbacks: Vec::new(),
             });
         }
         true
@@ -1116,36 +1685,102 @@ fn analyze_callback(
     }
 }
 
-pub fn find_function<'a>(env: &'a Env, c_identifier: &str) -> Option<&'a Function> {
+/// Looks for the function with the given C identifier in the whole library, starting with the
+/// current namespace. Returns it along with the ID of its namespace.
+pub fn find_function<'a>(env: &'a Env, c_identifier: &str) -> Option<(u16, &'a Function)> {
     let find = |functions: &'a [Function]| -> Option<&'a Function> {
-        for function in functions {
-            if let Some(ref func_c_identifier) = function.c_identifier {
-                if func_c_identifier == c_identifier {
-                    return Some(function);
-                }
-            }
-        }
-        None
+        functions
+            .iter()
+            .find(|function| function.c_identifier.as_deref() == Some(c_identifier))
+    };
+    let find_in_namespace = |namespace: &'a library::Namespace| -> Option<&'a Function> {
+        find(&namespace.functions).or_else(|| {
+            namespace.types.iter().flatten().find_map(|typ| match *typ {
+                Type::Class(ref class) => find(&class.functions),
+                Type::Interface(ref interface) => find(&interface.functions),
+                Type::Record(ref record) => find(&record.functions),
+                Type::Enumeration(ref enum_) => find(&enum_.functions),
+                Type::Bitfield(ref bitfield) => find(&bitfield.functions),
+                _ => None,
+            })
+        })
     };
 
-    if let Some(index) = env.library.find_namespace(&env.config.library_name) {
-        let namespace = env.library.namespace(index);
-        if let Some(f) = find(&namespace.functions) {
-            return Some(f);
+    let main_ns_id = env.library.find_namespace(&env.config.library_name);
+    main_ns_id
+        .into_iter()
+        .chain((0..env.library.namespaces.len() as u16).filter(|ns_id| Some(*ns_id) != main_ns_id))
+        .find_map(|ns_id| {
+            find_in_namespace(env.library.namespace(ns_id)).map(|function| (ns_id, function))
+        })
+}
+
+/// Returns the path of the FFI function with the given C identifier from the given namespace.
+fn ffi_function_path(env: &Env, ns_id: u16, c_identifier: &str) -> String {
+    if ns_id == namespaces::MAIN {
+        format!("{}::{}", env.main_sys_crate_name(), c_identifier)
+    } else {
+        let ns = &env.namespaces[ns_id];
+        format!(
+            "{}::{}::{}",
+            ns.higher_crate_name, ns.sys_crate_name, c_identifier
+        )
+    }
+}
+
+/// Returns the C identifier of the finish function of the given async function, either from
+/// its `glib:finish-func` attribute or guessed from its name.
+///
+/// The finish function is looked for alongside the async function first, then, if the attribute
+/// is a C identifier, on other types and in the global namespace.
+pub fn finish_function_c_identifier(env: &Env, func: &Function) -> String {
+    let func_name = func.c_identifier.as_ref().unwrap();
+    match func.finish_func {
+        Some(ref finish_func) => find_sibling_function(env, func_name, finish_func)
+            .and_then(|function| function.c_identifier.clone())
+            .unwrap_or_else(|| match find_function(env, finish_func) {
+                Some(_) => finish_func.clone(),
+                None => finish_function_name(func_name),
+            }),
+        None => finish_function_name(func_name),
+    }
+}
+
+/// Finds the function named `name` defined alongside the function with the given C identifier.
+fn find_sibling_function<'a>(env: &'a Env, c_identifier: &str, name: &str) -> Option<&'a Function> {
+    let find = |functions: &'a [Function]| -> Option<&'a Function> {
+        if functions
+            .iter()
+            .any(|f| f.c_identifier.as_deref() == Some(c_identifier))
+        {
+            functions.iter().find(|f| f.name == name)
+        } else {
+            None
         }
-        for typ in &namespace.types {
-            if let Some(Type::Class(ref class)) = *typ {
-                if let Some(f) = find(&class.functions) {
-                    return Some(f);
-                }
-            } else if let Some(Type::Interface(ref interface)) = *typ {
-                if let Some(f) = find(&interface.functions) {
-                    return Some(f);
-                }
-            }
+    };
+
+    let index = env.library.find_namespace(&env.config.library_name)?;
+    let namespace = env.library.namespace(index);
+    find(&namespace.functions).or_else(|| {
+        namespace.types.iter().flatten().find_map(|typ| match *typ {
+            Type::Class(ref class) => find(&class.functions),
+            Type::Interface(ref interface) => find(&interface.functions),
+            Type::Record(ref record) => find(&record.functions),
+            _ => None,
+        })
+    })
+}
+
+/// Whether the given function finishes an async function, sync functions also naming their
+/// async function in `glib:async-func`.
+fn is_finish_function(env: &Env, func: &Function) -> bool {
+    match (&func.async_func, &func.c_identifier) {
+        (Some(async_func), Some(c_identifier)) => {
+            find_sibling_function(env, c_identifier, async_func)
+                .map_or(true, |f| f.sync_func.as_ref() != Some(&func.name))
         }
+        (async_func, _) => async_func.is_some(),
     }
-    None
 }
 
 /// Given async function name tries to guess the name of finish function.
@@ -15,6 +15,7 @@ pub mod constants;
 pub mod conversion_type;
 pub mod enums;
 pub mod ffi_type;
+pub mod field_accessors;
 pub mod flags;
 pub mod function_parameters;
 pub mod functions;
@@ -40,6 +41,7 @@ pub mod symbols;
 pub mod trampoline_parameters;
 pub mod trampolines;
 pub mod types;
+pub mod variant_type;
 
 #[derive(Debug, Default)]
 pub struct Analysis {
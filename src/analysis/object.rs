@@ -25,6 +25,7 @@ pub struct Info {
     pub builder_properties: Vec<properties::Property>,
     pub builder_postprocess: Option<String>,
     pub child_properties: ChildProperties,
+    pub fields: Vec<field_accessors::FieldAccessor>,
     pub signatures: Signatures,
 }
 
@@ -134,6 +135,16 @@ pub fn class(env: &Env, obj: &GObject, deps: &[library::TypeId]) -> Option<Info>
     let child_properties =
         child_properties::analyze(env, obj.child_properties.as_ref(), class_tid, &mut imports);
 
+    let fields = field_accessors::analyze(
+        env,
+        &klass.fields,
+        field_accessors::Owner::Object,
+        &klass.c_type,
+        obj,
+        &functions,
+        &mut imports,
+    );
+
     let has_methods = functions
         .iter()
         .any(|f| f.kind == library::FunctionKind::Method && f.status.need_generate());
@@ -176,7 +187,7 @@ pub fn class(env: &Env, obj: &GObject, deps: &[library::TypeId]) -> Option<Info>
 
     // patch up trait methods in the symbol table
     if generate_trait {
-        let mut symbols = env.symbols.borrow_mut();
+        let mut symbols = env.symbols.write().unwrap();
         for func in base.methods() {
             if let Some(symbol) = symbols.by_c_name_mut(&func.glib_name) {
                 symbol.make_trait_method(&trait_name);
@@ -205,6 +216,7 @@ pub fn class(env: &Env, obj: &GObject, deps: &[library::TypeId]) -> Option<Info>
         builder_properties,
         builder_postprocess: obj.builder_postprocess.clone(),
         child_properties,
+        fields,
         signatures,
     };
 
@@ -1,9 +1,15 @@
 use crate::{
     analysis::{
-        conversion_type::ConversionType, function_parameters::CParameter,
-        functions::is_carray_with_direct_elements, imports::Imports, ref_mode::RefMode,
-        return_value, rust_type::parameter_rust_type,
+        conversion_type::ConversionType,
+        function_parameters::CParameter,
+        functions::{is_carray_needing_length, is_carray_with_owned_record_values},
+        imports::Imports,
+        record_type::RecordType,
+        ref_mode::RefMode,
+        return_value,
+        rust_type::parameter_rust_type,
     },
+    case::CaseExt,
     config,
     env::Env,
     library::*,
@@ -32,6 +38,15 @@ impl Default for Mode {
 pub struct Info {
     pub mode: Mode,
     pub params: Vec<Parameter>,
+    pub out_struct: Option<OutStruct>,
+}
+
+/// Named struct returned instead of a tuple of the outputs.
+#[derive(Clone, Debug)]
+pub struct OutStruct {
+    pub name: String,
+    /// Field names with the index of their output in `Info::params`
+    pub fields: Vec<(String, usize)>,
 }
 
 impl Info {
@@ -84,6 +99,10 @@ pub fn analyze(
             if let Some(c_par) = func_c_params.iter().find(|c_par| c_par.name == par.name) {
                 par.typ = c_par.typ;
             }
+            if par.caller_allocates {
+                // Filled in place, so there's always a value
+                par.nullable = Nullable(false);
+            }
             info.params.push(par);
         } else {
             unsupported_outs = true;
@@ -108,11 +127,92 @@ pub fn analyze(
     (info, unsupported_outs)
 }
 
+pub fn analyze_out_struct(
+    env: &Env,
+    func: &Function,
+    type_tid: TypeId,
+    info: &Info,
+    configured_functions: &[&config::functions::Function],
+) -> Option<OutStruct> {
+    let fields: Vec<(String, usize)> = info
+        .params
+        .iter()
+        .enumerate()
+        .filter(|&(_, par)| !par.is_error && !is_array_length(func, info, par))
+        .map(|(pos, par)| {
+            // The actual return value is inserted with an empty name
+            let name = if par.name.is_empty() {
+                "result".to_owned()
+            } else {
+                par.name.clone()
+            };
+            (name, pos)
+        })
+        .collect();
+    if fields.is_empty() {
+        return None;
+    }
+
+    let use_out_struct = configured_functions
+        .iter()
+        .filter_map(|f| f.out_struct)
+        .next()
+        .unwrap_or(matches!(
+            env.config.out_struct_threshold,
+            Some(threshold) if fields.len() >= threshold
+        ));
+    if !use_out_struct {
+        return None;
+    }
+
+    let owner_name = if type_tid == TypeId::tid_none() {
+        String::new()
+    } else {
+        match *env.library.type_(type_tid) {
+            Type::Class(..) | Type::Interface(..) | Type::Record(..) => {
+                env.library.type_(type_tid).get_name()
+            }
+            _ => {
+                error!(
+                    "Out struct of function `{}` is only supported for objects, records \
+                     and global functions",
+                    func.name
+                );
+                return None;
+            }
+        }
+    };
+    let name = configured_functions
+        .iter()
+        .filter_map(|f| f.out_struct_name.clone())
+        .next()
+        .unwrap_or_else(|| {
+            let func_name = func.name.strip_prefix("get_").unwrap_or(&func.name);
+            format!("{}{}", owner_name, func_name.to_camel())
+        });
+
+    Some(OutStruct { name, fields })
+}
+
+/// Whether `par` is the length of an array output, these aren't returned.
+fn is_array_length(func: &Function, info: &Info, par: &Parameter) -> bool {
+    let pos = match func
+        .parameters
+        .iter()
+        .position(|p| nameutil::mangle_keywords(&*p.name) == par.name)
+    {
+        Some(pos) => pos as u32,
+        None => return false,
+    };
+    info.params.iter().any(|p| p.array_length == Some(pos))
+}
+
 pub fn analyze_imports(env: &Env, parameters: &[Parameter], imports: &mut Imports) {
     for par in parameters {
         if par.direction == ParameterDirection::Out {
             match *env.library.type_(par.typ) {
                 Type::Bitfield(..) | Type::Enumeration(..) => imports.add("std::mem"),
+                Type::FixedArray(..) if par.caller_allocates => imports.add("std::mem"),
                 Type::Fundamental(fund)
                     if fund != Fundamental::Utf8
                         && fund != Fundamental::OsString
@@ -136,8 +236,22 @@ pub fn can_as_return(env: &Env, par: &Parameter) -> bool {
         Direct => true,
         Scalar => true,
         Pointer => {
-            // Disallow fundamental arrays without length
-            if is_carray_with_direct_elements(env, par.typ) && par.array_length.is_none() {
+            // Disallow arrays without length that need one
+            if is_carray_needing_length(env, par.typ, &par.c_type, par.direction)
+                && par.array_length.is_none()
+            {
+                return false;
+            }
+            if par.caller_allocates && !can_caller_allocate(env, par.typ) {
+                return false;
+            }
+            if is_carray_with_owned_record_values(
+                env,
+                par.typ,
+                &par.c_type,
+                par.direction,
+                par.transfer,
+            ) {
                 return false;
             }
 
@@ -156,6 +270,49 @@ pub fn can_as_return(env: &Env, par: &Parameter) -> bool {
     }
 }
 
+fn can_caller_allocate(env: &Env, typ: TypeId) -> bool {
+    caller_allocation_error(env, typ).is_none()
+}
+
+/// Tells why the memory of a caller-allocated out of type `typ` can't be set up
+/// before the call, this needs the size of the C type and an `Uninitialized`
+/// implementation.
+pub fn caller_allocation_error(env: &Env, typ: TypeId) -> Option<String> {
+    let name = typ.full_name(&env.library);
+    match *env.library.type_(typ) {
+        Type::Record(ref record) => {
+            if record.disguised || record.standalone_boxed || record.fields.is_empty() {
+                return Some(format!("the size of `{}` isn't known", name));
+            }
+            match RecordType::of(record) {
+                RecordType::Direct => None,
+                RecordType::Refcounted => Some(format!(
+                    "refcounted `{}` can only be allocated by its own constructors",
+                    name
+                )),
+                RecordType::AutoBoxed | RecordType::Boxed => {
+                    let configured = env.config.objects.get(&name).map_or(false, |obj| {
+                        obj.init_function_expression.is_some()
+                            && obj.clear_function_expression.is_some()
+                    });
+                    if configured {
+                        None
+                    } else {
+                        Some(format!(
+                            "boxed `{}` needs `init_function_expression` and \
+                             `clear_function_expression` to be configured",
+                            name
+                        ))
+                    }
+                }
+            }
+        }
+        Type::Union(..) | Type::FixedArray(..) => None,
+        Type::Alias(ref alias) => caller_allocation_error(env, alias.typ),
+        _ => Some(format!("`{}` can't be allocated by the caller", name)),
+    }
+}
+
 pub fn use_return_value_for_result(
     env: &Env,
     ret: &return_value::Info,
@@ -18,9 +18,11 @@ pub struct Info {
     pub base: InfoBase,
     pub glib_get_type: Option<(String, Option<Version>)>,
     pub is_boxed: bool,
+    pub is_direct: bool,
     pub derives: Derives,
     pub init_function_expression: Option<String>,
     pub clear_function_expression: Option<String>,
+    pub fields: Vec<field_accessors::FieldAccessor>,
 }
 
 impl Deref for Info {
@@ -77,7 +79,9 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
 
     let record: &library::Record = type_.maybe_ref()?;
 
-    let is_boxed = RecordType::of(&record) == RecordType::AutoBoxed;
+    let record_type = RecordType::of(record);
+    let is_boxed = record_type == RecordType::AutoBoxed;
+    let is_direct = record_type == RecordType::Direct;
 
     let mut imports = Imports::with_defined(&env.library, &name);
 
@@ -111,6 +115,12 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
 
     let mut derives = if let Some(ref derives) = obj.derives {
         derives.clone()
+    } else if is_direct {
+        // The `ffi` struct only implements `Debug` besides `Clone` and `Copy`
+        vec![Derive {
+            names: vec!["Debug".into()],
+            cfg_condition: None,
+        }]
     } else {
         let derives = vec![Derive {
             names: vec![
@@ -142,8 +152,41 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
         }
     }
 
+    if is_direct {
+        // Direct records are always passed around by value
+        let names = ["Clone".to_owned(), "Copy".to_owned()];
+        match derives.iter_mut().find(|d| d.cfg_condition.is_none()) {
+            Some(derive) => {
+                derive.names.retain(|n| !names.contains(n));
+                derive.names.splice(0..0, names.iter().cloned());
+            }
+            None => derives.insert(
+                0,
+                Derive {
+                    names: names.to_vec(),
+                    cfg_condition: None,
+                },
+            ),
+        }
+    }
+
     special_functions::analyze_imports(&specials, &mut imports);
 
+    let owner = match record_type {
+        RecordType::Refcounted => field_accessors::Owner::Shared,
+        RecordType::Direct => field_accessors::Owner::Direct,
+        _ => field_accessors::Owner::Boxed,
+    };
+    let fields = field_accessors::analyze(
+        env,
+        &record.fields,
+        owner,
+        &record.c_type,
+        obj,
+        &functions,
+        &mut imports,
+    );
+
     let glib_get_type = if let Some(ref glib_get_type) = record.glib_get_type {
         let configured_functions = obj.functions.matched("get_type");
         let get_type_version = configured_functions
@@ -159,7 +202,10 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
 
     // Check if we have to make use of the GType and the generic
     // boxed functions.
-    if !is_shared
+    if is_direct {
+        imports.add("glib::translate::*");
+        imports.add("std::mem");
+    } else if !is_shared
         && (!specials.has_trait(special_functions::Type::Copy)
             || !specials.has_trait(special_functions::Type::Free))
     {
@@ -202,8 +248,10 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
         glib_get_type,
         derives,
         is_boxed,
+        is_direct,
         init_function_expression: obj.init_function_expression.clone(),
         clear_function_expression: obj.clear_function_expression.clone(),
+        fields,
     };
 
     Some(info)
@@ -9,12 +9,21 @@ pub enum RecordType {
     Boxed,
     /// Referencecounted record
     Refcounted,
-    //TODO: detect and generate direct records
-    //Direct,
+    /// Plain C struct wrapped by value, without memory management functions
+    Direct,
 }
 
 impl RecordType {
     pub fn of(record: &library::Record) -> RecordType {
+        if record.direct {
+            return RecordType::Direct;
+        }
+
+        if record.standalone_boxed {
+            // Only the GType is known for those, so let's always go through it.
+            return RecordType::AutoBoxed;
+        }
+
         let mut has_copy = false;
         let mut has_free = false;
         let mut has_ref = false;
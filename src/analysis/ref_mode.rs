@@ -1,4 +1,4 @@
-use super::{c_type::is_mut_ptr, record_type::RecordType};
+use super::{c_type::is_mut_ptr, record_type::RecordType, variant_type};
 use crate::{config::gobjects::GObject, env, library};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -42,7 +42,9 @@ impl RefMode {
             | List(..)
             | SList(..)
             | PtrArray(..)
-            | CArray(..) => {
+            | CArray(..)
+            | FixedArray(..)
+            | HashTable(..) => {
                 if direction == library::ParameterDirection::In {
                     RefMode::ByRef
                 } else {
@@ -67,6 +69,15 @@ impl RefMode {
                     RefMode::None
                 }
             }
+            Typed(ref typed) => {
+                if direction == library::ParameterDirection::In
+                    && !variant_type::is_copy(&variant_type::typed_rust_type(typed, "Variant"))
+                {
+                    RefMode::ByRef
+                } else {
+                    RefMode::None
+                }
+            }
             Alias(ref alias) => RefMode::of(env, alias.typ, direction),
             _ => RefMode::None,
         }
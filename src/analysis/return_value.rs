@@ -1,7 +1,14 @@
 use crate::{
     analysis::{
-        imports::Imports, namespaces, override_string_type::override_string_type_return,
-        ref_mode::RefMode, rust_type::*,
+        functions::{
+            is_carray_with_owned_record_values, is_carray_with_record_values,
+            is_carray_with_scalar_elements,
+        },
+        imports::Imports,
+        namespaces,
+        override_string_type::override_string_type_return,
+        ref_mode::RefMode,
+        rust_type::*,
     },
     config,
     env::Env,
@@ -36,9 +43,7 @@ pub fn analyze(
     let mut parameter = if typ == Default::default() {
         None
     } else {
-        if let Ok(s) = used_rust_type(env, typ, false) {
-            used_types.push(s);
-        }
+        used_types.extend(used_rust_types(env, typ, false));
 
         let mut nullable = func.ret.nullable;
         if !obj.trust_return_value_nullability {
@@ -56,6 +61,10 @@ pub fn analyze(
         if let Some(val) = nullable_override {
             nullable = val;
         }
+        if let library::Type::Typed(_) = *env.library.type_(typ) {
+            // A value of another type than the configured one is returned as `None`
+            nullable = Nullable(true);
+        }
         Some(library::Parameter {
             typ,
             nullable,
@@ -65,6 +74,20 @@ pub fn analyze(
 
     let commented = if typ == Default::default() {
         false
+    } else if func.ret.array_length.is_none()
+        && (is_carray_with_scalar_elements(env, typ)
+            || is_carray_with_record_values(env, typ, &func.ret.c_type, func.ret.direction))
+    {
+        // Element-wise converted arrays need a length
+        true
+    } else if is_carray_with_owned_record_values(
+        env,
+        typ,
+        &func.ret.c_type,
+        func.ret.direction,
+        func.ret.transfer,
+    ) {
+        true
     } else {
         parameter_rust_type(
             env,
@@ -1,6 +1,7 @@
 use super::conversion_type::ConversionType;
 use crate::{
-    analysis::ref_mode::RefMode,
+    analysis::{ref_mode::RefMode, variant_type},
+    config::data_model,
     env::Env,
     library::{self, Nullable, ParameterScope},
     nameutil::{is_gstring, use_glib_type},
@@ -111,62 +112,53 @@ pub fn rust_type_full(
     let mut skip_option = false;
     let type_ = env.library.type_(type_id);
     let mut rust_type = match *type_ {
-        Fundamental(fund) => {
-            match fund {
-                None => err("()"),
-                Boolean => ok("bool"),
-                Int8 => ok("i8"),
-                UInt8 => ok("u8"),
-                Int16 => ok("i16"),
-                UInt16 => ok("u16"),
-                Int32 => ok("i32"),
-                UInt32 => ok("u32"),
-                Int64 => ok("i64"),
-                UInt64 => ok("u64"),
-
-                Int => ok("i32"),  //maybe dependent on target system
-                UInt => ok("u32"), //maybe dependent on target system
+        Fundamental(fund) => match fund {
+            None => err("()"),
+            Boolean => ok("bool"),
+            Int8 => ok("i8"),
+            UInt8 => ok("u8"),
+            Int16 => ok("i16"),
+            UInt16 => ok("u16"),
+            Int32 => ok("i32"),
+            UInt32 => ok("u32"),
+            Int64 => ok("i64"),
+            UInt64 => ok("u64"),
 
-                Short => ok("libc::c_short"), //depends of target system
-                UShort => ok("libc::c_ushort"), //depends of target system
-                Long => ok("libc::c_long"),   //depends of target system
-                ULong => ok("libc::c_ulong"), //depends of target system
-
-                Size => ok("usize"),  //depends of target system
-                SSize => ok("isize"), //depends of target system
+            Short | UShort | Int | UInt | Long | ULong | Size | SSize => {
+                Ok(data_model::rust_type(&env.config.data_models, fund).unwrap())
+            }
 
-                Float => ok("f32"),
-                Double => ok("f64"),
+            Float => ok("f32"),
+            Double => ok("f64"),
 
-                UniChar => ok("char"),
-                Utf8 => {
-                    if ref_mode.is_ref() {
-                        ok("str")
-                    } else {
-                        Ok(use_glib_type(env, "GString"))
-                    }
+            UniChar => ok("char"),
+            Utf8 => {
+                if ref_mode.is_ref() {
+                    ok("str")
+                } else {
+                    Ok(use_glib_type(env, "GString"))
                 }
-                Filename => {
-                    if ref_mode.is_ref() {
-                        ok("std::path::Path")
-                    } else {
-                        ok("std::path::PathBuf")
-                    }
+            }
+            Filename => {
+                if ref_mode.is_ref() {
+                    ok("std::path::Path")
+                } else {
+                    ok("std::path::PathBuf")
                 }
-                OsString => {
-                    if ref_mode.is_ref() {
-                        ok("std::ffi::OsStr")
-                    } else {
-                        ok("std::ffi::OsString")
-                    }
+            }
+            OsString => {
+                if ref_mode.is_ref() {
+                    ok("std::ffi::OsStr")
+                } else {
+                    ok("std::ffi::OsString")
                 }
-                Type => Ok(use_glib_type(env, "types::Type")),
-                Char => Ok(use_glib_type(env, "Char")),
-                UChar => Ok(use_glib_type(env, "UChar")),
-                Unsupported => err("Unsupported"),
-                _ => err(&format!("Fundamental: {:?}", fund)),
             }
-        }
+            Type => Ok(use_glib_type(env, "types::Type")),
+            Char => Ok(use_glib_type(env, "Char")),
+            UChar => Ok(use_glib_type(env, "UChar")),
+            Unsupported => err("Unsupported"),
+            _ => err(&format!("Fundamental: {:?}", fund)),
+        },
         Alias(ref alias) => rust_type_full(env, alias.typ, nullable, ref_mode, scope, concurrency)
             .map_any(|_| alias.name.clone()),
         Record(library::Record { ref c_type, .. }) if c_type == "GVariantType" => {
@@ -211,20 +203,21 @@ pub fn rust_type_full(
         CArray(inner_tid) if ConversionType::of(env, inner_tid) == ConversionType::Direct => {
             if let Fundamental(fund) = *env.library.type_(inner_tid) {
                 let array_type = match fund {
-                    Int8 => Some("i8"),
-                    UInt8 => Some("u8"),
-                    Int16 => Some("i16"),
-                    UInt16 => Some("u16"),
-                    Int32 => Some("i32"),
-                    UInt32 => Some("u32"),
-                    Int64 => Some("i64"),
-                    UInt64 => Some("u64"),
+                    Int8 => Some("i8".into()),
+                    UInt8 => Some("u8".into()),
+                    Int16 => Some("i16".into()),
+                    UInt16 => Some("u16".into()),
+                    Int32 => Some("i32".into()),
+                    UInt32 => Some("u32".into()),
+                    Int64 => Some("i64".into()),
+                    UInt64 => Some("u64".into()),
 
-                    Int => Some("i32"),  //maybe dependent on target system
-                    UInt => Some("u32"), //maybe dependent on target system
+                    Short | UShort | Int | UInt | Long | ULong => {
+                        data_model::rust_type(&env.config.data_models, fund)
+                    }
 
-                    Float => Some("f32"),
-                    Double => Some("f64"),
+                    Float => Some("f32".into()),
+                    Double => Some("f64".into()),
                     _ => Option::None,
                 };
 
@@ -242,7 +235,45 @@ pub fn rust_type_full(
                 Err(TypeError::Unimplemented(type_.get_name()))
             }
         }
+        CArray(inner_tid) if ConversionType::of(env, inner_tid) == ConversionType::Scalar => {
+            skip_option = true;
+            rust_type(env, inner_tid).map_any(|s| {
+                if ref_mode.is_ref() {
+                    format!("[{}]", s)
+                } else {
+                    format!("Vec<{}>", s)
+                }
+            })
+        }
+        FixedArray(inner_tid, size, _)
+            if ConversionType::of(env, inner_tid) == ConversionType::Direct =>
+        {
+            // Only returned arrays may be missing
+            skip_option = ref_mode.is_ref();
+            rust_type(env, inner_tid).map_any(|s| format!("[{}; {}]", s, size))
+        }
+        HashTable(key_tid, value_tid) => {
+            skip_option = true;
+            let key = hash_table_item_rust_type(env, key_tid);
+            let value = hash_table_item_rust_type(env, value_tid);
+            match (key, value) {
+                (Ok(k), Ok(v)) => Ok(format!("HashMap<{}, {}>", k, v)),
+                (k, v) => Err(TypeError::Unimplemented(format!(
+                    "HashMap<{}, {}>",
+                    into_inner(k),
+                    into_inner(v)
+                ))),
+            }
+        }
         Custom(library::Custom { ref name, .. }) => Ok(name.clone()),
+        Typed(ref typed) => {
+            let rust_type = variant_type::typed_rust_type(typed, &use_glib_type(env, "Variant"));
+            if rust_type == "String" && ref_mode.is_ref() {
+                ok("str")
+            } else {
+                Ok(rust_type)
+            }
+        }
         Function(ref f) => {
             let concurrency = match concurrency {
                 _ if scope.is_call() => "",
@@ -385,14 +416,35 @@ pub fn rust_type_full(
     rust_type
 }
 
+/// Maps a `GHashTable` key or value type to the matching `HashMap` item type.
+///
+/// Only strings and objects are supported as these are the only types that can
+/// be copied out of the untyped `gpointer` slots of the table.
+fn hash_table_item_rust_type(env: &Env, type_id: library::TypeId) -> Result {
+    use crate::library::Type::*;
+    let type_ = env.library.type_(type_id);
+    match *type_ {
+        Fundamental(library::Fundamental::Utf8) => Ok("String".into()),
+        Class(..) | Interface(..) => rust_type(env, type_id),
+        _ => Err(TypeError::Unimplemented(type_.get_name())),
+    }
+}
+
 pub fn used_rust_type(env: &Env, type_id: library::TypeId, is_in: bool) -> Result {
     use crate::library::Type::*;
     match *env.library.type_(type_id) {
-        Fundamental(library::Fundamental::Type)
-        | Fundamental(library::Fundamental::Short)
+        // Platform dependent integers only need `libc` if they don't have a fixed size
+        Fundamental(library::Fundamental::Short)
         | Fundamental(library::Fundamental::UShort)
         | Fundamental(library::Fundamental::Long)
-        | Fundamental(library::Fundamental::ULong)
+        | Fundamental(library::Fundamental::ULong) => rust_type(env, type_id).and_then(|s| {
+            if s.contains("::") {
+                Ok(s)
+            } else {
+                Err(TypeError::Ignored("Don't need use".to_owned()))
+            }
+        }),
+        Fundamental(library::Fundamental::Type)
         | Fundamental(library::Fundamental::Char)
         | Fundamental(library::Fundamental::UChar)
         | Fundamental(library::Fundamental::Filename)
@@ -405,15 +457,31 @@ pub fn used_rust_type(env: &Env, type_id: library::TypeId, is_in: bool) -> Resul
         | Enumeration(..)
         | Interface(..) => rust_type(env, type_id),
         //process inner types as return parameters
-        List(inner_tid) | SList(inner_tid) | CArray(inner_tid) | PtrArray(inner_tid) => {
-            used_rust_type(env, inner_tid, false)
-        }
+        List(inner_tid)
+        | SList(inner_tid)
+        | CArray(inner_tid)
+        | PtrArray(inner_tid)
+        | FixedArray(inner_tid, ..) => used_rust_type(env, inner_tid, false),
         Custom(..) => rust_type(env, type_id),
         Fundamental(library::Fundamental::Utf8) if !is_in => Ok(use_glib_type(env, "GString")),
         _ => Err(TypeError::Ignored("Don't need use".to_owned())),
     }
 }
 
+/// Returns all the types used by the given type, see `used_rust_type`.
+///
+/// Unlike `used_rust_type`, both the key and the value types of a `GHashTable` are returned.
+pub fn used_rust_types(env: &Env, type_id: library::TypeId, is_in: bool) -> Vec<String> {
+    match *env.library.type_(type_id) {
+        //the strings of a table are converted to `String` and don't need `GString`
+        library::Type::HashTable(key_tid, value_tid) => [key_tid, value_tid]
+            .iter()
+            .filter_map(|&tid| used_rust_type(env, tid, true).ok())
+            .collect(),
+        _ => used_rust_type(env, type_id, is_in).into_iter().collect(),
+    }
+}
+
 pub fn parameter_rust_type(
     env: &Env,
     type_id: library::TypeId,
@@ -476,7 +544,13 @@ pub fn parameter_rust_type(
             library::ParameterDirection::In | library::ParameterDirection::Return => rust_type,
             _ => Err(TypeError::Unimplemented(into_inner(rust_type))),
         },
-        CArray(..) | PtrArray(..) => match direction {
+        HashTable(..) => match direction {
+            library::ParameterDirection::In
+            | library::ParameterDirection::Out
+            | library::ParameterDirection::Return => rust_type,
+            _ => Err(TypeError::Unimplemented(into_inner(rust_type))),
+        },
+        CArray(..) | PtrArray(..) | FixedArray(..) => match direction {
             library::ParameterDirection::In
             | library::ParameterDirection::Out
             | library::ParameterDirection::Return => rust_type,
@@ -487,6 +561,10 @@ pub fn parameter_rust_type(
         }
         Function(_) => rust_type,
         Custom(..) => rust_type.map_any(|s| format_parameter(s, direction)),
+        Typed(..) => match direction {
+            library::ParameterDirection::In | library::ParameterDirection::Return => rust_type,
+            _ => Err(TypeError::Unimplemented(into_inner(rust_type))),
+        },
         _ => Err(TypeError::Unimplemented(type_.get_name())),
     }
 }
@@ -157,6 +157,7 @@ impl IsIncomplete for Type {
             Type::Record(ref record) => record.is_incomplete(lib),
             Type::Union(ref union) => union.is_incomplete(lib),
             Type::Function(ref function) => function.is_incomplete(lib),
+            Type::Typed(ref typed) => typed.typ.is_incomplete(lib),
             Type::Interface(..) => true,
             Type::Custom(..)
             | Type::Enumeration(..)
@@ -211,6 +212,7 @@ impl IsExternal for Type {
             Type::Class(ref klass) => klass.is_external(lib),
             Type::Record(ref record) => record.is_external(lib),
             Type::Union(ref union) => union.is_external(lib),
+            Type::Typed(ref typed) => lib.type_(typed.typ).is_external(lib),
             Type::Interface(..) => true,
             Type::Custom(..)
             | Type::Fundamental(..)
@@ -0,0 +1,164 @@
+//! Mapping of `GVariant` type strings to the Rust types implementing
+//! `ToVariant` and `FromVariant` for them.
+
+use crate::library::{Typed, TypedKind};
+use std::{iter::Peekable, str::Chars};
+
+/// Returns the Rust type for the variant type string `type_string`,
+/// `variant` is the path of `glib::Variant` used for `v`.
+pub fn rust_type(type_string: &str, variant: &str) -> Result<String, String> {
+    let mut chars = type_string.chars().peekable();
+    let rust_type = parse(&mut chars, variant)
+        .map_err(|e| format!("{} in variant type `{}`", e, type_string))?;
+    match chars.next() {
+        None => Ok(rust_type),
+        Some(c) => Err(format!(
+            "Unexpected `{}` after the end of variant type `{}`",
+            c, type_string
+        )),
+    }
+}
+
+/// Returns the Rust type of a typed `GVariant` or `GValue`.
+pub fn typed_rust_type(typed: &Typed, variant: &str) -> String {
+    match typed.kind {
+        // Invalid variant types are reported and dropped by the library postprocessing
+        TypedKind::Variant => {
+            rust_type(&typed.type_string, variant).unwrap_or_else(|_| variant.to_owned())
+        }
+        TypedKind::Value => typed.type_string.clone(),
+    }
+}
+
+/// Checks that the `GValue` type `type_string` looks like a Rust type,
+/// e.g. `String` or `Vec<glib::GString>`.
+pub fn check_value_type(type_string: &str) -> Result<(), String> {
+    let mut depth = 0usize;
+    for c in type_string.chars() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| format!("Unbalanced `{}` in value type `{}`", c, type_string))?
+            }
+            ':' | ',' | ' ' | '_' => (),
+            c if c.is_alphanumeric() => (),
+            c => {
+                return Err(format!(
+                    "Unexpected `{}` in value type `{}`",
+                    c, type_string
+                ))
+            }
+        }
+    }
+    if depth != 0 || type_string.trim().is_empty() {
+        return Err(format!("Invalid value type `{}`", type_string));
+    }
+    Ok(())
+}
+
+/// Whether `rust_type` is passed by value instead of by reference.
+pub fn is_copy(rust_type: &str) -> bool {
+    matches!(
+        rust_type,
+        "bool" | "u8" | "i16" | "u16" | "i32" | "u32" | "i64" | "u64" | "f32" | "f64"
+    )
+}
+
+fn parse(chars: &mut Peekable<Chars<'_>>, variant: &str) -> Result<String, String> {
+    let c = chars.next().ok_or("Unexpected end")?;
+    let basic = match c {
+        'b' => "bool",
+        'y' => "u8",
+        'n' => "i16",
+        'q' => "u16",
+        'i' => "i32",
+        'u' => "u32",
+        'x' => "i64",
+        't' => "u64",
+        'd' => "f64",
+        's' => "String",
+        'v' => variant,
+        'a' if chars.peek() == Some(&'{') => {
+            chars.next();
+            let key = parse(chars, variant)?;
+            if !is_copy(&key) && key != "String" {
+                return Err(format!("Unsupported dictionary key `{}`", key));
+            }
+            let value = parse(chars, variant)?;
+            if chars.next() != Some('}') {
+                return Err("Missing `}`".into());
+            }
+            return Ok(format!("HashMap<{}, {}>", key, value));
+        }
+        'a' => return Ok(format!("Vec<{}>", parse(chars, variant)?)),
+        '(' => {
+            let mut items = Vec::new();
+            while chars.peek() != Some(&')') {
+                items.push(parse(chars, variant)?);
+            }
+            chars.next();
+            return Ok(match items.len() {
+                1 => format!("({},)", items[0]),
+                _ => format!("({})", items.join(", ")),
+            });
+        }
+        _ => return Err(format!("Unsupported `{}`", c)),
+    };
+    Ok(basic.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    fn rust_type(type_string: &str) -> Result<String, String> {
+        super::rust_type(type_string, "glib::Variant")
+    }
+
+    #[test]
+    fn basic_types() {
+        assert_eq!(rust_type("b"), Ok("bool".into()));
+        assert_eq!(rust_type("i"), Ok("i32".into()));
+        assert_eq!(rust_type("t"), Ok("u64".into()));
+        assert_eq!(rust_type("s"), Ok("String".into()));
+        assert_eq!(rust_type("v"), Ok("glib::Variant".into()));
+    }
+
+    #[test]
+    fn container_types() {
+        assert_eq!(
+            rust_type("a{sv}"),
+            Ok("HashMap<String, glib::Variant>".into())
+        );
+        assert_eq!(rust_type("as"), Ok("Vec<String>".into()));
+        assert_eq!(
+            rust_type("(sa{sv})"),
+            Ok("(String, HashMap<String, glib::Variant>)".into())
+        );
+        assert_eq!(rust_type("(i)"), Ok("(i32,)".into()));
+        assert_eq!(rust_type("()"), Ok("()".into()));
+        assert_eq!(rust_type("aai"), Ok("Vec<Vec<i32>>".into()));
+    }
+
+    #[test]
+    fn invalid_types() {
+        assert!(rust_type("").is_err());
+        assert!(rust_type("o").is_err());
+        assert!(rust_type("ii").is_err());
+        assert!(rust_type("a{sv").is_err());
+        assert!(rust_type("a{vs}").is_err());
+        assert!(rust_type("(ss").is_err());
+    }
+
+    #[test]
+    fn value_types() {
+        use super::check_value_type;
+        assert!(check_value_type("String").is_ok());
+        assert!(check_value_type("Vec<glib::GString>").is_ok());
+        assert!(check_value_type("(i32, bool)").is_ok());
+        assert!(check_value_type("").is_err());
+        assert!(check_value_type("Vec<String").is_err());
+        assert!(check_value_type("String>").is_err());
+        assert!(check_value_type("&str").is_err());
+    }
+}
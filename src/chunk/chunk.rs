@@ -41,6 +41,10 @@ pub enum Chunk {
     NullMutPtr,
     Custom(String),
     Tuple(Vec<Chunk>, TupleMode),
+    Struct {
+        name: String,
+        fields: Vec<(String, Chunk)>,
+    },
     FromGlibConversion {
         mode: conversion_from_glib::Mode,
         array_length_name: Option<String>,
@@ -4,16 +4,22 @@ use crate::library;
 #[derive(Clone, Debug)]
 pub struct Mode {
     pub typ: library::TypeId,
+    pub c_type: String,
+    pub direction: library::ParameterDirection,
     pub transfer: library::Transfer,
     pub is_uninitialized: bool,
+    pub nullable: bool,
 }
 
 impl<'a> From<&'a parameter_ffi_call_out::Parameter> for Mode {
     fn from(orig: &'a parameter_ffi_call_out::Parameter) -> Mode {
         Mode {
             typ: orig.typ,
+            c_type: orig.c_type.clone(),
+            direction: library::ParameterDirection::Out,
             transfer: orig.transfer,
             is_uninitialized: orig.is_uninitialized,
+            nullable: orig.nullable,
         }
     }
 }
@@ -22,8 +28,11 @@ impl<'a> From<&'a library::Parameter> for Mode {
     fn from(orig: &'a library::Parameter) -> Mode {
         Mode {
             typ: orig.typ,
+            c_type: orig.c_type.clone(),
+            direction: orig.direction,
             transfer: orig.transfer,
             is_uninitialized: false,
+            nullable: *orig.nullable,
         }
     }
 }
@@ -4,10 +4,12 @@ use crate::{analysis, library};
 pub struct Parameter {
     pub name: String,
     pub typ: library::TypeId,
+    pub c_type: String,
     pub transfer: library::Transfer,
     pub caller_allocates: bool,
     pub is_error: bool,
     pub is_uninitialized: bool,
+    pub nullable: bool,
 }
 
 impl Parameter {
@@ -18,10 +20,12 @@ impl Parameter {
         Parameter {
             name: orig.name.clone(),
             typ: orig.typ,
+            c_type: orig.c_type.clone(),
             transfer: orig.transfer,
             caller_allocates: orig.caller_allocates,
             is_error: orig.is_error,
             is_uninitialized,
+            nullable: *orig.nullable,
         }
     }
 }
@@ -31,10 +35,12 @@ impl<'a> From<&'a library::Parameter> for Parameter {
         Parameter {
             name: orig.name.clone(),
             typ: orig.typ,
+            c_type: orig.c_type.clone(),
             transfer: orig.transfer,
             caller_allocates: orig.caller_allocates,
             is_error: orig.is_error,
             is_uninitialized: false,
+            nullable: *orig.nullable,
         }
     }
 }
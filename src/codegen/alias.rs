@@ -34,7 +34,7 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
     }
 
     let path = root_path.join("alias.rs");
-    file_saver::save_to_file(path, env.config.make_backup, |w| {
+    file_saver::save_to_file(path, env, |w| {
         general::start_comments(w, &env.config)?;
         writeln!(w)?;
         writeln!(w, "#[allow(unused_imports)]")?;
@@ -91,9 +91,7 @@ impl_function_like_type!(Signal);
 
 pub fn generate(env: &Env) {
     info!("Generating documentation {:?}", env.config.doc_target_path);
-    save_to_file(&env.config.doc_target_path, env.config.make_backup, |w| {
-        generate_doc(w, env)
-    });
+    save_to_file(&env.config.doc_target_path, env, |w| generate_doc(w, env));
 }
 
 #[allow(clippy::type_complexity)]
@@ -119,6 +117,34 @@ fn generate_doc(w: &mut dyn Write, env: &Env) -> Result<()> {
         }
     }
 
+    let functions = env
+        .analysis
+        .objects
+        .values()
+        .filter(|info| info.type_id.ns_id == MAIN)
+        .flat_map(|info| info.functions.iter())
+        .chain(
+            env.analysis
+                .records
+                .values()
+                .filter(|info| info.type_id.ns_id == MAIN)
+                .flat_map(|info| info.functions.iter()),
+        )
+        .chain(
+            env.analysis
+                .global_functions
+                .iter()
+                .flat_map(|info| info.functions.iter()),
+        );
+    for info in functions {
+        if let Some(out_struct) = info.generated_out_struct(env) {
+            generators.push((
+                &out_struct.name,
+                Box::new(move |w, e| create_out_struct_doc(w, e, info)),
+            ));
+        }
+    }
+
     for (tid, type_) in env.library.namespace_types(MAIN) {
         if let LType::Enumeration(ref enum_) = *type_ {
             if !env
@@ -158,7 +184,7 @@ fn generate_doc(w: &mut dyn Write, env: &Env) -> Result<()> {
 }
 
 fn create_object_doc(w: &mut dyn Write, env: &Env, info: &analysis::object::Info) -> Result<()> {
-    let symbols = env.symbols.borrow();
+    let symbols = env.symbols.read().unwrap();
     let ty = TypeStruct::new(SType::Struct, &info.name);
     let ty_ext = TypeStruct::new(SType::Trait, &info.trait_name);
     let has_trait = info.generate_trait;
@@ -329,7 +355,7 @@ fn create_object_doc(w: &mut dyn Write, env: &Env, info: &analysis::object::Info
 fn create_record_doc(w: &mut dyn Write, env: &Env, info: &analysis::record::Info) -> Result<()> {
     let record: &Record = env.library.type_(info.type_id).to_ref_as();
     let ty = record.to_stripper_type();
-    let symbols = env.symbols.borrow();
+    let symbols = env.symbols.read().unwrap();
 
     write_item_doc(w, &ty, |w| {
         if let Some(ref doc) = record.doc {
@@ -364,7 +390,7 @@ fn create_record_doc(w: &mut dyn Write, env: &Env, info: &analysis::record::Info
 
 fn create_enum_doc(w: &mut dyn Write, env: &Env, enum_: &Enumeration) -> Result<()> {
     let ty = enum_.to_stripper_type();
-    let symbols = env.symbols.borrow();
+    let symbols = env.symbols.read().unwrap();
 
     write_item_doc(w, &ty, |w| {
         if let Some(ref doc) = enum_.doc {
@@ -406,9 +432,38 @@ fn create_enum_doc(w: &mut dyn Write, env: &Env, enum_: &Enumeration) -> Result<
     Ok(())
 }
 
+fn create_out_struct_doc(
+    w: &mut dyn Write,
+    env: &Env,
+    info: &analysis::functions::Info,
+) -> Result<()> {
+    let out_struct = match info.generated_out_struct(env) {
+        Some(out_struct) => out_struct,
+        None => return Ok(()),
+    };
+    let ty = TypeStruct::new(SType::Struct, &out_struct.name);
+    let symbols = env.symbols.read().unwrap();
+
+    for (name, pos) in &out_struct.fields {
+        if let Some(ref doc) = info.outs.params[*pos].doc {
+            let sub_ty = TypeStruct {
+                name: name.clone(),
+                parent: Some(Box::new(ty.clone())),
+                ty: SType::Variant,
+                args: Vec::new(),
+            };
+            write_item_doc(w, &sub_ty, |w| {
+                writeln!(w, "{}", reformat_doc(doc, &symbols))?;
+                Ok(())
+            })?;
+        }
+    }
+    Ok(())
+}
+
 fn create_bitfield_doc(w: &mut dyn Write, env: &Env, bitfield: &Bitfield) -> Result<()> {
     let ty = bitfield.to_stripper_type();
-    let symbols = env.symbols.borrow();
+    let symbols = env.symbols.read().unwrap();
 
     write_item_doc(w, &ty, |w| {
         if let Some(ref doc) = bitfield.doc {
@@ -484,7 +539,7 @@ where
         return Ok(());
     }
 
-    let symbols = env.symbols.borrow();
+    let symbols = env.symbols.read().unwrap();
     let mut st = fn_.to_stripper_type();
     if let Some(name_override) = name_override {
         st.name = name_override;
@@ -566,7 +621,7 @@ fn create_property_doc(
     let name_for_func = nameutil::signal_to_snake(&property.name);
     let mut v = Vec::with_capacity(2);
 
-    let symbols = env.symbols.borrow();
+    let symbols = env.symbols.read().unwrap();
     if property.readable {
         v.push(TypeStruct {
             parent: parent.clone(),
@@ -624,7 +679,7 @@ fn get_type_trait_for_implements(env: &Env, tid: TypeId) -> String {
     };
     if tid.ns_id == MAIN_NAMESPACE {
         implements_link(&trait_name)
-    } else if let Some(symbol) = env.symbols.borrow().by_tid(tid) {
+    } else if let Some(symbol) = env.symbols.read().unwrap().by_tid(tid) {
         let mut full_trait_name = symbol.full_rust_name();
         let crate_path = if let Some(crate_name) = symbol.crate_name() {
             if crate_name == "gobject" {
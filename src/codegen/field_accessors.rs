@@ -0,0 +1,119 @@
+use super::general::{doc_hidden, version_condition};
+use crate::{
+    analysis::{
+        field_accessors::{FieldAccessor, FieldKind},
+        rust_type::{rust_type, rust_type_nullable},
+    },
+    chunk::conversion_from_glib::Mode,
+    codegen::translate_from_glib::TranslateFromGlib,
+    env::Env,
+    library,
+    traits::IntoString,
+    writer::primitives::tabs,
+};
+use std::io::{Result, Write};
+
+pub fn generate(
+    w: &mut dyn Write,
+    env: &Env,
+    accessor: &FieldAccessor,
+    in_trait: bool,
+    only_declaration: bool,
+    indent: usize,
+) -> Result<()> {
+    let pub_prefix = if in_trait { "" } else { "pub " };
+    let decl_suffix = if only_declaration { ";" } else { " {" };
+    let self_ptr = if in_trait { "self.as_ref()" } else { "self" };
+
+    writeln!(w)?;
+    version_condition(w, env, accessor.version, false, indent)?;
+    doc_hidden(w, accessor.doc_hidden, "", indent)?;
+
+    let field = format!("(*ptr).{}", accessor.field_name);
+    if accessor.is_get {
+        let nullable = library::Nullable(accessor.kind == FieldKind::Pointer);
+        writeln!(
+            w,
+            "{}{}fn {}(&self) -> {}{}",
+            tabs(indent),
+            pub_prefix,
+            accessor.func_name,
+            rust_type_nullable(env, accessor.typ, nullable).into_string(),
+            decl_suffix
+        )?;
+        if only_declaration {
+            return Ok(());
+        }
+        writeln!(w, "{}unsafe {{", tabs(indent + 1))?;
+        writeln!(
+            w,
+            "{}let ptr: {} = {}.to_glib_none().0;",
+            tabs(indent + 2),
+            ptr_type(accessor, true),
+            self_ptr
+        )?;
+        let value = match accessor.kind {
+            FieldKind::Direct => field,
+            FieldKind::Scalar => {
+                let (prefix, suffix) = Mode {
+                    typ: accessor.typ,
+                    c_type: String::new(),
+                    direction: library::ParameterDirection::Return,
+                    transfer: library::Transfer::None,
+                    is_uninitialized: false,
+                    nullable: false,
+                }
+                .translate_from_glib_as_function(env, None);
+                format!("{}{}{}", prefix, field, suffix)
+            }
+            FieldKind::Pointer => format!("from_glib_none({})", field),
+            FieldKind::Embedded => format!("from_glib_none(&{} as *const _)", field),
+        };
+        writeln!(w, "{}{}", tabs(indent + 2), value)?;
+    } else {
+        let (self_ref, to_glib) = if accessor.is_owned_by_value() {
+            ("&mut self", "to_glib_none_mut")
+        } else {
+            ("&self", "to_glib_none")
+        };
+        writeln!(
+            w,
+            "{}{}fn {}({}, value: {}){}",
+            tabs(indent),
+            pub_prefix,
+            accessor.func_name,
+            self_ref,
+            rust_type(env, accessor.typ).into_string(),
+            decl_suffix
+        )?;
+        if only_declaration {
+            return Ok(());
+        }
+        writeln!(w, "{}unsafe {{", tabs(indent + 1))?;
+        writeln!(
+            w,
+            "{}let ptr: {} = {}.{}().0;",
+            tabs(indent + 2),
+            ptr_type(accessor, false),
+            self_ptr,
+            to_glib
+        )?;
+        let value = if accessor.kind == FieldKind::Scalar {
+            "value.to_glib()"
+        } else {
+            "value"
+        };
+        writeln!(w, "{}{} = {};", tabs(indent + 2), field, value)?;
+    }
+    writeln!(w, "{}}}", tabs(indent + 1))?;
+    writeln!(w, "{}}}", tabs(indent))
+}
+
+fn ptr_type(accessor: &FieldAccessor, is_get: bool) -> String {
+    let mutability = if is_get && accessor.is_owned_by_value() {
+        "const"
+    } else {
+        "mut"
+    };
+    format!("*{} {}", mutability, accessor.owner_ffi_type)
+}
@@ -1,23 +1,24 @@
 use super::{
     function_body_chunk,
     general::{
-        cfg_condition, cfg_deprecated, doc_alias, doc_hidden, not_version_condition,
-        version_condition,
+        cfg_condition, cfg_condition_string, cfg_deprecated, doc_alias, doc_hidden,
+        not_version_condition, version_condition, version_condition_string,
     },
     parameter::ToParameter,
-    return_value::{out_parameters_as_return, ToReturnValue},
+    return_value::{out_parameter_as_return, out_parameters_as_return, ToReturnValue},
     special_functions,
 };
 use crate::{
     analysis::{
         self,
         bounds::{Bound, Bounds},
-        functions::Visibility,
+        functions::{AsyncFuture, IteratorAdapter, IteratorKind, Visibility},
         namespaces,
     },
     chunk::{ffi_function_todo, Chunk},
     env::Env,
     library,
+    nameutil::use_gio_type,
     writer::{primitives::tabs, safety_assertion_mode_to_str, ToCode},
 };
 use log::warn;
@@ -142,12 +143,109 @@ pub fn generate(
         }
     }
 
+    if let Some(ref adapter) = analysis.iterator {
+        let (declaration, body) = match iterator_adapter(env, analysis, adapter, in_trait) {
+            Some(adapter) => adapter,
+            None => return Ok(()),
+        };
+        let suffix = if only_declaration { ";" } else { " {" };
+
+        writeln!(w)?;
+        if !in_trait || only_declaration {
+            cfg_deprecated(w, env, analysis.deprecated_version, commented, indent)?;
+        }
+        cfg_condition(w, &analysis.cfg_condition, commented, indent)?;
+        version_condition(w, env, analysis.version, commented, indent)?;
+        not_version_condition(w, analysis.not_version, commented, indent)?;
+        doc_hidden(w, analysis.doc_hidden, comment_prefix, indent)?;
+        writeln!(
+            w,
+            "{}{}{}{}{}",
+            tabs(indent),
+            comment_prefix,
+            pub_prefix,
+            declaration,
+            suffix
+        )?;
+
+        if !only_declaration {
+            for s in body.lines() {
+                writeln!(w, "{}{}{}", tabs(indent + 1), comment_prefix, s)?;
+            }
+            writeln!(w, "{}{}}}", tabs(indent), comment_prefix)?;
+        }
+    }
+
     Ok(())
 }
 
+/// Generates the struct returned instead of a tuple by functions with many outputs.
+pub fn generate_out_struct(
+    w: &mut dyn Write,
+    env: &Env,
+    analysis: &analysis::functions::Info,
+) -> Result<()> {
+    let out_struct = match analysis.generated_out_struct(env) {
+        Some(out_struct) => out_struct,
+        None => return Ok(()),
+    };
+
+    writeln!(w)?;
+    cfg_condition(w, &analysis.cfg_condition, false, 0)?;
+    version_condition(w, env, analysis.version, false, 0)?;
+    not_version_condition(w, analysis.not_version, false, 0)?;
+    writeln!(w, "#[derive(Debug, Clone)]")?;
+    writeln!(w, "pub struct {} {{", out_struct.name)?;
+    for (name, pos) in &out_struct.fields {
+        writeln!(
+            w,
+            "\tpub {}: {},",
+            name,
+            out_parameter_as_return(&analysis.outs.params[*pos], env)
+        )?;
+    }
+    writeln!(w, "}}")
+}
+
+/// Re-exports of the structs returned by `functions` defined in `module_name`
+/// under the conditions `cfgs` of the module.
+pub fn out_struct_reexports(
+    env: &Env,
+    functions: &[analysis::functions::Info],
+    module_name: &str,
+    cfgs: &[String],
+) -> Vec<String> {
+    let mut contents = Vec::new();
+    for func in functions {
+        if let Some(out_struct) = func.generated_out_struct(env) {
+            contents.extend_from_slice(cfgs);
+            if let Some(cfg) = cfg_condition_string(&func.cfg_condition, false, 0) {
+                contents.push(cfg);
+            }
+            if let Some(cfg) = version_condition_string(env, func.version, false, 0) {
+                contents.push(cfg);
+            }
+            contents.push(format!(
+                "pub use self::{}::{};",
+                module_name, out_struct.name
+            ));
+        }
+    }
+    contents
+}
+
 pub fn declaration(env: &Env, analysis: &analysis::functions::Info) -> String {
+    declaration_with(
+        env,
+        analysis,
+        analysis.codegen_name(),
+        &return_str(env, analysis),
+    )
+}
+
+fn return_str(env: &Env, analysis: &analysis::functions::Info) -> String {
     let outs_as_return = !analysis.outs.is_empty();
-    let return_str = if outs_as_return {
+    if outs_as_return {
         out_parameters_as_return(env, analysis)
     } else if analysis.ret.bool_return_is_error.is_some() {
         if env.namespaces.glib_ns_id == namespaces::MAIN {
@@ -157,7 +255,16 @@ pub fn declaration(env: &Env, analysis: &analysis::functions::Info) -> String {
         }
     } else {
         analysis.ret.to_return_value(env, false)
-    };
+    }
+}
+
+/// Declaration of the function under the given name and with the given return type.
+fn declaration_with(
+    env: &Env,
+    analysis: &analysis::functions::Info,
+    name: &str,
+    return_str: &str,
+) -> String {
     let mut param_str = String::with_capacity(100);
 
     let (bounds, _) = bounds(&analysis.bounds, &[], false, false);
@@ -171,23 +278,36 @@ pub fn declaration(env: &Env, analysis: &analysis::functions::Info) -> String {
         param_str.push_str(&s);
     }
 
-    format!(
-        "fn {}{}({}){}",
-        analysis.codegen_name(),
-        bounds,
-        param_str,
-        return_str,
-    )
+    format!("fn {}{}({}){}", name, bounds, param_str, return_str)
 }
 
 pub fn declaration_futures(env: &Env, analysis: &analysis::functions::Info) -> String {
     let async_future = analysis.async_future.as_ref().unwrap();
 
-    let return_str = format!(
-        " -> Pin<Box_<dyn std::future::Future<Output = Result<{}, {}>> + 'static>>",
+    let future_str = format!(
+        "Pin<Box_<dyn std::future::Future<Output = Result<{}, {}>> + 'static>>",
         async_future.success_parameters, async_future.error_parameters
     );
+    let return_str = if let Some(ref stream) = async_future.stream {
+        format!(
+            " -> ({}, Pin<Box_<dyn futures_core::stream::Stream<Item = {}> + 'static>>)",
+            future_str, stream.item_type
+        )
+    } else {
+        format!(" -> {}", future_str)
+    };
+
+    declaration_futures_with(env, analysis, &async_future.name, &return_str)
+}
 
+/// Declaration with the parameters of the future variant of the function.
+fn declaration_futures_with(
+    env: &Env,
+    analysis: &analysis::functions::Info,
+    name: &str,
+    return_str: &str,
+) -> String {
+    let async_future = analysis.async_future.as_ref().unwrap();
     let mut param_str = String::with_capacity(100);
 
     let mut skipped = 0;
@@ -195,7 +315,10 @@ pub fn declaration_futures(env: &Env, analysis: &analysis::functions::Info) -> S
     for (pos, par) in analysis.parameters.rust_parameters.iter().enumerate() {
         let c_par = &analysis.parameters.c_parameters[par.ind_c];
 
-        if c_par.name == "callback" || c_par.name == "cancellable" {
+        if c_par.name == "callback"
+            || c_par.name == "cancellable"
+            || is_future_callback(async_future, &c_par.name)
+        {
             skipped += 1;
             if let Some((t, _)) = analysis.bounds.get_parameter_alias_info(&c_par.name) {
                 skipped_bounds.push(t);
@@ -216,10 +339,14 @@ pub fn declaration_futures(env: &Env, analysis: &analysis::functions::Info) -> S
 
     let (bounds, _) = bounds(&analysis.bounds, skipped_bounds.as_ref(), true, false);
 
-    format!(
-        "fn {}{}({}){}",
-        async_future.name, bounds, param_str, return_str,
-    )
+    format!("fn {}{}({}){}", name, bounds, param_str, return_str)
+}
+
+/// Returns `true` if this parameter is one of the extra callbacks of the async function, which
+/// are provided by the future variant.
+fn is_future_callback(async_future: &AsyncFuture, name: &str) -> bool {
+    matches!(async_future.stream, Some(ref stream) if stream.callback_name == name)
+        || async_future.ignored_callbacks.iter().any(|c| c == name)
 }
 
 pub fn bound_to_string(bound: &Bound, r#async: bool) -> String {
@@ -328,6 +455,9 @@ pub fn body_chunk(env: &Env, analysis: &analysis::functions::Info) -> Chunk {
         .transformations(&analysis.parameters.transformations)
         .in_unsafe(analysis.unsafe_)
         .outs_mode(analysis.outs.mode);
+    if let Some(ref out_struct) = analysis.outs.out_struct {
+        builder.out_struct(out_struct);
+    }
 
     if analysis.r#async {
         if let Some(ref trampoline) = analysis.trampoline {
@@ -338,13 +468,12 @@ pub fn body_chunk(env: &Env, analysis: &analysis::functions::Info) -> Chunk {
                 analysis.name
             );
         }
-    } else {
-        for trampoline in analysis.callbacks.iter() {
-            builder.callback(trampoline);
-        }
-        for trampoline in analysis.destroys.iter() {
-            builder.destroy(trampoline);
-        }
+    }
+    for trampoline in analysis.callbacks.iter() {
+        builder.callback(trampoline);
+    }
+    for trampoline in analysis.destroys.iter() {
+        builder.destroy(trampoline);
     }
 
     for par in &analysis.parameters.c_parameters {
@@ -389,7 +518,10 @@ pub fn body_chunk_futures(
 
     // Skip the instance parameter
     for par in analysis.parameters.rust_parameters.iter().skip(skip) {
-        if par.name == "cancellable" || par.name == "callback" {
+        if par.name == "cancellable"
+            || par.name == "callback"
+            || is_future_callback(async_future, &par.name)
+        {
             continue;
         }
 
@@ -414,17 +546,30 @@ pub fn body_chunk_futures(
         }
     }
 
+    if let Some(ref stream) = async_future.stream {
+        writeln!(
+            body,
+            "let ({0}_sender, {0}_receiver) = futures_channel::mpsc::unbounded();",
+            stream.callback_name
+        )?;
+    }
+
+    let let_future = if async_future.stream.is_some() {
+        "let fut = "
+    } else {
+        ""
+    };
     if async_future.is_method {
         writeln!(
             body,
-            "Box_::pin({}::new(self, move |obj, send| {{",
-            gio_future_name
+            "{}Box_::pin({}::new(self, move |obj, send| {{",
+            let_future, gio_future_name
         )?;
     } else {
         writeln!(
             body,
-            "Box_::pin({}::new(&(), move |_obj, send| {{",
-            gio_future_name
+            "{}Box_::pin({}::new(&(), move |_obj, send| {{",
+            let_future, gio_future_name
         )?;
     }
 
@@ -447,6 +592,29 @@ pub fn body_chunk_futures(
             writeln!(body, "\t\tSome(&cancellable),")?;
         } else if par.name == "callback" {
             continue;
+        } else if async_future.ignored_callbacks.contains(&par.name) {
+            writeln!(body, "\t\tNone,")?;
+        } else if let Some(stream) = async_future
+            .stream
+            .as_ref()
+            .filter(|stream| stream.callback_name == par.name)
+        {
+            let arguments = stream.arguments.join(", ");
+            let closure = format!(
+                "move |{}| {{\n\t\t\tlet _ = {}_sender.unbounded_send({});\n\t\t}}",
+                arguments,
+                stream.callback_name,
+                if stream.arguments.len() == 1 {
+                    arguments.clone()
+                } else {
+                    format!("({})", arguments)
+                }
+            );
+            if stream.nullable {
+                writeln!(body, "\t\tSome(Box_::new({})),", closure)?;
+            } else {
+                writeln!(body, "\t\t{},", closure)?;
+            }
         } else {
             let c_par = &analysis.parameters.c_parameters[par.ind_c];
 
@@ -470,7 +638,1054 @@ pub fn body_chunk_futures(
     writeln!(body, "\t);")?;
     writeln!(body)?;
     writeln!(body, "\tcancellable")?;
-    writeln!(body, "}}))")?;
+    if let Some(ref stream) = async_future.stream {
+        writeln!(body, "}}));")?;
+        writeln!(body)?;
+        writeln!(body, "(fut, Box_::pin({}_receiver))", stream.callback_name)?;
+    } else {
+        writeln!(body, "}}))")?;
+    }
 
     Ok(body)
 }
+
+/// Returns the declaration and the body of the iterator or stream adapter of the function.
+fn iterator_adapter(
+    env: &Env,
+    analysis: &analysis::functions::Info,
+    adapter: &IteratorAdapter,
+    in_trait: bool,
+) -> Option<(String, String)> {
+    let adapter = match adapter.kind {
+        IteratorKind::Next => next_iterator_adapter(env, analysis, &adapter.name, in_trait),
+        IteratorKind::Indexed { ref length_func } => {
+            indexed_iterator_adapter(env, analysis, &adapter.name, length_func, in_trait)
+        }
+        IteratorKind::Stream => stream_adapter(env, analysis, &adapter.name),
+    };
+    if adapter.is_none() {
+        warn!(
+            "Unsupported return type for the iterator of {}",
+            analysis.glib_name
+        );
+    }
+    adapter
+}
+
+/// Returns the return type of an iterator adapter and the way to wrap its iterator expression.
+///
+/// Trait methods can't return `impl Iterator` so the iterator is boxed there.
+fn iterator_return(item_type: &str, in_trait: bool, iter: &str) -> (String, String) {
+    if in_trait {
+        (
+            format!(" -> Box_<dyn Iterator<Item = {}> + '_>", item_type),
+            format!("Box_::new({})", iter),
+        )
+    } else {
+        (
+            format!(" -> impl Iterator<Item = {}> + '_", item_type),
+            iter.to_owned(),
+        )
+    }
+}
+
+fn next_iterator_adapter(
+    env: &Env,
+    analysis: &analysis::functions::Info,
+    name: &str,
+    in_trait: bool,
+) -> Option<(String, String)> {
+    let return_str = return_str(env, analysis);
+    let ret = return_str.trim_start_matches(" -> ");
+    let (item_type, throws) = match type_arguments(ret, "Result").as_deref() {
+        Some(&[ok, err]) => (
+            format!("Result<{}, {}>", single_type_argument(ok, "Option")?, err),
+            true,
+        ),
+        Some(_) => return None,
+        None => (single_type_argument(ret, "Option")?.to_owned(), false),
+    };
+
+    let mut body = String::new();
+    let mut arguments = Vec::new();
+    for par in analysis.parameters.rust_parameters.iter().skip(1) {
+        let c_par = &analysis.parameters.c_parameters[par.ind_c];
+        if par.name == "cancellable" {
+            // Bound parameters can't be kept in the iterator, use the actual type instead.
+            let cancellable_type = use_gio_type(env, "Cancellable");
+            if *c_par.nullable {
+                body.push_str(&format!(
+                    "let cancellable: Option<{}> = cancellable.map(|c| c.as_ref().clone());\n",
+                    cancellable_type
+                ));
+                arguments.push("cancellable.as_ref()".to_owned());
+            } else {
+                body.push_str(&format!(
+                    "let cancellable: {} = cancellable.as_ref().clone();\n",
+                    cancellable_type
+                ));
+                arguments.push("&cancellable".to_owned());
+            }
+        } else {
+            arguments.push(par.name.clone());
+        }
+    }
+    let call = format!("self.{}({})", analysis.codegen_name(), arguments.join(", "));
+    let iter = if throws {
+        // Stops after the first error, the C function would likely keep failing otherwise.
+        body.push_str("let mut done = false;\n");
+        format!(
+            "std::iter::from_fn(move || {{
+\tif done {{
+\t\treturn None;
+\t}}
+\tlet res = {}.transpose();
+\tdone = !matches!(res, Some(Ok(_)));
+\tres
+}})",
+            call
+        )
+    } else {
+        format!("std::iter::from_fn(move || {})", call)
+    };
+    let (return_str, iter) = iterator_return(&item_type, in_trait, &iter);
+    body.push_str(&iter);
+
+    Some((declaration_with(env, analysis, name, &return_str), body))
+}
+
+fn indexed_iterator_adapter(
+    env: &Env,
+    analysis: &analysis::functions::Info,
+    name: &str,
+    length_func: &str,
+    in_trait: bool,
+) -> Option<(String, String)> {
+    let return_str = return_str(env, analysis);
+    let ret = return_str.trim_start_matches(" -> ");
+    let (item_type, map) = match single_type_argument(ret, "Option") {
+        Some(item_type) => (item_type, "filter_map"),
+        None if !ret.is_empty() && type_arguments(ret, "Result").is_none() => (ret, "map"),
+        None => return None,
+    };
+    let index = &analysis.parameters.rust_parameters.get(1)?.name;
+
+    let iter = format!(
+        "(0..self.{length}()).{map}(move |{index}| self.{name}({index}))",
+        length = length_func,
+        map = map,
+        index = index,
+        name = analysis.codegen_name()
+    );
+    let (return_str, body) = iterator_return(item_type, in_trait, &iter);
+    Some((format!("fn {}(&self){}", name, return_str), body))
+}
+
+fn stream_adapter(
+    env: &Env,
+    analysis: &analysis::functions::Info,
+    name: &str,
+) -> Option<(String, String)> {
+    let async_future = analysis.async_future.as_ref()?;
+    let success = &*async_future.success_parameters;
+    let (item_type, end_arms) = if let Some(item_type) = single_type_argument(success, "Option") {
+        (item_type, ["Ok(None) => None,", "Ok(Some(res))"])
+    } else if type_arguments(success, "Vec").is_some() {
+        (success, ["Ok(res) if res.is_empty() => None,", "Ok(res)"])
+    } else {
+        return None;
+    };
+    let item_type = format!("Result<{}, {}>", item_type, async_future.error_parameters);
+
+    let skip = if async_future.is_method { 1 } else { 0 };
+    let arguments = analysis
+        .parameters
+        .rust_parameters
+        .iter()
+        .skip(skip)
+        .filter(|par| {
+            par.name != "cancellable"
+                && par.name != "callback"
+                && !is_future_callback(async_future, &par.name)
+        })
+        .map(|par| par.name.clone())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let (state, next_state, end_state, call) = if async_future.is_method {
+        ("Some(self.clone())", "Some(obj)", "None", "obj.")
+    } else if analysis.type_name.is_ok() {
+        ("true", "true", "false", "Self::")
+    } else {
+        ("true", "true", "false", "")
+    };
+
+    let mut body = String::new();
+    body.push_str(&format!(
+        "Box_::pin(futures_util::stream::unfold({}, move |state| async move {{\n",
+        state
+    ));
+    if async_future.is_method {
+        body.push_str("\tlet obj = state?;\n");
+    } else {
+        body.push_str("\tif !state {\n\t\treturn None;\n\t}\n");
+    }
+    body.push_str(&format!(
+        "\tmatch {}{}({}).await {{\n",
+        call, async_future.name, arguments
+    ));
+    body.push_str(&format!("\t\t{}\n", end_arms[0]));
+    body.push_str(&format!(
+        "\t\t{} => Some((Ok(res), {})),\n",
+        end_arms[1], next_state
+    ));
+    body.push_str(&format!(
+        "\t\tErr(err) => Some((Err(err), {})),\n",
+        end_state
+    ));
+    body.push_str("\t}\n}))");
+
+    let return_str = format!(
+        " -> Pin<Box_<dyn futures_core::stream::Stream<Item = {}> + 'static>>",
+        item_type
+    );
+    Some((
+        declaration_futures_with(env, analysis, name, &return_str),
+        body,
+    ))
+}
+
+/// Returns the type arguments of `type_` if it's a `name` generic type.
+fn type_arguments<'a>(type_: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let arguments = type_
+        .strip_prefix(name)?
+        .strip_prefix('<')?
+        .strip_suffix('>')?;
+    let mut depth = 0;
+    let mut start = 0;
+    let mut ret = Vec::new();
+    for (pos, c) in arguments.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                ret.push(arguments[start..pos].trim());
+                start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    ret.push(arguments[start..].trim());
+    Some(ret)
+}
+
+fn single_type_argument<'a>(type_: &'a str, name: &str) -> Option<&'a str> {
+    match type_arguments(type_, name).as_deref() {
+        Some(&[argument]) => Some(argument),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_arguments() {
+        assert_eq!(
+            type_arguments("Result<Option<FileInfo>, glib::Error>", "Result"),
+            Some(vec!["Option<FileInfo>", "glib::Error"])
+        );
+        assert_eq!(
+            type_arguments("Result<(i32, Vec<u8>), glib::Error>", "Result"),
+            Some(vec!["(i32, Vec<u8>)", "glib::Error"])
+        );
+        assert_eq!(
+            single_type_argument("Option<FileInfo>", "Option"),
+            Some("FileInfo")
+        );
+        assert_eq!(single_type_argument("Vec<FileInfo>", "Option"), None);
+        assert_eq!(type_arguments("OptionFoo<i32>", "Option"), None);
+    }
+
+    const GLIB_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <namespace name="GLib" version="2.0" shared-library="libglib-2.0.so.0"
+             c:identifier-prefixes="G" c:symbol-prefixes="g">
+    <record name="Error" c:type="GError" glib:type-name="GError"
+            glib:get-type="g_error_get_type">
+      <field name="code" writable="1"><type name="gint" c:type="gint"/></field>
+    </record>
+  </namespace>
+</repository>
+"#;
+
+    const FOO_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <include name="GLib" version="2.0"/>
+  <namespace name="Foo" version="1.0" shared-library="libfoo-1.0.so.0"
+             c:identifier-prefixes="Foo" c:symbol-prefixes="foo">
+    <record name="Direct" c:type="FooDirect">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+    </record>
+    <record name="Boxed" c:type="FooBoxed" glib:type-name="FooBoxed"
+            glib:get-type="foo_boxed_get_type">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+    </record>
+    <record name="Copied" c:type="FooCopied">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+      <method name="copy" c:identifier="foo_copied_copy">
+        <return-value transfer-ownership="full">
+          <type name="Copied" c:type="FooCopied*"/>
+        </return-value>
+        <parameters>
+          <instance-parameter name="copied" transfer-ownership="none">
+            <type name="Copied" c:type="const FooCopied*"/>
+          </instance-parameter>
+        </parameters>
+      </method>
+      <method name="free" c:identifier="foo_copied_free">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="copied" transfer-ownership="none">
+            <type name="Copied" c:type="FooCopied*"/>
+          </instance-parameter>
+        </parameters>
+      </method>
+    </record>
+    <record name="Shared" c:type="FooShared">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+      <method name="ref" c:identifier="foo_shared_ref">
+        <return-value transfer-ownership="full">
+          <type name="Shared" c:type="FooShared*"/>
+        </return-value>
+        <parameters>
+          <instance-parameter name="shared" transfer-ownership="none">
+            <type name="Shared" c:type="FooShared*"/>
+          </instance-parameter>
+        </parameters>
+      </method>
+      <method name="unref" c:identifier="foo_shared_unref">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="shared" transfer-ownership="none">
+            <type name="Shared" c:type="FooShared*"/>
+          </instance-parameter>
+        </parameters>
+      </method>
+    </record>
+    <function name="get_direct" c:identifier="foo_get_direct">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="out" direction="out" caller-allocates="1" transfer-ownership="none">
+          <type name="Direct" c:type="FooDirect*"/>
+        </parameter>
+      </parameters>
+    </function>
+    <function name="get_boxed" c:identifier="foo_get_boxed">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="out" direction="out" caller-allocates="1" transfer-ownership="none">
+          <type name="Boxed" c:type="FooBoxed*"/>
+        </parameter>
+      </parameters>
+    </function>
+    <function name="get_copied" c:identifier="foo_get_copied">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="out" direction="out" caller-allocates="1" transfer-ownership="none">
+          <type name="Copied" c:type="FooCopied*"/>
+        </parameter>
+      </parameters>
+    </function>
+    <function name="get_shared" c:identifier="foo_get_shared">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="out" direction="out" caller-allocates="1" transfer-ownership="none">
+          <type name="Shared" c:type="FooShared*"/>
+        </parameter>
+      </parameters>
+    </function>
+  </namespace>
+</repository>
+"#;
+
+    /// Generates the global function `name` of `FOO_GIR`, `config` is appended
+    /// to the `Foo.Boxed` object.
+    fn generate_foo_function(config: &str, name: &str) -> String {
+        let config = format!(
+            "generate = [\"Foo.Direct\", \"Foo.Copied\", \"Foo.Shared\"]\n\
+             [[object]]\nname = \"Foo.Boxed\"\nstatus = \"generate\"\n{}",
+            config
+        );
+        generate_global_function(&[GLIB_GIR, FOO_GIR], &config, name)
+    }
+
+    #[test]
+    fn test_caller_allocated_direct_record() {
+        let code = generate_foo_function("", "get_direct");
+        assert!(code.contains("pub fn get_direct() -> Direct {"), "{}", code);
+        assert!(
+            code.contains("let mut out = Direct::uninitialized();"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("ffi::foo_get_direct(out.to_glib_none_mut().0);"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_caller_allocated_boxed_record() {
+        let config = "init_function_expression = \"|_ptr| ()\"\n\
+                      clear_function_expression = \"|_ptr| ()\"\n";
+        let code = generate_foo_function(config, "get_boxed");
+        assert!(code.contains("pub fn get_boxed() -> Boxed {"), "{}", code);
+        assert!(
+            code.contains("let mut out = Boxed::uninitialized();"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("ffi::foo_get_boxed(out.to_glib_none_mut().0);"),
+            "{}",
+            code
+        );
+
+        // Without init function, there's no way to set up the record
+        let code = generate_foo_function("", "get_boxed");
+        assert!(code.contains("//pub fn get_boxed(out: Boxed)"), "{}", code);
+        let code = generate_foo_function("", "get_copied");
+        assert!(
+            code.contains("//pub fn get_copied(out: Copied)"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_caller_allocated_refcounted_record() {
+        let code = generate_foo_function("", "get_shared");
+        assert!(
+            code.contains("//pub fn get_shared(out: Shared)"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("g_malloc0"), "{}", code);
+    }
+
+    const GOBJECT_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <include name="GLib" version="2.0"/>
+  <namespace name="GObject" version="2.0" shared-library="libgobject-2.0.so.0"
+             c:identifier-prefixes="G" c:symbol-prefixes="g">
+    <class name="Object" c:type="GObject" c:symbol-prefix="object"
+           glib:type-name="GObject" glib:get-type="g_object_get_type">
+    </class>
+  </namespace>
+</repository>
+"#;
+
+    const GIO_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <include name="GObject" version="2.0"/>
+  <namespace name="Gio" version="2.0" shared-library="libgio-2.0.so.0"
+             c:identifier-prefixes="G" c:symbol-prefixes="g">
+    <callback name="AsyncReadyCallback" c:type="GAsyncReadyCallback">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="source_object" transfer-ownership="none" nullable="1">
+          <type name="gpointer" c:type="GObject*"/>
+        </parameter>
+        <parameter name="res" transfer-ownership="none">
+          <type name="AsyncResult" c:type="GAsyncResult*"/>
+        </parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1" closure="2">
+          <type name="gpointer" c:type="gpointer"/>
+        </parameter>
+      </parameters>
+    </callback>
+    <interface name="AsyncResult" c:type="GAsyncResult" c:symbol-prefix="async_result"
+               glib:type-name="GAsyncResult"
+               glib:get-type="g_async_result_get_type">
+      <prerequisite name="GObject.Object"/>
+    </interface>
+    <class name="Cancellable" c:type="GCancellable" c:symbol-prefix="cancellable"
+           glib:type-name="GCancellable"
+           glib:get-type="g_cancellable_get_type" parent="GObject.Object">
+    </class>
+    <function name="fetch_finish" c:identifier="g_fetch_finish" throws="1">
+      <return-value transfer-ownership="none"><type name="gint" c:type="gint"/></return-value>
+      <parameters>
+        <parameter name="result" transfer-ownership="none">
+          <type name="AsyncResult" c:type="GAsyncResult*"/>
+        </parameter>
+      </parameters>
+    </function>
+  </namespace>
+</repository>
+"#;
+
+    const ASYNC_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <include name="Gio" version="2.0"/>
+  <namespace name="Foo" version="1.0" shared-library="libfoo-1.0.so.0"
+             c:identifier-prefixes="Foo" c:symbol-prefixes="foo">
+    <callback name="ProgressCallback" c:type="FooProgressCallback">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="current" transfer-ownership="none">
+          <type name="gint" c:type="gint"/>
+        </parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1" closure="1">
+          <type name="gpointer" c:type="gpointer"/>
+        </parameter>
+      </parameters>
+    </callback>
+    <function name="load_async" c:identifier="foo_load_async" glib:finish-func="load_finish">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="cancellable" transfer-ownership="none" nullable="1">
+          <type name="Gio.Cancellable" c:type="GCancellable*"/>
+        </parameter>
+        <parameter name="progress" transfer-ownership="none" {}closure="2">
+          <type name="ProgressCallback" c:type="FooProgressCallback"/>
+        </parameter>
+        <parameter name="progress_data" transfer-ownership="none" nullable="1">
+          <type name="gpointer" c:type="gpointer"/>
+        </parameter>
+        <parameter name="callback" transfer-ownership="none" scope="async" closure="4">
+          <type name="Gio.AsyncReadyCallback" c:type="GAsyncReadyCallback"/>
+        </parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1">
+          <type name="gpointer" c:type="gpointer"/>
+        </parameter>
+      </parameters>
+    </function>
+    <function name="fetch_async" c:identifier="foo_fetch_async" glib:finish-func="g_fetch_finish">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="cancellable" transfer-ownership="none" nullable="1">
+          <type name="Gio.Cancellable" c:type="GCancellable*"/>
+        </parameter>
+        <parameter name="callback" transfer-ownership="none" scope="async" closure="2">
+          <type name="Gio.AsyncReadyCallback" c:type="GAsyncReadyCallback"/>
+        </parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1">
+          <type name="gpointer" c:type="gpointer"/>
+        </parameter>
+      </parameters>
+    </function>
+    <function name="load_finish" c:identifier="foo_load_finish" throws="1">
+      <return-value transfer-ownership="none"><type name="gboolean" c:type="gboolean"/></return-value>
+      <parameters>
+        <parameter name="result" transfer-ownership="none">
+          <type name="Gio.AsyncResult" c:type="GAsyncResult*"/>
+        </parameter>
+      </parameters>
+    </function>
+  </namespace>
+</repository>
+"#;
+
+    /// Generates the global function `name` of the `Foo` namespace read from `girs`,
+    /// `config` is inserted after the options of the config.
+    fn generate_global_function(girs: &[&str], config: &str, name: &str) -> String {
+        let toml = format!(
+            "[options]\nlibrary = \"Foo\"\nversion = \"1.0\"\n\
+             target_path = \".\"\nwork_mode = \"normal\"\n\
+             manual = [\"GLib.Error\", \"GObject.Object\", \"Gio.AsyncResult\", \"Gio.Cancellable\"]\n\
+             {}\n[[object]]\nname = \"Foo.*\"\nstatus = \"generate\"\n",
+            config
+        );
+        let env = Env::for_test(girs, &toml);
+        let functions = &env.analysis.global_functions.as_ref().unwrap().functions;
+        let function = functions.iter().find(|f| f.name == name).unwrap();
+        let mut w = Vec::new();
+        generate(&mut w, &env, function, None, false, false, 0).unwrap();
+        String::from_utf8(w).unwrap()
+    }
+
+    /// Generates the global function `name` of `ASYNC_GIR`, `scope` being the
+    /// attributes of the progress callback of `load_async`.
+    fn generate_async_function(scope: &str, name: &str) -> String {
+        let gir = ASYNC_GIR.replace("{}", scope);
+        generate_global_function(&[GLIB_GIR, GOBJECT_GIR, GIO_GIR, &gir], "", name)
+    }
+
+    #[test]
+    fn test_async_extra_callback() {
+        let code = generate_async_function("scope=\"notified\" destroy=\"3\" ", "load_async");
+        assert!(code.contains("//pub fn load_async"), "{}", code);
+        let code = generate_async_function("scope=\"async\" ", "load_async");
+        assert!(code.contains("//pub fn load_async"), "{}", code);
+        let code = generate_async_function("scope=\"call\" ", "load_async");
+        assert!(code.contains("//pub fn load_async"), "{}", code);
+
+        let code = generate_async_function("", "load_async");
+        assert!(
+            code.contains("let callback: &P = &*(user_data as *mut _);"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("Box_::new((callback, Box_::new((super_callback0,))"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("ffi::foo_load_async(cancellable.to_glib_none().0, progress, super_callback0_ptr, Some(callback), Box_::into_raw(user_data) as *mut _);"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("futures_core::stream::Stream<Item = i32>"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_async_included_finish_function() {
+        let code = generate_async_function("", "fetch_async");
+        assert!(
+            code.contains("FnOnce(Result<i32, glib::Error>) + Send + 'static>(cancellable: Option<&gio::Cancellable>, callback: P)"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("let ret = gio::ffi::g_fetch_finish(res, &mut error);"),
+            "{}",
+            code
+        );
+        assert!(code.contains("pub fn fetch_async_future("), "{}", code);
+    }
+
+    /// Wraps `content` in a `Foo` namespace including GLib and GObject.
+    fn foo_gir(content: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <include name="GObject" version="2.0"/>
+  <namespace name="Foo" version="1.0" shared-library="libfoo-1.0.so.0"
+             c:identifier-prefixes="Foo" c:symbol-prefixes="foo">
+    {}
+  </namespace>
+</repository>
+"#,
+            content
+        )
+    }
+
+    const FOO_TYPES: &str = r#"<enumeration name="Color" c:type="FooColor">
+      <member name="red" value="0" c:identifier="FOO_COLOR_RED"/>
+    </enumeration>
+    <bitfield name="Style" c:type="FooStyle">
+      <member name="bold" value="1" c:identifier="FOO_STYLE_BOLD"/>
+    </bitfield>
+    <record name="Point" c:type="FooPoint">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+    </record>"#;
+
+    /// Generates the global function `name` with the given parameters and return value,
+    /// alongside the types of `FOO_TYPES`.
+    fn generate_simple_function(name: &str, parameter: &str, ret: &str) -> String {
+        let ret = if ret.is_empty() {
+            r#"<return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>"#
+        } else {
+            ret
+        };
+        let gir = foo_gir(&format!(
+            r#"{}
+    <function name="{}" c:identifier="foo_{}">
+      {}
+      <parameters>{}</parameters>
+    </function>"#,
+            FOO_TYPES, name, name, ret, parameter
+        ));
+        generate_global_function(
+            &[GLIB_GIR, GOBJECT_GIR, &gir],
+            "generate = [\"Foo.Color\", \"Foo.Style\", \"Foo.Point\"]",
+            name,
+        )
+    }
+
+    const STRING_TABLE: &str = r#"<type name="GLib.HashTable" c:type="GHashTable*">
+          <type name="utf8"/><type name="utf8"/>
+        </type>"#;
+
+    #[test]
+    fn test_hash_table_in() {
+        for transfer in &["none", "full"] {
+            let code = generate_simple_function(
+                "take",
+                &format!(
+                    r#"<parameter name="table" transfer-ownership="{}">{}</parameter>"#,
+                    transfer, STRING_TABLE
+                ),
+                "",
+            );
+            assert!(
+                code.contains("pub fn take(table: &HashMap<String, String>) {"),
+                "{}",
+                code
+            );
+            assert!(
+                code.contains(
+                    "let table_table = { let table_table = glib::ffi::g_hash_table_new_full(\
+                     Some(glib::ffi::g_str_hash), Some(glib::ffi::g_str_equal), \
+                     Some(glib::ffi::g_free), Some(glib::ffi::g_free)); \
+                     for (key, value) in table { glib::ffi::g_hash_table_insert(table_table, "
+                ),
+                "{}",
+                code
+            );
+            assert!(
+                code.contains("        ffi::foo_take(table_table);\n"),
+                "{}",
+                code
+            );
+            // The callee owns the table given with a full transfer
+            assert_eq!(
+                code.contains("glib::ffi::g_hash_table_unref(table_table);"),
+                *transfer == "none",
+                "{}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_table_out() {
+        let from_glib = |table: &str, transfer: &str| {
+            format!(
+                "{{ let table = {}; let mut map = HashMap::new(); if !table.is_null() {{ \
+                 let mut iter = std::mem::MaybeUninit::uninit(); \
+                 glib::ffi::g_hash_table_iter_init(iter.as_mut_ptr(), table); \
+                 let mut key = std::ptr::null_mut(); let mut value = std::ptr::null_mut(); \
+                 while glib::ffi::g_hash_table_iter_next(iter.as_mut_ptr(), &mut key, &mut value) \
+                 != glib::ffi::GFALSE {{ map.insert(from_glib_none(key as *const _), \
+                 from_glib_none(value as *const _)); }}{} }} map }}",
+                table,
+                if transfer == "none" {
+                    ""
+                } else {
+                    " glib::ffi::g_hash_table_unref(table);"
+                }
+            )
+        };
+
+        for transfer in &["none", "container", "full"] {
+            let code = generate_simple_function(
+                "get",
+                "",
+                &format!(
+                    r#"<return-value transfer-ownership="{}">{}</return-value>"#,
+                    transfer, STRING_TABLE
+                ),
+            );
+            assert!(
+                code.contains("pub fn get() -> HashMap<String, String> {"),
+                "{}",
+                code
+            );
+            assert!(
+                code.contains(&from_glib("ffi::foo_get()", transfer)),
+                "{}",
+                code
+            );
+
+            let code = generate_simple_function(
+                "get_out",
+                &format!(
+                    r#"<parameter name="table" direction="out" transfer-ownership="{}">{}</parameter>"#,
+                    transfer, STRING_TABLE
+                ),
+                "",
+            );
+            assert!(
+                code.contains("pub fn get_out() -> HashMap<String, String> {"),
+                "{}",
+                code
+            );
+            assert!(
+                code.contains(
+                    "let mut table = ptr::null_mut();\n        ffi::foo_get_out(&mut table);"
+                ),
+                "{}",
+                code
+            );
+            assert!(code.contains(&from_glib("table", transfer)), "{}", code);
+        }
+    }
+
+    /// Element types of arrays converted element by element: their GIR name, C type,
+    /// Rust type and conversions to and from their C value.
+    const ARRAY_ELEMENTS: &[(&str, &str, &str, &str, &str)] = &[
+        ("Color", "FooColor", "Color", "v.to_glib()", "from_glib(*v)"),
+        ("Style", "FooStyle", "Style", "v.to_glib()", "from_glib(*v)"),
+        (
+            "gboolean",
+            "gboolean",
+            "bool",
+            "v.to_glib()",
+            "from_glib(*v)",
+        ),
+        (
+            "Point",
+            "FooPoint",
+            "Point",
+            "std::ptr::read(v.to_glib_none().0)",
+            "from_glib_none(v as *const _)",
+        ),
+    ];
+
+    const LENGTH: &str = r#"<parameter name="n" transfer-ownership="none">
+          <type name="gsize" c:type="gsize"/>
+        </parameter>"#;
+
+    const OUT_LENGTH: &str = r#"<parameter name="n" direction="out" transfer-ownership="full">
+          <type name="gsize" c:type="gsize*"/>
+        </parameter>"#;
+
+    #[test]
+    fn test_array_elements_in() {
+        for &(name, c_type, rust_type, to_glib, _) in ARRAY_ELEMENTS {
+            let parameter = |transfer| {
+                format!(
+                    r#"<parameter name="items" transfer-ownership="{}">
+          <array length="1" c:type="{}*"><type name="{}" c:type="{}"/></array>
+        </parameter>{}"#,
+                    transfer, c_type, name, c_type, LENGTH
+                )
+            };
+            let item_type = if name == "Point" {
+                "&Point".to_owned()
+            } else {
+                rust_type.to_owned()
+            };
+
+            let code = generate_simple_function("take", &parameter("none"), "");
+            assert!(
+                code.contains(&format!("pub fn take(items: &[{}]) {{", item_type)),
+                "{}",
+                code
+            );
+            assert!(
+                code.contains(&format!(
+                    "ffi::foo_take(items.iter().map(|v| {}).collect::<Vec<_>>().as_mut_ptr(), n);",
+                    to_glib
+                )),
+                "{}",
+                code
+            );
+
+            // The temporary array can't be given away
+            let code = generate_simple_function("take", &parameter("full"), "");
+            assert!(
+                code.contains(&format!("//pub fn take(items: &[{}]) {{", item_type)),
+                "{}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_array_elements_out() {
+        for &(name, c_type, rust_type, _, from_glib) in ARRAY_ELEMENTS {
+            for transfer in &["none", "full"] {
+                let from_glib = |array: &str| {
+                    format!(
+                        "{{ let array = {}; let vec = if array.is_null() {{ Vec::new() }} else {{ \
+                         std::slice::from_raw_parts(array, n.assume_init() as usize).iter()\
+                         .map(|v| {}).collect() }};{} vec }}",
+                        array,
+                        from_glib,
+                        if *transfer == "none" {
+                            ""
+                        } else {
+                            " glib::ffi::g_free(array as *mut _);"
+                        }
+                    )
+                };
+
+                let code = generate_simple_function(
+                    "get",
+                    OUT_LENGTH,
+                    &format!(
+                        r#"<return-value transfer-ownership="{}">
+          <array length="0" c:type="{}*"><type name="{}" c:type="{}"/></array>
+        </return-value>"#,
+                        transfer, c_type, name, c_type
+                    ),
+                );
+                assert!(
+                    code.contains(&format!("pub fn get() -> Vec<{}> {{", rust_type)),
+                    "{}",
+                    code
+                );
+                assert!(
+                    code.contains(&from_glib("ffi::foo_get(n.as_mut_ptr())")),
+                    "{}",
+                    code
+                );
+
+                let code = generate_simple_function(
+                    "get_out",
+                    &format!(
+                        r#"<parameter name="items" direction="out" transfer-ownership="{}">
+          <array length="1" c:type="{}**"><type name="{}" c:type="{}"/></array>
+        </parameter>{}"#,
+                        transfer, c_type, name, c_type, OUT_LENGTH
+                    ),
+                    "",
+                );
+                assert!(
+                    code.contains(&format!("pub fn get_out() -> Vec<{}> {{", rust_type)),
+                    "{}",
+                    code
+                );
+                assert!(
+                    code.contains("ffi::foo_get_out(&mut items, n.as_mut_ptr());"),
+                    "{}",
+                    code
+                );
+                assert!(code.contains(&from_glib("items")), "{}", code);
+            }
+        }
+    }
+
+    const FIXED_ARRAY: &str = r#"<array zero-terminated="0" fixed-size="4" c:type="guint8*">
+            <type name="guint8" c:type="guint8"/>
+          </array>"#;
+
+    #[test]
+    fn test_fixed_array_in() {
+        let code = generate_simple_function(
+            "take",
+            &format!(
+                r#"<parameter name="data" transfer-ownership="none">{}</parameter>"#,
+                FIXED_ARRAY
+            ),
+            "",
+        );
+        assert!(code.contains("pub fn take(data: &[u8; 4]) {"), "{}", code);
+        assert!(!code.contains("assert_eq!"), "{}", code);
+        assert!(
+            code.contains("ffi::foo_take(data.as_ptr() as *mut _);"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_fixed_array_out() {
+        // Returned pointers aren't trusted to be non-null
+        let code = generate_simple_function(
+            "get",
+            "",
+            &format!(
+                r#"<return-value transfer-ownership="none">{}</return-value>"#,
+                FIXED_ARRAY
+            ),
+        );
+        assert!(
+            code.contains("pub fn get() -> Option<[u8; 4]> {"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains(
+                "{ let array = ffi::foo_get(); if array.is_null() { None } else { \
+                 let res = *(array as *const [_; 4]); Some(res) } }"
+            ),
+            "{}",
+            code
+        );
+
+        let out = |nullable| {
+            generate_simple_function(
+                "get_out",
+                &format!(
+                    r#"<parameter name="data" direction="out" transfer-ownership="full" nullable="{}">{}</parameter>"#,
+                    nullable,
+                    FIXED_ARRAY.replace("guint8*\"", "guint8**\"")
+                ),
+                "",
+            )
+        };
+        let code = out("1");
+        assert!(
+            code.contains("pub fn get_out() -> Option<[u8; 4]> {"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains(
+                "{ let array = data; if array.is_null() { None } else { \
+                 let res = *(array as *const [_; 4]); glib::ffi::g_free(array as *mut _); \
+                 Some(res) } }"
+            ),
+            "{}",
+            code
+        );
+        let code = out("0");
+        assert!(code.contains("pub fn get_out() -> [u8; 4] {"), "{}", code);
+        assert!(
+            code.contains(
+                "{ let array = data; let res = *(array as *const [_; 4]); \
+                 glib::ffi::g_free(array as *mut _); res }"
+            ),
+            "{}",
+            code
+        );
+
+        // Caller-allocated arrays are always filled
+        let code = generate_simple_function(
+            "fill",
+            &format!(
+                r#"<parameter name="data" direction="out" caller-allocates="1" transfer-ownership="none" nullable="1">{}</parameter>"#,
+                FIXED_ARRAY
+            ),
+            "",
+        );
+        assert!(code.contains("pub fn fill() -> [u8; 4] {"), "{}", code);
+        assert!(
+            code.contains("ffi::foo_fill(data.as_mut_ptr() as *mut _);"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_iterator_return() {
+        assert_eq!(
+            iterator_return("FileInfo", false, "iter"),
+            (
+                " -> impl Iterator<Item = FileInfo> + '_".to_owned(),
+                "iter".to_owned()
+            )
+        );
+        assert_eq!(
+            iterator_return("FileInfo", true, "iter"),
+            (
+                " -> Box_<dyn Iterator<Item = FileInfo> + '_>".to_owned(),
+                "Box_::new(iter)".to_owned()
+            )
+        );
+    }
+}
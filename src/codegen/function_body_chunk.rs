@@ -5,7 +5,7 @@ use crate::{
             CParameter as AnalysisCParameter, Transformation, TransformationType,
         },
         functions::{find_index_to_ignore, AsyncTrampoline},
-        out_parameters::Mode,
+        out_parameters::{Mode, OutStruct},
         return_value,
         rust_type::rust_type,
         safety_assertion_mode::SafetyAssertionMode,
@@ -62,6 +62,7 @@ pub struct Builder {
     outs_as_return: bool,
     in_unsafe: bool,
     outs_mode: Mode,
+    out_struct: Option<OutStruct>,
     assertion: SafetyAssertionMode,
 }
 
@@ -129,6 +130,10 @@ impl Builder {
         self.outs_mode = mode;
         self
     }
+    pub fn out_struct(&mut self, out_struct: &OutStruct) -> &mut Builder {
+        self.out_struct = Some(out_struct.clone());
+        self
+    }
     pub fn in_unsafe(&mut self, in_unsafe: bool) -> &mut Builder {
         self.in_unsafe = in_unsafe;
         self
@@ -204,11 +209,39 @@ impl Builder {
         let call = self.generate_call_conversion(call, &mut uninitialized_vars);
         let ret = self.generate_out_return(&mut uninitialized_vars);
         let (call, ret) = self.apply_outs_mode(call, ret, &mut uninitialized_vars);
+        let ret = ret.map(|ret| self.apply_out_struct(ret));
 
-        body.push(call);
-        self.write_out_uninitialized(&mut body, uninitialized_vars);
-        if let Some(chunk) = ret {
-            body.push(chunk);
+        self.add_in_hash_tables(env, &mut body);
+        if self.has_in_hash_tables_to_free() {
+            // The tables have to be freed after the call, so it can't be the returned expression.
+            match ret {
+                Some(ret) => {
+                    body.push(call);
+                    self.free_in_hash_tables(env, &mut body);
+                    self.write_out_uninitialized(&mut body, uninitialized_vars);
+                    body.push(ret);
+                }
+                None if self.ret.ret.parameter.is_some() => {
+                    body.push(Chunk::Let {
+                        name: "ret".into(),
+                        is_mut: false,
+                        value: Box::new(call),
+                        type_: None,
+                    });
+                    self.free_in_hash_tables(env, &mut body);
+                    body.push(Chunk::Custom("ret".into()));
+                }
+                None => {
+                    body.push(call);
+                    self.free_in_hash_tables(env, &mut body);
+                }
+            }
+        } else {
+            body.push(call);
+            self.write_out_uninitialized(&mut body, uninitialized_vars);
+            if let Some(chunk) = ret {
+                body.push(chunk);
+            }
         }
 
         let mut chunks = Vec::new();
@@ -321,8 +354,19 @@ impl Builder {
                     });
                 }
             }
-        } else if let Some(ref trampoline) = self.async_trampoline {
-            self.add_async_trampoline(env, &mut chunks, trampoline);
+        }
+        if let Some(ref trampoline) = self.async_trampoline {
+            // The other callbacks have to be kept alive until the async operation is done.
+            let mut super_callbacks = group_by_user_data
+                .values()
+                .map(|FuncParameter { pos, .. }| *pos)
+                .collect::<Vec<_>>();
+            super_callbacks.sort_unstable();
+            let super_callbacks = super_callbacks
+                .into_iter()
+                .map(|pos| format!("super_callback{}", pos))
+                .collect::<Vec<_>>();
+            self.add_async_trampoline(env, &mut chunks, trampoline, &super_callbacks);
         }
 
         chunks.push(if self.in_unsafe {
@@ -711,15 +755,39 @@ impl Builder {
         env: &Env,
         chunks: &mut Vec<Chunk>,
         trampoline: &AsyncTrampoline,
+        super_callbacks: &[String],
     ) {
+        let user_data_type = if super_callbacks.is_empty() {
+            format!("Box_<{}>", trampoline.bound_name)
+        } else {
+            format!("Box_<({}, Box_<dyn std::any::Any>)>", trampoline.bound_name)
+        };
+        for super_callback in super_callbacks {
+            chunks.push(Chunk::Let {
+                name: format!("{}_ptr", super_callback),
+                is_mut: false,
+                value: Box::new(Chunk::Custom(format!(
+                    "&*{} as *const _ as *mut _",
+                    super_callback
+                ))),
+                type_: Some(Box::new(Chunk::Custom(use_glib_if_needed(
+                    env,
+                    "ffi::gpointer",
+                )))),
+            });
+        }
         chunks.push(Chunk::Let {
             name: "user_data".to_string(),
             is_mut: false,
-            value: Box::new(Chunk::Custom("Box_::new(callback)".into())),
-            type_: Some(Box::new(Chunk::Custom(format!(
-                "Box_<{}>",
-                trampoline.bound_name
-            )))),
+            value: Box::new(Chunk::Custom(if super_callbacks.is_empty() {
+                "Box_::new(callback)".to_owned()
+            } else {
+                format!(
+                    "Box_::new((callback, Box_::new(({},)) as Box_<dyn std::any::Any>))",
+                    super_callbacks.join(", ")
+                )
+            })),
+            type_: Some(Box::new(Chunk::Custom(user_data_type.clone()))),
         });
 
         let mut finish_args = vec![];
@@ -857,11 +925,12 @@ impl Builder {
             name: "callback".to_string(),
             is_mut: false,
             value: Box::new(Chunk::Custom("Box_::from_raw(user_data as *mut _)".into())),
-            type_: Some(Box::new(Chunk::Custom(format!(
-                "Box_<{}>",
-                trampoline.bound_name
-            )))),
+            type_: Some(Box::new(Chunk::Custom(user_data_type))),
         });
+        if !super_callbacks.is_empty() {
+            // Dropping the other callbacks now that the async operation is done.
+            body.push(Chunk::Custom("let (callback, _) = *callback;".to_owned()));
+        }
         body.push(Chunk::Call {
             func_name: "callback".to_string(),
             arguments: vec![Chunk::Name("result".to_string())],
@@ -941,6 +1010,69 @@ impl Builder {
         }
     }
 
+    fn add_in_hash_tables(&self, env: &Env, body: &mut Vec<Chunk>) {
+        for trans in &self.transformations {
+            if let TransformationType::ToGlibHashTable {
+                ref name,
+                key,
+                value,
+                nullable,
+                ..
+            } = trans.transformation_type
+            {
+                let table = hash_table_to_glib(env, name, key, value);
+                let value = if nullable {
+                    format!(
+                        "match {name} {{ Some({name}) => {table}, None => std::ptr::null_mut() }}",
+                        name = name,
+                        table = table
+                    )
+                } else {
+                    table
+                };
+                body.push(Chunk::Let {
+                    name: format!("{}_table", name),
+                    is_mut: false,
+                    value: Box::new(Chunk::Custom(value)),
+                    type_: None,
+                });
+            }
+        }
+    }
+
+    fn in_hash_tables_to_free(&self) -> impl Iterator<Item = (&String, bool)> {
+        self.transformations
+            .iter()
+            .filter_map(|trans| match trans.transformation_type {
+                TransformationType::ToGlibHashTable {
+                    ref name,
+                    transfer: library::Transfer::None,
+                    nullable,
+                    ..
+                } => Some((name, nullable)),
+                _ => None,
+            })
+    }
+
+    fn has_in_hash_tables_to_free(&self) -> bool {
+        self.in_hash_tables_to_free().next().is_some()
+    }
+
+    fn free_in_hash_tables(&self, env: &Env, body: &mut Vec<Chunk>) {
+        let unref = use_glib_type(env, "ffi::g_hash_table_unref");
+        for (name, nullable) in self.in_hash_tables_to_free() {
+            body.push(Chunk::Custom(if nullable {
+                format!(
+                    "if !{name}_table.is_null() {{ {unref}({name}_table); }}",
+                    name = name,
+                    unref = unref
+                )
+            } else {
+                format!("{}({}_table);", unref, name)
+            }));
+        }
+    }
+
     fn generate_call(&self, calls: &FuncParameters<'_>) -> Chunk {
         let params = self.generate_func_parameters(calls);
         let func = Chunk::FfiCall {
@@ -988,6 +1120,9 @@ impl Builder {
                     transformation_type: TransformationType::ToGlibDirect {
                         name: if all_call {
                             format!("super_callback{} as *const _ as usize as *mut _", pos)
+                        } else if self.async_trampoline.is_some() {
+                            // Owned by the async callback's user data.
+                            format!("super_callback{}_ptr", pos)
                         } else {
                             format!("Box_::into_raw(super_callback{}) as *mut _", pos)
                         },
@@ -1212,6 +1347,33 @@ impl Builder {
         }
     }
 
+    /// Replaces the returned tuple of outputs by the out struct.
+    fn apply_out_struct(&self, ret: Chunk) -> Chunk {
+        let out_struct = match self.out_struct {
+            Some(ref out_struct) => out_struct,
+            None => return ret,
+        };
+        match ret {
+            Chunk::Tuple(values, _) => Chunk::Struct {
+                name: out_struct.name.clone(),
+                fields: out_struct
+                    .fields
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .zip(values)
+                    .collect(),
+            },
+            Chunk::OptionalReturn { condition, value } => Chunk::OptionalReturn {
+                condition,
+                value: Box::new(self.apply_out_struct(*value)),
+            },
+            Chunk::ErrorResultReturn { value } => Chunk::ErrorResultReturn {
+                value: Box::new(self.apply_out_struct(*value)),
+            },
+            _ => ret,
+        }
+    }
+
     fn find_array_length_name(&self, array_name_: &str) -> Option<String> {
         self.transformations
             .iter()
@@ -1243,6 +1405,7 @@ fn c_type_mem_mode_lib(
 ) -> OutMemMode {
     use self::OutMemMode::*;
     match ConversionType::of(env, typ) {
+        ConversionType::Pointer if caller_allocates && is_fixed_array(env, typ) => Uninitialized,
         ConversionType::Pointer => {
             if caller_allocates {
                 UninitializedNamed(rust_type(env, typ).unwrap())
@@ -1269,6 +1432,69 @@ fn c_type_mem_mode_lib(
     }
 }
 
+/// Returns the expression building a `GHashTable` owning copies of the entries of a `HashMap`.
+fn hash_table_to_glib(env: &Env, name: &str, key: TypeId, value: TypeId) -> String {
+    let (key_copy, key_destroy) = hash_table_item_to_glib(env, key, "key");
+    let (value_copy, value_destroy) = hash_table_item_to_glib(env, value, "value");
+    let (hash, equal) = match *env.library.type_(key) {
+        library::Type::Fundamental(library::Fundamental::Utf8) => ("g_str_hash", "g_str_equal"),
+        _ => ("g_direct_hash", "g_direct_equal"),
+    };
+    format!(
+        "{{ let {name}_table = {new}(Some({hash}), Some({equal}), Some({key_destroy}), \
+         Some({value_destroy})); \
+         for (key, value) in {name} {{ {insert}({name}_table, {key_copy}, {value_copy}); }} \
+         {name}_table }}",
+        new = use_glib_type(env, "ffi::g_hash_table_new_full"),
+        hash = use_glib_type(env, &format!("ffi::{}", hash)),
+        equal = use_glib_type(env, &format!("ffi::{}", equal)),
+        key_destroy = key_destroy,
+        value_destroy = value_destroy,
+        name = name,
+        insert = use_glib_type(env, "ffi::g_hash_table_insert"),
+        key_copy = key_copy,
+        value_copy = value_copy,
+    )
+}
+
+/// Returns the expression copying a key or a value into a `GHashTable` and the function
+/// freeing it.
+///
+/// Only strings and objects can be stored in a `HashMap`, see `rust_type`.
+fn hash_table_item_to_glib(env: &Env, typ: TypeId, name: &str) -> (String, String) {
+    match *env.library.type_(typ) {
+        library::Type::Fundamental(library::Fundamental::Utf8) => (
+            format!(
+                "{}({name}.as_ptr() as *const _, {name}.len()) as {}",
+                use_glib_type(env, "ffi::g_strndup"),
+                use_glib_type(env, "ffi::gpointer"),
+                name = name
+            ),
+            use_glib_type(env, "ffi::g_free"),
+        ),
+        _ => (
+            format!(
+                "{}({}({}) as *mut _)",
+                use_glib_type(env, "gobject_ffi::g_object_ref"),
+                use_glib_type(env, "object::ObjectType::as_ptr"),
+                name
+            ),
+            format!(
+                "std::mem::transmute::<unsafe extern \"C\" fn(*mut {}), \
+                 unsafe extern \"C\" fn({})>({})",
+                use_glib_type(env, "gobject_ffi::GObject"),
+                use_glib_type(env, "ffi::gpointer"),
+                use_glib_type(env, "gobject_ffi::g_object_unref")
+            ),
+        ),
+    }
+}
+
+/// Caller-allocated fixed arrays are filled in place through a `MaybeUninit`.
+fn is_fixed_array(env: &Env, typ: library::TypeId) -> bool {
+    matches!(env.library.type_(typ), library::Type::FixedArray(..))
+}
+
 fn c_type_mem_mode(env: &Env, parameter: &AnalysisCParameter) -> OutMemMode {
     c_type_mem_mode_lib(
         env,
@@ -1280,6 +1506,11 @@ fn c_type_mem_mode(env: &Env, parameter: &AnalysisCParameter) -> OutMemMode {
 
 fn type_mem_mode(env: &Env, parameter: &library::Parameter) -> Chunk {
     match ConversionType::of(env, parameter.typ) {
+        ConversionType::Pointer
+            if parameter.caller_allocates && is_fixed_array(env, parameter.typ) =>
+        {
+            Chunk::Uninitialized
+        }
         ConversionType::Pointer => {
             if parameter.caller_allocates {
                 Chunk::UninitializedNamed {
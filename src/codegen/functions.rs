@@ -15,7 +15,7 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
     };
 
     let path = root_path.join("functions.rs");
-    file_saver::save_to_file(path, env.config.make_backup, |w| {
+    file_saver::save_to_file(path, env, |w| {
         general::start_comments(w, &env.config)?;
         general::uses(w, env, &functions.imports)?;
 
@@ -27,6 +27,10 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
             function::generate(w, env, func_analysis, None, false, false, 0)?;
         }
 
+        for func_analysis in &functions.functions {
+            function::generate_out_struct(w, env, func_analysis)?;
+        }
+
         Ok(())
     });
 }
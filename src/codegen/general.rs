@@ -463,6 +463,130 @@ pub fn define_shared_type(
     Ok(())
 }
 
+pub fn define_direct_type(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    glib_name: &str,
+    derive: &[Derive],
+) -> Result<()> {
+    let ffi_type = format!("{}::{}", env.main_sys_crate_name(), glib_name);
+    writeln!(w)?;
+    writeln!(w, "#[repr(transparent)]")?;
+    derives(w, derive, 0)?;
+    writeln!(w, "pub struct {}({});", type_name, ffi_type)?;
+
+    writeln!(w)?;
+    writeln!(w, "#[doc(hidden)]")?;
+    writeln!(w, "impl Uninitialized for {} {{", type_name)?;
+    writeln!(w, "\t#[inline]")?;
+    writeln!(w, "\tunsafe fn uninitialized() -> Self {{")?;
+    writeln!(w, "\t\tmem::zeroed()")?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")?;
+
+    writeln!(w)?;
+    writeln!(w, "#[doc(hidden)]")?;
+    writeln!(w, "impl ToGlib for {} {{", type_name)?;
+    writeln!(w, "\ttype GlibType = {};", ffi_type)?;
+    writeln!(w)?;
+    writeln!(w, "\tfn to_glib(&self) -> {} {{", ffi_type)?;
+    writeln!(w, "\t\tself.0")?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")?;
+
+    writeln!(w)?;
+    writeln!(w, "#[doc(hidden)]")?;
+    writeln!(w, "impl FromGlib<{}> for {} {{", ffi_type, type_name)?;
+    writeln!(w, "\tfn from_glib(value: {}) -> Self {{", ffi_type)?;
+    writeln!(w, "\t\t{}(value)", type_name)?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")?;
+
+    // Mutable pointers are only handed out through `ToGlibPtrMut`
+    writeln!(w)?;
+    writeln!(w, "#[doc(hidden)]")?;
+    writeln!(
+        w,
+        "impl<'a> ToGlibPtr<'a, *const {}> for {} {{",
+        ffi_type, type_name
+    )?;
+    writeln!(w, "\ttype Storage = &'a Self;")?;
+    writeln!(w)?;
+    writeln!(w, "\t#[inline]")?;
+    writeln!(
+        w,
+        "\tfn to_glib_none(&'a self) -> Stash<'a, *const {}, Self> {{",
+        ffi_type
+    )?;
+    writeln!(w, "\t\tStash(&self.0, self)")?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")?;
+
+    writeln!(w)?;
+    writeln!(w, "#[doc(hidden)]")?;
+    writeln!(
+        w,
+        "impl<'a> ToGlibPtrMut<'a, *mut {}> for {} {{",
+        ffi_type, type_name
+    )?;
+    writeln!(w, "\ttype Storage = &'a mut Self;")?;
+    writeln!(w)?;
+    writeln!(w, "\t#[inline]")?;
+    writeln!(
+        w,
+        "\tfn to_glib_none_mut(&'a mut self) -> StashMut<'a, *mut {}, Self> {{",
+        ffi_type
+    )?;
+    writeln!(w, "\t\tStashMut(&mut self.0, self)")?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")?;
+
+    for mutability in &["const", "mut"] {
+        writeln!(w)?;
+        writeln!(w, "#[doc(hidden)]")?;
+        writeln!(
+            w,
+            "impl FromGlibPtrNone<*{} {}> for {} {{",
+            mutability, ffi_type, type_name
+        )?;
+        writeln!(
+            w,
+            "\tunsafe fn from_glib_none(ptr: *{} {}) -> Self {{",
+            mutability, ffi_type
+        )?;
+        writeln!(w, "\t\tassert!(!ptr.is_null());")?;
+        writeln!(w, "\t\t{}(*ptr)", type_name)?;
+        writeln!(w, "\t}}")?;
+        writeln!(w, "}}")?;
+    }
+
+    writeln!(w)?;
+    writeln!(w, "#[doc(hidden)]")?;
+    writeln!(
+        w,
+        "impl FromGlibPtrFull<*mut {}> for {} {{",
+        ffi_type, type_name
+    )?;
+    writeln!(
+        w,
+        "\tunsafe fn from_glib_full(ptr: *mut {}) -> Self {{",
+        ffi_type
+    )?;
+    writeln!(w, "\t\tassert!(!ptr.is_null());")?;
+    writeln!(w, "\t\tlet res = {}(*ptr);", type_name)?;
+    writeln!(
+        w,
+        "\t\t{}(ptr as *mut _);",
+        use_glib_type(env, "ffi::g_free")
+    )?;
+    writeln!(w, "\t\tres")?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")?;
+
+    Ok(())
+}
+
 pub fn cfg_deprecated(
     w: &mut dyn Write,
     env: &Env,
@@ -1,11 +1,16 @@
 use crate::{config::WorkMode, env::Env, file_saver::*};
-use std::path::Path;
+use std::{
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
 
 mod alias;
 mod child_properties;
 mod constants;
 mod doc;
 mod enums;
+mod field_accessors;
 mod flags;
 pub mod function;
 mod function_body_chunk;
@@ -54,11 +59,50 @@ fn normal_generate(env: &Env) {
     constants::generate(env, root_path, &mut mod_rs);
 
     generate_mod_rs(env, root_path, &mod_rs, &traits);
+    remove_stale_files(env, root_path);
+}
+
+/// Runs `generate` for all `items` in parallel threads, `env` isn't changed
+/// anymore during code generation. The results are in the order of `items`.
+fn generate_parallel<T, R, F>(env: &Env, items: &[T], generate: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let jobs = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(items.len());
+    let next = AtomicUsize::new(0);
+
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    env.config.use_crate_name_overrides();
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(index) {
+                            Some(item) => results.push((index, generate(item))),
+                            None => break results,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
 }
 
 pub fn generate_mod_rs(env: &Env, root_path: &Path, mod_rs: &[String], traits: &[String]) {
     let path = root_path.join("mod.rs");
-    save_to_file(path, env.config.make_backup, |w| {
+    save_to_file(path, env, |w| {
         general::start_comments(w, &env.config)?;
         general::write_vec(w, mod_rs)?;
         writeln!(w)?;
@@ -71,8 +115,93 @@ pub fn generate_mod_rs(env: &Env, root_path: &Path, mod_rs: &[String], traits: &
 
 pub fn generate_single_version_file(env: &Env) {
     if let Some(ref path) = env.config.single_version_file {
-        save_to_file(path, env.config.make_backup, |w| {
-            general::single_version_file(w, &env.config)
+        save_to_file(path, env, |w| general::single_version_file(w, &env.config));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, time::Duration};
+
+    #[test]
+    fn generate_parallel_keeps_order() {
+        let env = Env::for_test(&[&glib_gir("")], GLIB_TOML);
+        let items: Vec<u64> = (0..100).collect();
+        // Later items are generated faster to finish out of order
+        let results = generate_parallel(&env, &items, |&item| {
+            thread::sleep(Duration::from_micros((100 - item) * 10));
+            item * 2
         });
+        assert_eq!(
+            results,
+            items.iter().map(|item| item * 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn records_mod_rs_in_order() {
+        let names: Vec<_> = (0..50)
+            .map(|i| format!("Record{:02}", (i * 7) % 50))
+            .collect();
+        let records: String = names
+            .iter()
+            .map(|name| {
+                format!(
+                    r#"<record name="{0}" c:type="G{0}" glib:type-name="G{0}"
+            glib:get-type="g_{1}_get_type" c:symbol-prefix="{1}">
+      <field name="x"><type name="gint" c:type="gint"/></field>
+    </record>
+    "#,
+                    name,
+                    name.to_lowercase()
+                )
+            })
+            .collect();
+        let generate: Vec<_> = names
+            .iter()
+            .map(|name| format!("\"GLib.{}\"", name))
+            .collect();
+        let toml = format!("{}generate = [{}]\n", GLIB_TOML, generate.join(", "));
+        let mut env = Env::for_test(&[&glib_gir(&records)], &toml);
+        let dir = std::env::temp_dir().join(format!("gir-codegen-test-{}", std::process::id()));
+        env.config.auto_path = dir.clone();
+
+        let mut mod_rs = Vec::new();
+        records::generate(&env, &dir, &mut mod_rs);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut expected = names.clone();
+        expected.sort();
+        let expected: Vec<_> = expected
+            .iter()
+            .flat_map(|name| {
+                let module = name.to_lowercase();
+                vec![
+                    String::new(),
+                    format!("mod {};", module),
+                    format!("pub use self::{}::{};", module, name),
+                ]
+            })
+            .collect();
+        assert_eq!(mod_rs, expected);
+    }
+
+    const GLIB_TOML: &str = "[options]\nlibrary = \"GLib\"\nversion = \"2.0\"\n\
+                             target_path = \".\"\nwork_mode = \"normal\"\n";
+
+    fn glib_gir(content: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <namespace name="GLib" version="2.0" c:identifier-prefixes="G" c:symbol-prefixes="g">
+    {}
+  </namespace>
+</repository>
+"#,
+            content
+        )
     }
 }
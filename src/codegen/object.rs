@@ -1,4 +1,6 @@
-use super::{child_properties, function, general, properties, signal, trait_impls};
+use super::{
+    child_properties, field_accessors, function, general, properties, signal, trait_impls,
+};
 use crate::{
     analysis::special_functions::Type,
     analysis::{
@@ -83,6 +85,10 @@ pub fn generate(
         }
 
         if !need_generate_trait(analysis) {
+            for accessor in &analysis.fields {
+                field_accessors::generate(w, env, accessor, false, false, 1)?;
+            }
+
             for signal_analysis in analysis
                 .signals
                 .iter()
@@ -158,6 +164,10 @@ pub fn generate(
         generate_trait(w, env, analysis)?;
     }
 
+    for func_analysis in &analysis.functions {
+        function::generate_out_struct(w, env, func_analysis)?;
+    }
+
     if generate_display_trait && !analysis.specials.has_trait(Type::Display) {
         writeln!(w, "\nimpl fmt::Display for {} {{", analysis.name,)?;
         // Generate Display trait implementation.
@@ -313,6 +323,9 @@ fn generate_trait(w: &mut dyn Write, env: &Env, analysis: &analysis::object::Inf
     for child_property in &analysis.child_properties {
         child_properties::generate(w, env, child_property, true, true, 1)?;
     }
+    for accessor in &analysis.fields {
+        field_accessors::generate(w, env, accessor, true, true, 1)?;
+    }
     for signal_analysis in analysis
         .signals
         .iter()
@@ -346,6 +359,9 @@ fn generate_trait(w: &mut dyn Write, env: &Env, analysis: &analysis::object::Inf
     for child_property in &analysis.child_properties {
         child_properties::generate(w, env, child_property, true, false, 1)?;
     }
+    for accessor in &analysis.fields {
+        field_accessors::generate(w, env, accessor, true, false, 1)?;
+    }
     for signal_analysis in analysis
         .signals
         .iter()
@@ -414,4 +430,11 @@ pub fn generate_reexports(
             module_name, analysis.name
         ));
     }
+
+    contents.extend(function::out_struct_reexports(
+        env,
+        &analysis.functions,
+        module_name,
+        &cfgs,
+    ));
 }
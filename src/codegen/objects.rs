@@ -4,12 +4,19 @@ use std::path::Path;
 
 pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>, traits: &mut Vec<String>) {
     info!("Generate objects");
-    for class_analysis in env.analysis.objects.values() {
-        let obj = &env.config.objects[&class_analysis.full_name];
-        if !obj.status.need_generate() {
-            continue;
-        }
+    let class_analyses: Vec<_> = env
+        .analysis
+        .objects
+        .values()
+        .filter(|class_analysis| {
+            env.config.objects[&class_analysis.full_name]
+                .status
+                .need_generate()
+        })
+        .collect();
 
+    let reexports = super::generate_parallel(env, &class_analyses, |class_analysis| {
+        let obj = &env.config.objects[&class_analysis.full_name];
         let mod_name = obj
             .module_name
             .clone()
@@ -20,10 +27,18 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>, traits: &
         path.set_extension("rs");
         info!("Generating file {:?}", path);
 
-        save_to_file(path, env.config.make_backup, |ref mut w| {
+        save_to_file(path, env, |ref mut w| {
             super::object::generate(w, env, class_analysis, generate_display_trait)
         });
 
-        super::object::generate_reexports(env, class_analysis, &mod_name, mod_rs, traits);
+        let mut mod_rs = Vec::new();
+        let mut traits = Vec::new();
+        super::object::generate_reexports(env, class_analysis, &mod_name, &mut mod_rs, &mut traits);
+        (mod_rs, traits)
+    });
+
+    for (object_mod_rs, object_traits) in reexports {
+        mod_rs.extend(object_mod_rs);
+        traits.extend(object_traits);
     }
 }
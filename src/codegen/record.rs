@@ -1,4 +1,4 @@
-use super::{function, general, trait_impls};
+use super::{field_accessors, function, general, trait_impls};
 use crate::{
     analysis::{self, special_functions::Type},
     env::Env,
@@ -12,7 +12,9 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
     general::start_comments(w, &env.config)?;
     general::uses(w, env, &analysis.imports)?;
 
-    if analysis.is_boxed {
+    if analysis.is_direct {
+        general::define_direct_type(w, env, &analysis.name, &type_.c_type, &analysis.derives)?;
+    } else if analysis.is_boxed {
         if let Some((ref glib_get_type, _)) = analysis.glib_get_type {
             general::define_auto_boxed_type(
                 w,
@@ -84,6 +86,7 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
         .functions
         .iter()
         .any(|f| f.status.need_generate() && !f.visibility.hidden())
+        || !analysis.fields.is_empty()
     {
         writeln!(w)?;
         write!(w, "impl {} {{", analysis.name)?;
@@ -100,6 +103,10 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
             )?;
         }
 
+        for accessor in &analysis.fields {
+            field_accessors::generate(w, env, accessor, false, false, 1)?;
+        }
+
         writeln!(w, "}}")?;
     }
 
@@ -132,6 +139,10 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
         writeln!(w, "unsafe impl Sync for {} {{}}", analysis.name)?;
     }
 
+    for func_analysis in &analysis.functions {
+        function::generate_out_struct(w, env, func_analysis)?;
+    }
+
     Ok(())
 }
 
@@ -158,4 +169,11 @@ pub fn generate_reexports(
         "{}pub use self::{}::{};",
         cfg, module_name, analysis.name
     ));
+    let cfgs: Vec<_> = cfg.lines().map(ToOwned::to_owned).collect();
+    contents.extend(function::out_struct_reexports(
+        env,
+        &analysis.functions,
+        module_name,
+        &cfgs,
+    ));
 }
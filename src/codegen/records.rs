@@ -4,12 +4,19 @@ use std::path::Path;
 
 pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
     info!("Generate records");
-    for record_analysis in env.analysis.records.values() {
-        let obj = &env.config.objects[&record_analysis.full_name];
-        if !obj.status.need_generate() {
-            continue;
-        }
+    let record_analyses: Vec<_> = env
+        .analysis
+        .records
+        .values()
+        .filter(|record_analysis| {
+            env.config.objects[&record_analysis.full_name]
+                .status
+                .need_generate()
+        })
+        .collect();
 
+    let reexports = super::generate_parallel(env, &record_analyses, |record_analysis| {
+        let obj = &env.config.objects[&record_analysis.full_name];
         let mod_name = obj
             .module_name
             .clone()
@@ -19,10 +26,14 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
         path.set_extension("rs");
         info!("Generating file {:?}", path);
 
-        save_to_file(path, env.config.make_backup, |w| {
+        save_to_file(path, env, |w| {
             super::record::generate(w, env, record_analysis)
         });
 
-        super::record::generate_reexports(env, record_analysis, &mod_name, mod_rs);
-    }
+        let mut mod_rs = Vec::new();
+        super::record::generate_reexports(env, record_analysis, &mod_name, &mut mod_rs);
+        mod_rs
+    });
+
+    mod_rs.extend(reexports.into_iter().flatten());
 }
@@ -84,7 +84,16 @@ fn out_parameter_as_return_parts(
     // "normal" arguments and one "size" argument, we still need to wrap them into "()" so we take
     // that into account. If the opposite, it means that there are two arguments in any case so
     // we need "()" too.
-    let num_outs = cmp::max(num_out_args, num_out_sizes);
+    let num_outs = if analysis.outs.out_struct.is_some() {
+        // The struct takes the place of the single output
+        if let Throws(..) = analysis.outs.mode {
+            2
+        } else {
+            1
+        }
+    } else {
+        cmp::max(num_out_args, num_out_sizes)
+    };
     match analysis.outs.mode {
         Normal | Combined => {
             if num_outs > 1 {
@@ -127,6 +136,12 @@ pub fn out_parameters_as_return(env: &Env, analysis: &analysis::functions::Info)
     return_str.push_str(" -> ");
     return_str.push_str(prefix);
 
+    if let Some(ref out_struct) = analysis.outs.out_struct {
+        return_str.push_str(&out_struct.name);
+        return_str.push_str(&suffix);
+        return return_str;
+    }
+
     let array_lengths: Vec<_> = analysis
         .outs
         .iter()
@@ -168,7 +183,7 @@ pub fn out_parameters_as_return(env: &Env, analysis: &analysis::functions::Info)
     return_str
 }
 
-fn out_parameter_as_return(par: &library::Parameter, env: &Env) -> String {
+pub fn out_parameter_as_return(par: &library::Parameter, env: &Env) -> String {
     //TODO: upcasts?
     let rust_type = parameter_rust_type(
         env,
@@ -4,6 +4,7 @@ use crate::{
         namespaces,
         rust_type::{Result, TypeError},
     },
+    config::data_model,
     env::Env,
     library::{self, *},
     traits::*,
@@ -83,16 +84,13 @@ fn ffi_inner(env: &Env, tid: library::TypeId, mut inner: String) -> Result {
                 UInt32 => "u32",
                 Int64 => "i64",
                 UInt64 => "u64",
-                Char => "c_char",
-                UChar => "c_uchar",
-                Short => "c_short",
-                UShort => "c_ushort",
-                Int => "c_int",
-                UInt => "c_uint",
-                Long => "c_long",
-                ULong => "c_ulong",
-                Size => "size_t",
-                SSize => "ssize_t",
+                Char | UChar | Short | UShort | Int | UInt | Long | ULong | Size | SSize
+                | IntPtr | UIntPtr => {
+                    // The `libc` aliases are imported in the sys crate
+                    let ffi_type = data_model::ffi_type(&env.config.data_models, fund).unwrap();
+                    inner = ffi_type.trim_start_matches("libc::").into();
+                    &*inner
+                }
                 Float => "c_float",
                 Double => "c_double",
                 UniChar => "u32",
@@ -107,8 +105,6 @@ fn ffi_inner(env: &Env, tid: library::TypeId, mut inner: String) -> Result {
                         _ => &*inner,
                     }
                 }
-                IntPtr => "intptr_t",
-                UIntPtr => "uintptr_t",
                 Unsupported => return Err(TypeError::Unimplemented(inner)),
                 VarArgs => panic!("Should not reach here"),
             };
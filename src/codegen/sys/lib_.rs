@@ -20,7 +20,7 @@ pub fn generate(env: &Env) {
     let path = env.config.auto_path.join(file_name_sys("lib"));
 
     info!("Generating file {:?}", path);
-    save_to_file(&path, env.config.make_backup, |w| generate_lib(w, env));
+    save_to_file(&path, env, |w| generate_lib(w, env));
 }
 
 fn write_link_attr(w: &mut dyn Write, shared_libs: &[String]) -> Result<()> {
@@ -39,23 +39,21 @@ pub fn generate(env: &Env, crate_name: &str) {
 
     let manual_h = tests.join("manual.h");
     if !manual_h.exists() {
-        save_to_file(&manual_h, env.config.make_backup, |w| {
-            generate_manual_h(env, &manual_h, w)
-        });
+        save_to_file(&manual_h, env, |w| generate_manual_h(env, &manual_h, w));
     }
 
     let layout_c = tests.join("layout.c");
-    save_to_file(&layout_c, env.config.make_backup, |w| {
-        generate_layout_c(env, &layout_c, w)
+    save_to_file(&layout_c, env, |w| {
+        generate_layout_c(env, &layout_c, w, &ctypes)
     });
 
     let constant_c = tests.join("constant.c");
-    save_to_file(&constant_c, env.config.make_backup, |w| {
-        generate_constant_c(env, &constant_c, w)
+    save_to_file(&constant_c, env, |w| {
+        generate_constant_c(env, &constant_c, w, &cconsts)
     });
 
     let abi_rs = tests.join("abi.rs");
-    save_to_file(&abi_rs, env.config.make_backup, |w| {
+    save_to_file(&abi_rs, env, |w| {
         generate_abi_rs(env, &abi_rs, w, crate_name, &ctypes, &cconsts)
     });
 }
@@ -201,8 +199,16 @@ fn generate_manual_h(env: &Env, path: &Path, w: &mut dyn Write) -> io::Result<()
     Ok(())
 }
 
+/// Prints the size and alignment of all types in one run, one `name;size;alignment` line each.
+///
+/// With `ABI_TYPE_NAME` defined only the layout of that type is printed.
 #[allow(clippy::write_literal)]
-fn generate_layout_c(env: &Env, path: &Path, w: &mut dyn Write) -> io::Result<()> {
+fn generate_layout_c(
+    env: &Env,
+    path: &Path,
+    w: &mut dyn Write,
+    ctypes: &[CType],
+) -> io::Result<()> {
     info!("Generating file {:?}", path);
     general::start_comments(w, &env.config)?;
     writeln!(w)?;
@@ -213,16 +219,37 @@ fn generate_layout_c(env: &Env, path: &Path, w: &mut dyn Write) -> io::Result<()
     writeln!(
         w,
         "{}",
-        r##"
+        r####"
+#define PRINT_LAYOUT(TYPE_NAME) \
+    printf("%s;%zu;%zu\n", #TYPE_NAME, sizeof(TYPE_NAME), alignof(TYPE_NAME));
+
 int main() {
-    printf("%zu\n%zu", sizeof(ABI_TYPE_NAME), alignof(ABI_TYPE_NAME));
-    return 0;
-}"##
-    )
+#ifdef ABI_TYPE_NAME
+    PRINT_LAYOUT(ABI_TYPE_NAME);
+#else
+    PRINT_LAYOUT(char);"####
+    )?;
+
+    for ctype in ctypes {
+        writeln!(w, "    PRINT_LAYOUT({});", ctype.name)?;
+    }
+
+    writeln!(w, "#endif")?;
+    writeln!(w, "    return 0;")?;
+    writeln!(w, "}}")
 }
 
+/// Prints the value of all constants in one run, one `name;###gir test###value###gir test###`
+/// record each.
+///
+/// With `ABI_CONSTANT_NAME` defined only the value of that constant is printed.
 #[allow(clippy::write_literal)]
-fn generate_constant_c(env: &Env, path: &Path, w: &mut dyn Write) -> io::Result<()> {
+fn generate_constant_c(
+    env: &Env,
+    path: &Path,
+    w: &mut dyn Write,
+    cconsts: &[CConstant],
+) -> io::Result<()> {
     info!("Generating file {:?}", path);
     general::start_comments(w, &env.config)?;
     writeln!(w)?;
@@ -233,27 +260,40 @@ fn generate_constant_c(env: &Env, path: &Path, w: &mut dyn Write) -> io::Result<
         w,
         "{}",
         r####"
+#define PRINT_CONSTANT(CONSTANT_NAME) \
+    printf("%s;", #CONSTANT_NAME); \
+    printf(_Generic((CONSTANT_NAME), \
+                    char *: "###gir test###%s###gir test###\n", \
+                    const char *: "###gir test###%s###gir test###\n", \
+                    char: "###gir test###%c###gir test###\n", \
+                    signed char: "###gir test###%hhd###gir test###\n", \
+                    unsigned char: "###gir test###%hhu###gir test###\n", \
+                    short int: "###gir test###%hd###gir test###\n", \
+                    unsigned short int: "###gir test###%hu###gir test###\n", \
+                    int: "###gir test###%d###gir test###\n", \
+                    unsigned int: "###gir test###%u###gir test###\n", \
+                    long: "###gir test###%ld###gir test###\n", \
+                    unsigned long: "###gir test###%lu###gir test###\n", \
+                    long long: "###gir test###%lld###gir test###\n", \
+                    unsigned long long: "###gir test###%llu###gir test###\n", \
+                    double: "###gir test###%f###gir test###\n", \
+                    long double: "###gir test###%ld###gir test###\n"), \
+           CONSTANT_NAME);
+
 int main() {
-    printf(_Generic((ABI_CONSTANT_NAME),
-                    char *: "###gir test###%s###gir test###\n",
-                    const char *: "###gir test###%s###gir test###\n",
-                    char: "###gir test###%c###gir test###\n",
-                    signed char: "###gir test###%hhd###gir test###\n",
-                    unsigned char: "###gir test###%hhu###gir test###\n",
-                    short int: "###gir test###%hd###gir test###\n",
-                    unsigned short int: "###gir test###%hu###gir test###\n",
-                    int: "###gir test###%d###gir test###\n",
-                    unsigned int: "###gir test###%u###gir test###\n",
-                    long: "###gir test###%ld###gir test###\n",
-                    unsigned long: "###gir test###%lu###gir test###\n",
-                    long long: "###gir test###%lld###gir test###\n",
-                    unsigned long long: "###gir test###%llu###gir test###\n",
-                    double: "###gir test###%f###gir test###\n",
-                    long double: "###gir test###%ld###gir test###\n"),
-           ABI_CONSTANT_NAME);
-    return 0;
-}"####
-    )
+#ifdef ABI_CONSTANT_NAME
+    PRINT_CONSTANT(ABI_CONSTANT_NAME);
+#else
+    PRINT_CONSTANT(1);"####
+    )?;
+
+    for cconst in cconsts {
+        writeln!(w, "    PRINT_CONSTANT({});", cconst.name)?;
+    }
+
+    writeln!(w, "#endif")?;
+    writeln!(w, "    return 0;")?;
+    writeln!(w, "}}")
 }
 
 #[allow(clippy::write_literal)]
@@ -272,6 +312,7 @@ fn generate_abi_rs(
     general::start_comments(w, &env.config)?;
     writeln!(w)?;
 
+    writeln!(w, "use std::collections::HashMap;")?;
     writeln!(w, "use std::env;")?;
     writeln!(w, "use std::error::Error;")?;
     writeln!(w, "use std::ffi::OsString;")?;
@@ -406,13 +447,28 @@ fn cross_validate_constants_with_c() {
     let tmpdir = Builder::new().prefix("abi").tempdir().expect("temporary directory");
     let cc = Compiler::new().expect("configured compiler");
 
+    // All constants are checked by one program, if it can't be built each
+    // constant gets its own program to find the faulty ones.
+    let c_constants = get_c_output(tmpdir.path(), &cc, "constant", None)
+        .and_then(|output| parse_constants(&output))
+        .unwrap_or_else(|e| {
+            eprintln!("{}\nChecking each constant on its own", e);
+            HashMap::new()
+        });
+    let c_value = |name: &str| -> Result<String, Box<dyn Error>> {
+        match c_constants.get(name) {
+            Some(c_value) => Ok(c_value.clone()),
+            None => get_c_value(tmpdir.path(), &cc, name),
+        }
+    };
+
     assert_eq!("1",
-               get_c_value(tmpdir.path(), &cc, "1").expect("C constant"),
+               c_value("1").expect("C constant"),
                "failed to obtain correct constant value for 1");
 
     let mut results : Results = Default::default();
     for (i, &(name, rust_value)) in RUST_CONSTANTS.iter().enumerate() {
-        match get_c_value(tmpdir.path(), &cc, name) {
+        match c_value(name) {
             Err(e) => {
                 results.record_failed_to_compile();
                 eprintln!("{}", e);
@@ -439,13 +495,28 @@ fn cross_validate_layout_with_c() {
     let tmpdir = Builder::new().prefix("abi").tempdir().expect("temporary directory");
     let cc = Compiler::new().expect("configured compiler");
 
+    // All types are checked by one program, if it can't be built each
+    // type gets its own program to find the faulty ones.
+    let c_layouts = get_c_output(tmpdir.path(), &cc, "layout", None)
+        .and_then(|output| parse_layouts(&output))
+        .unwrap_or_else(|e| {
+            eprintln!("{}\nChecking each type on its own", e);
+            HashMap::new()
+        });
+    let c_layout = |name: &str| -> Result<Layout, Box<dyn Error>> {
+        match c_layouts.get(name) {
+            Some(c_layout) => Ok(*c_layout),
+            None => get_c_layout(tmpdir.path(), &cc, name),
+        }
+    };
+
     assert_eq!(Layout {size: 1, alignment: 1},
-               get_c_layout(tmpdir.path(), &cc, "char").expect("C layout"),
+               c_layout("char").expect("C layout"),
                "failed to obtain correct layout for char type");
 
     let mut results : Results = Default::default();
     for (i, &(name, rust_layout)) in RUST_LAYOUTS.iter().enumerate() {
-        match get_c_layout(tmpdir.path(), &cc, name) {
+        match c_layout(name) {
             Err(e) => {
                 results.record_failed_to_compile();
                 eprintln!("{}", e);
@@ -468,10 +539,32 @@ fn cross_validate_layout_with_c() {
 }
 
 fn get_c_layout(dir: &Path, cc: &Compiler, name: &str) -> Result<Layout, Box<dyn Error>> {
-    let exe = dir.join("layout");
+    let output = get_c_output(dir, cc, "layout", Some(("ABI_TYPE_NAME", name)))?;
+    let layouts = parse_layouts(&output)?;
+    let layout = layouts.values().next().ok_or_else(|| format!("no layout for {}", name))?;
+    Ok(*layout)
+}
+
+fn get_c_value(dir: &Path, cc: &Compiler, name: &str) -> Result<String, Box<dyn Error>> {
+    let output = get_c_output(dir, cc, "constant", Some(("ABI_CONSTANT_NAME", name)))?;
+    let constants = parse_constants(&output)?;
+    let value = constants.values().next().ok_or_else(|| format!("no value for {}", name))?;
+    Ok(value.clone())
+}
+
+/// Compiles and runs `tests/{name}.c` with an optional define, returning what it printed.
+fn get_c_output(
+    dir: &Path,
+    cc: &Compiler,
+    name: &str,
+    define: Option<(&str, &str)>,
+) -> Result<String, Box<dyn Error>> {
+    let exe = dir.join(name);
     let mut cc = cc.clone();
-    cc.define("ABI_TYPE_NAME", name);
-    cc.compile(Path::new("tests/layout.c"), &exe)?;
+    if let Some((var, val)) = define {
+        cc.define(var, val);
+    }
+    cc.compile(&Path::new("tests").join(name).with_extension("c"), &exe)?;
 
     let mut abi_cmd = Command::new(exe);
     let output = abi_cmd.output()?;
@@ -480,34 +573,63 @@ fn get_c_layout(dir: &Path, cc: &Compiler, name: &str) -> Result<Layout, Box<dyn
                            &abi_cmd, &output).into());
     }
 
-    let stdout = str::from_utf8(&output.stdout)?;
-    let mut words = stdout.trim().split_whitespace();
-    let size = words.next().unwrap().parse().unwrap();
-    let alignment = words.next().unwrap().parse().unwrap();
-    Ok(Layout {size, alignment})
+    Ok(String::from_utf8(output.stdout)?)
 }
 
-fn get_c_value(dir: &Path, cc: &Compiler, name: &str) -> Result<String, Box<dyn Error>> {
-    let exe = dir.join("constant");
-    let mut cc = cc.clone();
-    cc.define("ABI_CONSTANT_NAME", name);
-    cc.compile(Path::new("tests/constant.c"), &exe)?;
-
-    let mut abi_cmd = Command::new(exe);
-    let output = abi_cmd.output()?;
-    if !output.status.success() {
-        return Err(format!("command {:?} failed, {:?}",
-                           &abi_cmd, &output).into());
+/// Parses the `name;size;alignment` lines printed by `layout.c`.
+fn parse_layouts(output: &str) -> Result<HashMap<String, Layout>, Box<dyn Error>> {
+    let mut layouts = HashMap::new();
+    for line in output.lines() {
+        let mut words = line.trim().split(';');
+        let (name, size, alignment) = match (words.next(), words.next(), words.next()) {
+            (Some(name), Some(size), Some(alignment)) => (name, size, alignment),
+            _ => return Err(format!("invalid layout output {:?}", line).into()),
+        };
+        let size = size.parse()?;
+        let alignment = alignment.parse()?;
+        layouts.insert(name.to_owned(), Layout {size, alignment});
     }
+    Ok(layouts)
+}
 
-    let output = str::from_utf8(&output.stdout)?.trim();
-    if !output.starts_with("###gir test###") ||
-       !output.ends_with("###gir test###") {
-        return Err(format!("command {:?} return invalid output, {:?}",
-                           &abi_cmd, &output).into());
+/// Parses the `name;###gir test###value###gir test###` records printed by `constant.c`,
+/// values may contain new lines.
+fn parse_constants(output: &str) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    const MARKER: &str = "###gir test###";
+    let mut constants = HashMap::new();
+    let mut rest = output.trim_start();
+    while !rest.is_empty() {
+        let invalid = || format!("invalid constant output {:?}", rest);
+        let start = rest.find(MARKER).ok_or_else(invalid)?;
+        let value_start = start + MARKER.len();
+        let value_end = value_start + rest[value_start..].find(MARKER).ok_or_else(invalid)?;
+        let name = rest[..start].trim_end_matches(';');
+        constants.insert(name.to_owned(), rest[value_start..value_end].to_owned());
+        rest = rest[value_end + MARKER.len()..].trim_start();
     }
+    Ok(constants)
+}
+
+#[test]
+fn parse_c_layouts() {
+    let layouts = parse_layouts("char;1;1\nGType;8;8\n").expect("parsed layouts");
+    assert_eq!(layouts.len(), 2);
+    assert_eq!(layouts["char"], Layout {size: 1, alignment: 1});
+    assert_eq!(layouts["GType"], Layout {size: 8, alignment: 8});
+    assert!(parse_layouts("char;1\n").is_err());
+    assert!(parse_layouts("char;one;1\n").is_err());
+}
 
-    Ok(String::from(&output[14..(output.len() - 14)]))
+#[test]
+fn parse_c_constants() {
+    let constants = parse_constants(
+        "A;###gir test###1###gir test###\nB;###gir test###two\nlines###gir test###\n"
+    ).expect("parsed constants");
+    assert_eq!(constants.len(), 2);
+    assert_eq!(constants["A"], "1");
+    assert_eq!(constants["B"], "two\nlines");
+    assert!(parse_constants("A;###gir test###1\n").is_err());
+    assert!(parse_constants("A;1\n").is_err());
 }
 
 const RUST_LAYOUTS: &[(&str, Layout)] = &["####
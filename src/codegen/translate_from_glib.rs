@@ -1,5 +1,10 @@
 use crate::{
-    analysis::{self, conversion_type::ConversionType, rust_type::rust_type},
+    analysis::{
+        self,
+        conversion_type::ConversionType,
+        functions::{is_carray_with_record_values, is_carray_with_scalar_elements},
+        rust_type::rust_type,
+    },
     chunk::conversion_from_glib::Mode,
     env::Env,
     library,
@@ -34,6 +39,17 @@ impl TranslateFromGlib for Mode {
             Pointer => {
                 let trans = from_glib_xxx(self.transfer, array_length);
                 match *env.type_(self.typ) {
+                    library::Type::CArray(inner_tid)
+                        if is_carray_with_scalar_elements(env, self.typ)
+                            || is_carray_with_record_values(
+                                env,
+                                self.typ,
+                                &self.c_type,
+                                self.direction,
+                            ) =>
+                    {
+                        array_from_glib(env, inner_tid, self.transfer, array_length)
+                    }
                     library::Type::List(..)
                     | library::Type::SList(..)
                     | library::Type::PtrArray(..)
@@ -44,6 +60,14 @@ impl TranslateFromGlib for Mode {
                             (format!("FromGlibPtrContainer::{}", trans.0), trans.1)
                         }
                     }
+                    // Caller-allocated fixed arrays are filled in place
+                    library::Type::FixedArray(..) if self.is_uninitialized => {
+                        (String::new(), String::new())
+                    }
+                    library::Type::FixedArray(_, size, _) => {
+                        fixed_array_from_glib(env, size, self.transfer, self.nullable)
+                    }
+                    library::Type::HashTable(..) => hash_table_from_glib(env, self.transfer),
                     _ => trans,
                 }
             }
@@ -113,13 +137,142 @@ impl TranslateFromGlib for analysis::return_value::Info {
                         res
                     }
                 }
-                None => Mode::from(par).translate_from_glib_as_function(env, array_length),
+                None => match *env.type_(par.typ) {
+                    library::Type::Typed(ref typed) => typed_from_glib(env, typed, par.transfer),
+                    _ => Mode::from(par).translate_from_glib_as_function(env, array_length),
+                },
             },
             None => (String::new(), ";".into()),
         }
     }
 }
 
+/// Converts a returned `GVariant` or `GValue` to the configured Rust type.
+///
+/// The result is `None` for a `NULL` pointer and for a value of another type.
+fn typed_from_glib(
+    env: &Env,
+    typed: &library::Typed,
+    transfer: library::Transfer,
+) -> (String, String) {
+    let (wrapper, get) = match typed.kind {
+        library::TypedKind::Variant => ("Variant", "v.get()"),
+        library::TypedKind::Value => ("Value", "v.get().ok().flatten()"),
+    };
+    let wrapper = use_glib_type(env, wrapper);
+    let (from_glib, close) = from_glib_xxx(transfer, None);
+    (
+        format!("Option::<{}>::{}", wrapper, from_glib),
+        format!("{}.and_then(|v| {})", close, get),
+    )
+}
+
+/// Converts a C array of scalars or record values element by element.
+///
+/// glib only provides container conversions for fundamental types and
+/// pointers, so the elements are converted one by one from a slice.
+fn array_from_glib(
+    env: &Env,
+    inner_tid: library::TypeId,
+    transfer: library::Transfer,
+    array_length: Option<&String>,
+) -> (String, String) {
+    let array_length = match array_length {
+        Some(array_length) => array_length,
+        None => return ("/*Unknown conversion*/".into(), String::new()),
+    };
+    let element = match ConversionType::of(env, inner_tid) {
+        ConversionType::Scalar => {
+            let (prefix, suffix) = Mode {
+                typ: inner_tid,
+                c_type: String::new(),
+                direction: library::ParameterDirection::Return,
+                transfer: library::Transfer::None,
+                is_uninitialized: false,
+                nullable: false,
+            }
+            .translate_from_glib_as_function(env, None);
+            format!("{}*v{}", prefix, suffix)
+        }
+        _ => "from_glib_none(v as *const _)".into(),
+    };
+    // The elements are either plain values or not owned, see `is_carray_with_owned_record_values`
+    let free = match transfer {
+        library::Transfer::None => String::new(),
+        library::Transfer::Full | library::Transfer::Container => {
+            format!(" {}(array as *mut _);", use_glib_type(env, "ffi::g_free"))
+        }
+    };
+    (
+        "{ let array = ".into(),
+        format!(
+            "; let vec = if array.is_null() {{ Vec::new() }} else {{ \
+             std::slice::from_raw_parts(array, {}.assume_init() as usize)\
+             .iter().map(|v| {}).collect() }};{} vec }}",
+            array_length, element, free
+        ),
+    )
+}
+
+/// Copies a returned fixed-size C array into a Rust array, an `Option` of it if
+/// the array is nullable.
+fn fixed_array_from_glib(
+    env: &Env,
+    size: u16,
+    transfer: library::Transfer,
+    nullable: bool,
+) -> (String, String) {
+    let free = match transfer {
+        library::Transfer::None => String::new(),
+        library::Transfer::Full | library::Transfer::Container => {
+            format!(" {}(array as *mut _);", use_glib_type(env, "ffi::g_free"))
+        }
+    };
+    let copy = format!("let res = *(array as *const [_; {}]);{}", size, free);
+    let suffix = if nullable {
+        format!(
+            "; if array.is_null() {{ None }} else {{ {} Some(res) }} }}",
+            copy
+        )
+    } else {
+        format!("; {} res }}", copy)
+    };
+    ("{ let array = ".into(), suffix)
+}
+
+/// Copies the entries of a `GHashTable` into a `HashMap`.
+///
+/// glib only provides container conversions for `HashMap<String, String>`, so
+/// the table is walked with a `GHashTableIter` and every key and value is
+/// converted with `from_glib_none`. A `NULL` table results in an empty map.
+fn hash_table_from_glib(env: &Env, transfer: library::Transfer) -> (String, String) {
+    let unref = match transfer {
+        library::Transfer::None => String::new(),
+        library::Transfer::Full | library::Transfer::Container => {
+            format!(" {}(table);", use_glib_type(env, "ffi::g_hash_table_unref"))
+        }
+    };
+    (
+        "{ let table = ".into(),
+        format!(
+            "; let mut map = HashMap::new(); \
+             if !table.is_null() {{ \
+             let mut iter = std::mem::MaybeUninit::uninit(); \
+             {iter_init}(iter.as_mut_ptr(), table); \
+             let mut key = std::ptr::null_mut(); \
+             let mut value = std::ptr::null_mut(); \
+             while {iter_next}(iter.as_mut_ptr(), &mut key, &mut value) != {false_} {{ \
+             map.insert(from_glib_none(key as *const _), from_glib_none(value as *const _)); \
+             }}{unref} }} \
+             map }}",
+            iter_init = use_glib_type(env, "ffi::g_hash_table_iter_init"),
+            iter_next = use_glib_type(env, "ffi::g_hash_table_iter_next"),
+            false_ = use_glib_type(env, "ffi::GFALSE"),
+            unref = unref,
+        ),
+    )
+}
+
 fn from_glib_xxx(transfer: library::Transfer, array_length: Option<&String>) -> (String, String) {
     use crate::library::Transfer;
     let good_print = |name: &str| format!(", {}.assume_init() as usize)", name);
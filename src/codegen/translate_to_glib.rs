@@ -1,6 +1,6 @@
 use crate::{
     analysis::{function_parameters::TransformationType, ref_mode::RefMode},
-    library::Transfer,
+    library::{Transfer, TypedKind},
 };
 
 pub trait TranslateToGlib {
@@ -43,6 +43,58 @@ impl TranslateToGlib for TransformationType {
                     format!("{}{}{}{}{}", left, name, to_glib_extra, right, pointer_cast)
                 }
             }
+            ToGlibScalarArray { ref name } => format!(
+                "{}.iter().map(|v| v.to_glib()).collect::<Vec<_>>().as_mut_ptr()",
+                name
+            ),
+            // The temporary `Vec` lives until the end of the call
+            ToGlibRecordArray {
+                ref name,
+                nullable: false,
+            } => format!(
+                "{}.iter().map(|v| std::ptr::read(v.to_glib_none().0)).collect::<Vec<_>>()\
+                 .as_mut_ptr()",
+                name
+            ),
+            ToGlibRecordArray {
+                ref name,
+                nullable: true,
+            } => format!(
+                "{}.map(|a| a.iter().map(|v| std::ptr::read(v.to_glib_none().0))\
+                 .collect::<Vec<_>>()).as_mut().map_or(std::ptr::null_mut(), |a| a.as_mut_ptr())",
+                name
+            ),
+            ToGlibFixedArray { ref name, .. } => format!("{}.as_ptr() as *mut _", name),
+            // The table is built before the call, see `function_body_chunk`
+            ToGlibHashTable { ref name, .. } => format!("{}_table", name),
+            ToGlibTyped {
+                ref name,
+                kind,
+                transfer,
+                nullable,
+                mut_ptr,
+            } => {
+                let to = match kind {
+                    TypedKind::Variant => "to_variant",
+                    TypedKind::Value => "to_value",
+                };
+                let value = if nullable {
+                    format!("{}.map(|v| v.{}())", name, to)
+                } else {
+                    format!("{}.{}()", name, to)
+                };
+                let to_glib = if transfer == Transfer::None {
+                    "to_glib_none().0"
+                } else {
+                    "to_glib_full()"
+                };
+                // glib only converts values to const pointers
+                if kind == TypedKind::Value && mut_ptr {
+                    format!("mut_override({}.{})", value, to_glib)
+                } else {
+                    format!("{}.{}", value, to_glib)
+                }
+            }
             ToGlibBorrow => "/*Not applicable conversion Borrow*/".to_owned(),
             ToGlibUnknown { ref name } => format!("/*Unknown conversion*/{}", name),
             ToSome(ref name) => format!("Some({})", name),
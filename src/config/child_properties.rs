@@ -1,6 +1,9 @@
-use super::{error::TomlHelper, parsable::Parse};
+use super::{
+    error::TomlHelper,
+    parsable::{Parse, TableExt, ToToml},
+};
 use log::error;
-use toml::Value;
+use toml::{value::Table, Value};
 
 #[derive(Clone, Debug)]
 pub struct ChildProperty {
@@ -22,11 +25,6 @@ impl Parse for ChildProperty {
             return None;
         };
 
-        toml.check_unwanted(
-            &["name", "type", "doc_hidden"],
-            &format!("child property {}", object_name),
-        );
-
         let type_name = toml
             .lookup("type")
             .and_then(Value::as_str)
@@ -53,6 +51,16 @@ impl Parse for ChildProperty {
     }
 }
 
+impl ToToml for ChildProperty {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        table.insert("name".into(), self.name.clone().into());
+        table.insert("type".into(), self.type_name.clone().into());
+        table.insert_flag("doc_hidden", self.doc_hidden);
+        Value::Table(table)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ChildProperties {
     pub child_name: Option<String>,
@@ -97,6 +105,15 @@ impl Parse for ChildProperties {
     }
 }
 
+impl ChildProperties {
+    /// Writes the keys read by `ChildProperties::parse` to the table of the object
+    pub fn to_toml(&self, table: &mut Table) {
+        table.insert_opt("child_name", self.child_name.as_deref());
+        table.insert_opt("child_type", self.child_type.as_deref());
+        table.insert_items("child_prop", &self.properties);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{super::parsable::Parse, *};
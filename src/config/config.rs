@@ -1,27 +1,35 @@
 use super::{
+    data_model::DataModel,
     external_libraries::{read_external_libraries, ExternalLibrary},
-    gobjects, WorkMode,
+    gobjects,
+    parsable::TableExt,
+    schema::{self, enum_value},
+    validation::check_unknown_keys,
+    WorkMode,
 };
 use crate::{
     config::error::TomlHelper,
     git::repo_hash,
     library::{self, Library},
-    nameutil::set_crate_name_overrides,
+    nameutil::{crate_name, set_crate_name_overrides},
     version::Version,
 };
-use log::warn;
 use std::{
     collections::HashMap,
-    fs,
+    env, fs,
     path::{Path, PathBuf},
     str::FromStr,
 };
+use toml::{value::Table, Value};
 
 #[derive(Debug)]
 pub struct Config {
     pub work_mode: WorkMode,
-    pub girs_dir: PathBuf,
-    pub girs_version: String, //Version in girs_dir, detected by git
+    /// Directory of the config file, the paths in it are relative to
+    pub config_dir: PathBuf,
+    /// Directories searched in order for GIR files, before the system ones
+    pub girs_dirs: Vec<PathBuf>,
+    pub girs_version: String, //Version of the first git checkout in girs_dirs
     pub library_name: String,
     pub library_version: String,
     pub target_path: PathBuf,
@@ -41,18 +49,29 @@ pub struct Config {
     pub trust_return_value_nullability: bool,
     pub docs_rs_features: Vec<String>,
     pub disable_format: bool,
+    /// Format the generated files with the edition and `rustfmt.toml` of the project, like `cargo fmt`
+    pub format_in_project: bool,
     pub split_build_rs: bool,
     pub extra_versions: Vec<Version>,
     pub lib_version_overrides: HashMap<Version, Version>,
     pub feature_dependencies: HashMap<Version, Vec<String>>,
     pub dox_feature_dependencies: Vec<String>,
+    /// Data models of the targets the generated code has to be correct for
+    pub data_models: Vec<DataModel>,
+    /// Number of outputs from which functions return a struct instead of a tuple
+    pub out_struct_threshold: Option<usize>,
+    /// Directory of the parsed GIR cache, `None` when disabled
+    pub gir_cache_dir: Option<PathBuf>,
+    pub crate_name_overrides: HashMap<String, String>,
+    /// Keys of the config file missing from the schema, reported by `validate_config`
+    pub unknown_keys: Vec<String>,
 }
 
 impl Config {
     pub fn new<'a, S, W>(
         config_file: S,
         work_mode: W,
-        girs_dir: S,
+        girs_dirs: &[String],
         library_name: S,
         library_version: S,
         target_path: S,
@@ -60,6 +79,7 @@ impl Config {
         make_backup: bool,
         show_statistics: bool,
         disable_format: bool,
+        disable_gir_cache: bool,
     ) -> Result<Config, String>
     where
         S: Into<Option<&'a str>>,
@@ -71,11 +91,6 @@ impl Config {
         }
         .into();
 
-        let config_dir = match config_file.parent() {
-            Some(path) => path.into(),
-            None => PathBuf::new(),
-        };
-
         let toml = match read_toml(&config_file) {
             Ok(toml) => toml,
             Err(e) => {
@@ -87,10 +102,71 @@ impl Config {
             }
         };
 
-        let overrides = read_crate_name_overrides(&toml);
-        if !overrides.is_empty() {
-            set_crate_name_overrides(overrides);
-        }
+        Config::read(
+            toml,
+            config_file,
+            work_mode,
+            girs_dirs,
+            library_name,
+            library_version,
+            target_path,
+            doc_target_path,
+            make_backup,
+            show_statistics,
+            disable_format,
+            disable_gir_cache,
+        )
+    }
+
+    /// Reads a config from its TOML source, without GIR cache nor formatting.
+    #[cfg(test)]
+    pub fn from_toml_str(toml: &str) -> Result<Config, String> {
+        let toml = toml::from_str(toml).map_err(|e| e.to_string())?;
+        Config::read(
+            toml,
+            "Gir.toml".into(),
+            None,
+            &[],
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            true,
+            true,
+        )
+    }
+
+    fn read<'a, S, W>(
+        toml: toml::Value,
+        config_file: PathBuf,
+        work_mode: W,
+        girs_dirs: &[String],
+        library_name: S,
+        library_version: S,
+        target_path: S,
+        doc_target_path: S,
+        make_backup: bool,
+        show_statistics: bool,
+        disable_format: bool,
+        disable_gir_cache: bool,
+    ) -> Result<Config, String>
+    where
+        S: Into<Option<&'a str>>,
+        W: Into<Option<WorkMode>>,
+    {
+        let config_dir = match config_file.parent() {
+            Some(path) => path.into(),
+            None => PathBuf::new(),
+        };
+
+        let mut unknown_keys = Vec::new();
+        check_unknown_keys(&mut unknown_keys, &toml, schema::ROOT, "");
+
+        // Also resets the overrides of a previously read config
+        let crate_name_overrides = read_crate_name_overrides(&toml);
+        set_crate_name_overrides(crate_name_overrides.clone());
 
         let work_mode = match work_mode.into() {
             Some(w) => w,
@@ -109,14 +185,15 @@ impl Config {
             }
         };
 
-        let girs_dir: PathBuf = match girs_dir.into() {
-            Some("") | None => {
-                let path = toml.lookup_str("options.girs_dir", "No options.girs_dir")?;
-                config_dir.join(path)
-            }
-            Some(a) => a.into(),
+        let girs_dirs: Vec<PathBuf> = if girs_dirs.is_empty() {
+            read_girs_dirs(&toml, &config_dir)?
+        } else {
+            girs_dirs.iter().map(PathBuf::from).collect()
         };
-        let girs_version = repo_hash(&girs_dir).unwrap_or_else(|| "???".into());
+        let girs_version = girs_dirs
+            .iter()
+            .find_map(repo_hash)
+            .unwrap_or_else(|| "???".into());
 
         let (library_name, library_version) = match (library_name.into(), library_version.into()) {
             (Some(""), Some("")) | (None, None) => (
@@ -239,6 +316,11 @@ impl Config {
             }
         };
 
+        let format_in_project = match toml.lookup("options.format_in_project") {
+            Some(v) => v.as_result_bool("options.format_in_project")?,
+            None => true,
+        };
+
         let split_build_rs = match toml.lookup("options.split_build_rs") {
             Some(v) => v.as_result_bool("options.split_build_rs")?,
             None => false,
@@ -248,10 +330,30 @@ impl Config {
         let lib_version_overrides = read_lib_version_overrides(&toml)?;
         let feature_dependencies = read_feature_dependencies(&toml)?;
         let dox_feature_dependencies = read_dox_feature_dependencies(&toml)?;
+        let data_models = read_data_models(&toml)?;
+        let out_struct_threshold = match toml.lookup("options.out_struct_threshold") {
+            Some(v) => match v.as_integer() {
+                Some(n) if n > 1 => Some(n as usize),
+                _ => {
+                    return Err(
+                        "options.out_struct_threshold expected to be an integer greater than 1"
+                            .into(),
+                    )
+                }
+            },
+            None => None,
+        };
+
+        let gir_cache_dir = if disable_gir_cache {
+            None
+        } else {
+            read_gir_cache_dir(&toml, &config_dir)?
+        };
 
         Ok(Config {
             work_mode,
-            girs_dir,
+            config_dir,
+            girs_dirs,
             girs_version,
             library_name,
             library_version,
@@ -271,11 +373,17 @@ impl Config {
             trust_return_value_nullability,
             docs_rs_features,
             disable_format,
+            format_in_project,
             split_build_rs,
             extra_versions,
             lib_version_overrides,
             feature_dependencies,
             dox_feature_dependencies,
+            data_models,
+            out_struct_threshold,
+            gir_cache_dir,
+            crate_name_overrides,
+            unknown_keys,
         })
     }
 
@@ -297,15 +405,258 @@ impl Config {
         gobjects::resolve_type_ids(&mut self.objects, library)
     }
 
-    pub fn check_disable_format(&mut self) {
-        if !self.disable_format && !crate::fmt::check_fmt() {
-            warn!("Formatter not found, options.disable_format set to true");
-            self.disable_format = true;
+    /// Makes the crate name overrides of this config active in the current
+    /// thread, needed when the config wasn't read in it.
+    pub fn use_crate_name_overrides(&self) {
+        set_crate_name_overrides(self.crate_name_overrides.clone());
+    }
+
+    /// Serializes the config to normalized TOML: the keys are ordered as
+    /// in `schema`, default values are left out and objects configured only
+    /// with a status are listed in the status options.
+    pub fn to_toml(&self) -> Value {
+        let mut root = Table::new();
+        root.insert("options".into(), self.options_to_toml().into());
+
+        let custom_libs: Table = self
+            .external_libraries
+            .iter()
+            .filter(|lib| lib.crate_name != crate_name(&lib.namespace))
+            .map(|lib| (lib.crate_name.clone(), lib.namespace.clone().into()))
+            .collect();
+        root.insert_table("external_libraries", custom_libs.into());
+
+        let mut overrides: Vec<_> = self.crate_name_overrides.iter().collect();
+        overrides.sort();
+        let overrides: Table = overrides
+            .into_iter()
+            .map(|(name, crate_name)| (name.clone(), crate_name.clone().into()))
+            .collect();
+        root.insert_table("crate_name_overrides", overrides.into());
+
+        let mut lib_version_overrides: Vec<_> = self.lib_version_overrides.iter().collect();
+        lib_version_overrides.sort();
+        let lib_version_overrides: Vec<Value> = lib_version_overrides
+            .into_iter()
+            .map(|(version, lib_version)| {
+                let mut table = Table::new();
+                table.insert("version".into(), version.to_string().into());
+                table.insert("lib_version".into(), lib_version.to_string().into());
+                table.into()
+            })
+            .collect();
+        if !lib_version_overrides.is_empty() {
+            root.insert("lib_version_overrides".into(), lib_version_overrides.into());
+        }
+
+        let mut feature_dependencies: Vec<_> = self.feature_dependencies.iter().collect();
+        feature_dependencies.sort();
+        let feature_dependencies: Vec<Value> = feature_dependencies
+            .into_iter()
+            .map(|(version, dependencies)| {
+                let mut table = Table::new();
+                table.insert("version".into(), version.to_string().into());
+                table.insert("dependencies".into(), dependencies.clone().into());
+                table.into()
+            })
+            .collect();
+        if !feature_dependencies.is_empty() {
+            root.insert("feature_dependencies".into(), feature_dependencies.into());
+        }
+
+        let objects: Vec<Value> = self
+            .objects_to_toml()
+            .into_iter()
+            .filter(|object| !is_status_shorthand(object))
+            .collect();
+        if !objects.is_empty() {
+            root.insert("object".into(), objects.into());
+        }
+
+        Value::Table(root)
+    }
+
+    /// `to_toml` as a string, with the root keys in the order of `schema`
+    /// instead of the arrays of tables first.
+    pub fn to_toml_string(&self) -> Result<String, String> {
+        let mut parts = Vec::new();
+        for (key, value) in self.to_toml().as_table().unwrap() {
+            let mut table = Table::new();
+            table.insert(key.clone(), value.clone());
+            parts.push(toml::to_string_pretty(&table).map_err(|e| e.to_string())?);
         }
+        Ok(parts.join("\n"))
+    }
+
+    fn options_to_toml(&self) -> Table {
+        let mut options = Table::new();
+        options.insert(
+            "work_mode".into(),
+            enum_value(schema::WORK_MODES, &self.work_mode, |s| s.parse().ok()).into(),
+        );
+        options.insert("library".into(), self.library_name.clone().into());
+        options.insert("version".into(), self.library_version.clone().into());
+        if self.min_cfg_version != Version::default() {
+            options.insert(
+                "min_cfg_version".into(),
+                self.min_cfg_version.to_string().into(),
+            );
+        }
+        options.insert(
+            "target_path".into(),
+            relative_path(&self.target_path, &self.config_dir).into(),
+        );
+        let default_auto_path = if self.work_mode == WorkMode::Normal {
+            self.target_path.join("src").join("auto")
+        } else {
+            self.target_path.join("src")
+        };
+        if self.auto_path != default_auto_path {
+            options.insert(
+                "auto_path".into(),
+                relative_path(&self.auto_path, &self.target_path).into(),
+            );
+        }
+        if self.doc_target_path != self.target_path.join("vendor.md") {
+            options.insert(
+                "doc_target_path".into(),
+                relative_path(&self.doc_target_path, &self.config_dir).into(),
+            );
+        }
+        if !self.girs_dirs.is_empty() {
+            let girs_dirs: Vec<String> = self
+                .girs_dirs
+                .iter()
+                .map(|dir| relative_path(dir, &self.config_dir))
+                .collect();
+            options.insert("girs_directories".into(), girs_dirs.into());
+        }
+        match self.gir_cache_dir {
+            None => {
+                options.insert("gir_cache".into(), false.into());
+            }
+            Some(ref dir) if Some(dir) != default_gir_cache_dir().as_ref() => {
+                options.insert(
+                    "gir_cache_dir".into(),
+                    relative_path(dir, &self.config_dir).into(),
+                );
+            }
+            Some(_) => (),
+        }
+        let external_libraries: Vec<String> = self
+            .external_libraries
+            .iter()
+            .filter(|lib| lib.crate_name == crate_name(&lib.namespace))
+            .map(|lib| lib.namespace.clone())
+            .collect();
+        if !external_libraries.is_empty() {
+            options.insert("external_libraries".into(), external_libraries.into());
+        }
+        if self.concurrency != library::Concurrency::default() {
+            options.insert(
+                "concurrency".into(),
+                enum_value(schema::CONCURRENCIES, &self.concurrency, |s| s.parse().ok()).into(),
+            );
+        }
+        if !self.generate_display_trait {
+            options.insert("generate_display_trait".into(), false.into());
+        }
+        options.insert_flag(
+            "trust_return_value_nullability",
+            self.trust_return_value_nullability,
+        );
+        options.insert_flag("generate_safety_asserts", self.generate_safety_asserts);
+        options.insert_flag("deprecate_by_min_version", self.deprecate_by_min_version);
+        if let Some(ref file) = self.single_version_file {
+            let value = if *file == make_single_version_file(None, &self.target_path) {
+                true.into()
+            } else {
+                relative_path(file, &self.target_path).into()
+            };
+            options.insert("single_version_file".into(), value);
+        }
+        if !self.disable_format {
+            options.insert("disable_format".into(), false.into());
+        }
+        if !self.format_in_project {
+            options.insert("format_in_project".into(), false.into());
+        }
+        options.insert_flag("split_build_rs", self.split_build_rs);
+        if !self.extra_versions.is_empty() {
+            let extra_versions: Vec<String> =
+                self.extra_versions.iter().map(Version::to_string).collect();
+            options.insert("extra_versions".into(), extra_versions.into());
+        }
+        if !self.docs_rs_features.is_empty() {
+            options.insert(
+                "docs_rs_features".into(),
+                self.docs_rs_features.clone().into(),
+            );
+        }
+        if !self.dox_feature_dependencies.is_empty() {
+            options.insert(
+                "dox_feature_dependencies".into(),
+                self.dox_feature_dependencies.clone().into(),
+            );
+        }
+        if self.data_models != DataModel::ALL {
+            let data_models: Vec<&str> = self
+                .data_models
+                .iter()
+                .map(|model| enum_value(schema::DATA_MODELS, model, |s| s.parse().ok()))
+                .collect();
+            options.insert("data_models".into(), data_models.into());
+        }
+        options.insert_opt(
+            "out_struct_threshold",
+            self.out_struct_threshold.map(|n| n as i64),
+        );
+
+        let objects = self.objects_to_toml();
+        for &status in schema::STATUSES {
+            let names: Vec<Value> = objects
+                .iter()
+                .filter(|object| {
+                    is_status_shorthand(object)
+                        && object.lookup("status").and_then(Value::as_str) == Some(status)
+                })
+                .filter_map(|object| object.lookup("name").cloned())
+                .collect();
+            if !names.is_empty() {
+                options.insert(status.into(), names.into());
+            }
+        }
+        options
+    }
+
+    fn objects_to_toml(&self) -> Vec<Value> {
+        self.objects
+            .values()
+            .map(|object| {
+                object.to_toml(
+                    self.concurrency,
+                    self.generate_display_trait,
+                    self.trust_return_value_nullability,
+                )
+            })
+            .collect()
+    }
+
+    /// Only the modes generating Rust files need `rustfmt`.
+    pub fn check_format(&self) -> Result<(), String> {
+        if self.work_mode.is_generate_rust_files()
+            && !self.disable_format
+            && !crate::fmt::check_fmt()
+        {
+            return Err(
+                "Formatter not found, install rustfmt or set options.disable_format to true".into(),
+            );
+        }
+        Ok(())
     }
 }
 
-fn read_toml<P: AsRef<Path>>(filename: P) -> Result<toml::Value, String> {
+pub(crate) fn read_toml<P: AsRef<Path>>(filename: P) -> Result<toml::Value, String> {
     if !filename.as_ref().is_file() {
         return Err("Config don't exists or not file".to_owned());
     }
@@ -321,6 +672,21 @@ fn read_toml<P: AsRef<Path>>(filename: P) -> Result<toml::Value, String> {
     })
 }
 
+/// Whether the serialized object only has a name and a status and can be
+/// written to `options.generate`, `options.manual` or `options.ignore`
+fn is_status_shorthand(object: &Value) -> bool {
+    matches!(object.as_table(), Some(table) if table.len() == 2)
+}
+
+/// `path` relative to `base` if it's inside of it, like paths are written in the config
+fn relative_path(path: &Path, base: &Path) -> String {
+    match path.strip_prefix(base) {
+        Ok(path) if path.as_os_str().is_empty() => ".".into(),
+        Ok(path) => path.display().to_string(),
+        Err(_) => path.display().to_string(),
+    }
+}
+
 fn make_single_version_file(configured: Option<&str>, target_path: &Path) -> PathBuf {
     let file_dir = match configured {
         None | Some("") => target_path.join("src").join("auto"),
@@ -349,6 +715,59 @@ fn read_crate_name_overrides(toml: &toml::Value) -> HashMap<String, String> {
     overrides
 }
 
+/// Reads `options.girs_directories`, or the single `options.girs_dir`.
+fn read_girs_dirs(toml: &toml::Value, config_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let dirs = match (
+        toml.lookup("options.girs_directories"),
+        toml.lookup("options.girs_dir"),
+    ) {
+        (Some(_), Some(_)) => {
+            return Err(
+                "options.girs_dir and options.girs_directories can't be used together".into(),
+            )
+        }
+        (Some(a), None) => a
+            .as_result_vec("options.girs_directories")?
+            .iter()
+            .map(|v| {
+                v.as_str().ok_or_else(|| {
+                    "options.girs_directories expected to be array of string".to_string()
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        (None, Some(_)) => vec![toml.lookup_str("options.girs_dir", "No options.girs_dir")?],
+        (None, None) => Vec::new(),
+    };
+    Ok(dirs.into_iter().map(|dir| config_dir.join(dir)).collect())
+}
+
+/// Reads `options.gir_cache` and `options.gir_cache_dir`, the cache
+/// defaults to the `gir` directory of the user cache directory.
+fn read_gir_cache_dir(toml: &toml::Value, config_dir: &Path) -> Result<Option<PathBuf>, String> {
+    let enabled = match toml.lookup("options.gir_cache") {
+        Some(v) => v.as_result_bool("options.gir_cache")?,
+        None => true,
+    };
+    if !enabled {
+        return Ok(None);
+    }
+    if let Some(v) = toml.lookup("options.gir_cache_dir") {
+        return Ok(Some(
+            config_dir.join(v.as_result_str("options.gir_cache_dir")?),
+        ));
+    }
+    Ok(default_gir_cache_dir())
+}
+
+/// The `gir` directory of the user cache directory
+fn default_gir_cache_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .map(|dir| dir.join("gir"))
+}
+
 fn read_extra_versions(toml: &toml::Value) -> Result<Vec<Version>, String> {
     match toml.lookup("options.extra_versions") {
         Some(a) => a
@@ -365,6 +784,28 @@ fn read_extra_versions(toml: &toml::Value) -> Result<Vec<Version>, String> {
     }
 }
 
+fn read_data_models(toml: &toml::Value) -> Result<Vec<DataModel>, String> {
+    match toml.lookup("options.data_models") {
+        Some(a) => {
+            let data_models = a
+                .as_result_vec("options.data_models")?
+                .iter()
+                .map(|v| {
+                    v.as_str().ok_or_else(|| {
+                        "options.data_models expected to be array of string".to_string()
+                    })
+                })
+                .map(|s| s.and_then(str::parse))
+                .collect::<Result<Vec<_>, _>>()?;
+            if data_models.is_empty() {
+                return Err("options.data_models can't be empty".into());
+            }
+            Ok(data_models)
+        }
+        None => Ok(DataModel::ALL.to_vec()),
+    }
+}
+
 fn read_dox_feature_dependencies(toml: &toml::Value) -> Result<Vec<String>, String> {
     match toml.lookup("options.dox_feature_dependencies") {
         Some(a) => a
@@ -436,6 +877,55 @@ fn read_feature_dependencies(toml: &toml::Value) -> Result<HashMap<Version, Vec<
 mod tests {
     use super::*;
 
+    fn toml(input: &str) -> ::toml::Value {
+        let value = ::toml::from_str(input);
+        assert!(value.is_ok());
+        value.unwrap()
+    }
+
+    #[test]
+    fn test_read_gir_cache_dir() {
+        let config_dir = Path::new("/tmp/gtk");
+        assert_eq!(
+            read_gir_cache_dir(&toml("[options]\ngir_cache = false"), config_dir),
+            Ok(None)
+        );
+        assert_eq!(
+            read_gir_cache_dir(&toml("[options]\ngir_cache_dir = \"cache\""), config_dir),
+            Ok(Some(PathBuf::from("/tmp/gtk/cache")))
+        );
+        assert!(read_gir_cache_dir(&toml("[options]\ngir_cache = 1"), config_dir).is_err());
+    }
+
+    #[test]
+    fn test_read_girs_dirs() {
+        let config_dir = Path::new("/tmp/gtk");
+        assert_eq!(
+            read_girs_dirs(&toml("[options]\ngirs_dir = \"gir-files\""), config_dir),
+            Ok(vec![PathBuf::from("/tmp/gtk/gir-files")])
+        );
+        assert_eq!(
+            read_girs_dirs(
+                &toml("[options]\ngirs_directories = [\"girs\", \"../gir-files\"]"),
+                config_dir
+            ),
+            Ok(vec![
+                PathBuf::from("/tmp/gtk/girs"),
+                PathBuf::from("/tmp/gtk/../gir-files")
+            ])
+        );
+        assert_eq!(
+            read_girs_dirs(&toml("[options]"), config_dir),
+            Ok(Vec::new())
+        );
+        assert!(read_girs_dirs(
+            &toml("[options]\ngirs_dir = \"a\"\ngirs_directories = [\"b\"]"),
+            config_dir
+        )
+        .is_err());
+        assert!(read_girs_dirs(&toml("[options]\ngirs_directories = [1]"), config_dir).is_err());
+    }
+
     #[test]
     fn test_make_single_version_file() {
         let target_path = Path::new("/tmp/glib");
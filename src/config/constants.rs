@@ -1,7 +1,12 @@
-use super::{error::TomlHelper, gobjects::GStatus, ident::Ident, parsable::Parse};
+use super::{
+    error::TomlHelper,
+    gobjects::GStatus,
+    ident::Ident,
+    parsable::{Parse, TableExt, ToToml},
+};
 use crate::version::Version;
 use log::error;
-use toml::Value;
+use toml::{value::Table, Value};
 
 #[derive(Clone, Debug)]
 pub struct Constant {
@@ -23,17 +28,6 @@ impl Parse for Constant {
                 return None;
             }
         };
-        toml.check_unwanted(
-            &[
-                "ignore",
-                "manual",
-                "name",
-                "version",
-                "cfg_condition",
-                "pattern",
-            ],
-            &format!("function {}", object_name),
-        );
 
         let version = toml
             .lookup("version")
@@ -71,6 +65,17 @@ impl Parse for Constant {
     }
 }
 
+impl ToToml for Constant {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        self.ident.to_toml(&mut table);
+        table.insert_status(self.status);
+        table.insert_opt("version", self.version.map(|v| v.to_string()));
+        table.insert_opt("cfg_condition", self.cfg_condition.as_deref());
+        Value::Table(table)
+    }
+}
+
 impl AsRef<Ident> for Constant {
     fn as_ref(&self) -> &Ident {
         &self.ident
@@ -0,0 +1,173 @@
+use crate::library::Fundamental;
+use std::str::FromStr;
+
+/// C data model of a target, i.e. the sizes of its platform dependent integer types.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataModel {
+    /// 64-bit Unix-like targets
+    Lp64,
+    /// 64-bit Windows
+    Llp64,
+    /// 32-bit targets
+    Ilp32,
+}
+
+impl DataModel {
+    pub const ALL: [DataModel; 3] = [DataModel::Lp64, DataModel::Llp64, DataModel::Ilp32];
+
+    /// Size in bits of a platform dependent C integer type.
+    pub fn bits(self, fund: Fundamental) -> Option<u8> {
+        use self::DataModel::*;
+        use crate::library::Fundamental::*;
+        let bits = match fund {
+            Char | UChar => 8,
+            Short | UShort => 16,
+            Int | UInt => 32,
+            Long | ULong => match self {
+                Lp64 => 64,
+                Llp64 | Ilp32 => 32,
+            },
+            Size | SSize | IntPtr | UIntPtr => match self {
+                Lp64 | Llp64 => 64,
+                Ilp32 => 32,
+            },
+            _ => return Option::None,
+        };
+        Some(bits)
+    }
+}
+
+impl FromStr for DataModel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LP64" => Ok(DataModel::Lp64),
+            "LLP64" => Ok(DataModel::Llp64),
+            "ILP32" => Ok(DataModel::Ilp32),
+            _ => Err(format!("Wrong data model '{}'", s)),
+        }
+    }
+}
+
+/// Name of a platform dependent C integer type in `libc`.
+pub fn c_type_name(fund: Fundamental) -> Option<&'static str> {
+    use crate::library::Fundamental::*;
+    let name = match fund {
+        Char => "c_char",
+        UChar => "c_uchar",
+        Short => "c_short",
+        UShort => "c_ushort",
+        Int => "c_int",
+        UInt => "c_uint",
+        Long => "c_long",
+        ULong => "c_ulong",
+        Size => "size_t",
+        SSize => "ssize_t",
+        IntPtr => "intptr_t",
+        UIntPtr => "uintptr_t",
+        _ => return Option::None,
+    };
+    Some(name)
+}
+
+/// Rust type for a platform dependent C integer type.
+///
+/// This is a fixed-size type if the C type has the same size in all `data_models`
+/// and the `libc` alias otherwise.
+pub fn rust_type(data_models: &[DataModel], fund: Fundamental) -> Option<String> {
+    use crate::library::Fundamental::*;
+    let c_name = c_type_name(fund)?;
+    let signed = match fund {
+        // `usize` always matches `size_t` and `uintptr_t`
+        Size | IntPtr => return Some("usize".into()),
+        SSize => return Some("isize".into()),
+        // The signedness of `char` differs between targets
+        Char => return Some(format!("libc::{}", c_name)),
+        UChar | UShort | UInt | ULong => false,
+        _ => true,
+    };
+
+    let mut sizes = data_models.iter().filter_map(|model| model.bits(fund));
+    let bits = sizes.next()?;
+    if sizes.all(|b| b == bits) {
+        Some(format!("{}{}", if signed { "i" } else { "u" }, bits))
+    } else {
+        Some(format!("libc::{}", c_name))
+    }
+}
+
+/// `ffi` type for a platform dependent C integer type.
+///
+/// `long`, `size_t` and `ssize_t` are mapped like in `rust_type`, the other
+/// types always use the `libc` alias.
+pub fn ffi_type(data_models: &[DataModel], fund: Fundamental) -> Option<String> {
+    use crate::library::Fundamental::*;
+    match fund {
+        Long | ULong | Size | SSize => rust_type(data_models, fund),
+        _ => c_type_name(fund).map(|name| format!("libc::{}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_model_from_str() {
+        assert_eq!("LP64".parse(), Ok(DataModel::Lp64));
+        assert_eq!("LLP64".parse(), Ok(DataModel::Llp64));
+        assert_eq!("ILP32".parse(), Ok(DataModel::Ilp32));
+        assert!("lp64".parse::<DataModel>().is_err());
+    }
+
+    #[test]
+    fn rust_type_all_models() {
+        let models = &DataModel::ALL;
+        assert_eq!(rust_type(models, Fundamental::Int), Some("i32".into()));
+        assert_eq!(rust_type(models, Fundamental::UShort), Some("u16".into()));
+        assert_eq!(
+            rust_type(models, Fundamental::Long),
+            Some("libc::c_long".into())
+        );
+        assert_eq!(
+            rust_type(models, Fundamental::Char),
+            Some("libc::c_char".into())
+        );
+        assert_eq!(rust_type(models, Fundamental::Size), Some("usize".into()));
+        assert_eq!(rust_type(models, Fundamental::Double), None);
+    }
+
+    #[test]
+    fn ffi_type_models() {
+        let models = &DataModel::ALL;
+        assert_eq!(
+            ffi_type(models, Fundamental::Long),
+            Some("libc::c_long".into())
+        );
+        assert_eq!(ffi_type(models, Fundamental::SSize), Some("isize".into()));
+        assert_eq!(
+            ffi_type(models, Fundamental::Int),
+            Some("libc::c_int".into())
+        );
+        assert_eq!(
+            ffi_type(&[DataModel::Lp64], Fundamental::ULong),
+            Some("u64".into())
+        );
+    }
+
+    #[test]
+    fn rust_type_single_model() {
+        assert_eq!(
+            rust_type(&[DataModel::Lp64], Fundamental::ULong),
+            Some("u64".into())
+        );
+        assert_eq!(
+            rust_type(&[DataModel::Llp64], Fundamental::Long),
+            Some("i32".into())
+        );
+        assert_eq!(
+            rust_type(&[DataModel::Llp64, DataModel::Ilp32], Fundamental::Long),
+            Some("i32".into())
+        );
+    }
+}
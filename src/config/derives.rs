@@ -1,6 +1,9 @@
-use super::{error::TomlHelper, parsable::Parse};
+use super::{
+    error::TomlHelper,
+    parsable::{Parse, TableExt, ToToml},
+};
 use log::error;
-use toml::Value;
+use toml::{value::Table, Value};
 
 #[derive(Clone, Debug)]
 pub struct Derive {
@@ -17,10 +20,6 @@ impl Parse for Derive {
                 return None;
             }
         };
-        toml.check_unwanted(
-            &["name", "cfg_condition"],
-            &format!("derive {}", object_name),
-        );
 
         let cfg_condition = toml
             .lookup("cfg_condition")
@@ -39,4 +38,13 @@ impl Parse for Derive {
     }
 }
 
+impl ToToml for Derive {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        table.insert("name".into(), self.names.join(", ").into());
+        table.insert_opt("cfg_condition", self.cfg_condition.as_deref());
+        Value::Table(table)
+    }
+}
+
 pub type Derives = Vec<Derive>;
@@ -0,0 +1,171 @@
+use super::{
+    error::TomlHelper,
+    gobjects::GStatus,
+    ident::Ident,
+    parsable::{Parse, TableExt, ToToml},
+    property_generate_flags::PropertyGenerateFlags,
+};
+use crate::version::Version;
+use log::error;
+use toml::{value::Table, Value};
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub ident: Ident,
+    pub status: GStatus,
+    pub version: Option<Version>,
+    /// Only `get` and `set` are used for fields.
+    pub generate: Option<PropertyGenerateFlags>,
+    pub doc_hidden: bool,
+}
+
+impl Parse for Field {
+    fn parse(toml: &Value, object_name: &str) -> Option<Field> {
+        let ident = match Ident::parse(toml, object_name, "field") {
+            Some(ident) => ident,
+            None => {
+                error!(
+                    "No 'name' or 'pattern' given for field for object {}",
+                    object_name
+                );
+                return None;
+            }
+        };
+
+        let status = {
+            if toml
+                .lookup("ignore")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+            {
+                GStatus::Ignore
+            } else if toml
+                .lookup("manual")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+            {
+                GStatus::Manual
+            } else {
+                GStatus::Generate
+            }
+        };
+        let version = toml
+            .lookup("version")
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok());
+        let generate = toml.lookup("generate").and_then(|v| {
+            PropertyGenerateFlags::parse_flags(v, "generate")
+                .map_err(|e| error!("{} for object {}", e, object_name))
+                .ok()
+        });
+        let doc_hidden = toml
+            .lookup("doc_hidden")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        Some(Field {
+            ident,
+            status,
+            version,
+            generate,
+            doc_hidden,
+        })
+    }
+}
+
+impl ToToml for Field {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        self.ident.to_toml(&mut table);
+        table.insert_status(self.status);
+        table.insert_opt("version", self.version.map(|v| v.to_string()));
+        table.insert_flag("doc_hidden", self.doc_hidden);
+        table.insert_opt(
+            "generate",
+            self.generate.map(PropertyGenerateFlags::to_toml),
+        );
+        Value::Table(table)
+    }
+}
+
+impl AsRef<Ident> for Field {
+    fn as_ref(&self) -> &Ident {
+        &self.ident
+    }
+}
+
+pub type Fields = Vec<Field>;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            ident::Ident,
+            matchable::Matchable,
+            parsable::{Parsable, Parse},
+        },
+        *,
+    };
+    use crate::version::Version;
+
+    fn fields_toml(input: &str) -> ::toml::Value {
+        let mut value: ::toml::value::Table = ::toml::from_str(input).unwrap();
+        value.remove("f").unwrap()
+    }
+
+    fn toml(input: &str) -> ::toml::Value {
+        let value = input.parse();
+        assert!(value.is_ok());
+        value.unwrap()
+    }
+
+    #[test]
+    fn field_parse_ignore() {
+        let toml = toml(
+            r#"
+name = "x"
+ignore = true
+"#,
+        );
+        let f = Field::parse(&toml, "a").unwrap();
+        assert_eq!(f.ident, Ident::Name("x".into()));
+        assert!(f.status.ignored());
+    }
+
+    #[test]
+    fn field_parse_generate() {
+        let toml = toml(
+            r#"
+name = "x"
+version = "3.20"
+generate = ["get"]
+"#,
+        );
+        let f = Field::parse(&toml, "a").unwrap();
+        assert!(f.status.need_generate());
+        assert_eq!(f.version, Some(Version::Full(3, 20, 0)));
+        assert_eq!(f.generate, Some(PropertyGenerateFlags::GET));
+    }
+
+    #[test]
+    fn field_parse_matches() {
+        let toml = fields_toml(
+            r#"
+[[f]]
+name = "x"
+manual = true
+[[f]]
+pattern = "y.*"
+generate = ["set"]
+"#,
+        );
+        let fields = Fields::parse(Some(&toml), "a");
+        assert_eq!(fields.len(), 2);
+        assert!(fields.matched("x")[0].status.manual());
+        assert_eq!(
+            fields.matched("y_offset")[0].generate,
+            Some(PropertyGenerateFlags::SET)
+        );
+        assert!(fields.matched("z").is_empty());
+    }
+}
@@ -3,7 +3,8 @@ use super::{
     gobjects::GStatus,
     ident::Ident,
     parameter_matchable::Functionlike,
-    parsable::{Parsable, Parse},
+    parsable::{Parsable, Parse, TableExt, ToToml},
+    schema::{self, enum_value},
     string_type::StringType,
 };
 use crate::{
@@ -11,7 +12,7 @@ use crate::{
 };
 use log::error;
 use std::str::FromStr;
-use toml::Value;
+use toml::{value::Table, Value};
 
 #[derive(Clone, Debug)]
 pub struct Parameter {
@@ -22,6 +23,10 @@ pub struct Parameter {
     pub nullable: Option<Nullable>,
     pub length_of: Option<String>,
     pub string_type: Option<StringType>,
+    /// Variant type string of a `GVariant` parameter, like `a{sv}`
+    pub variant_type: Option<String>,
+    /// Rust type of the value held by a `GValue` parameter
+    pub value_type: Option<String>,
 }
 
 impl Parse for Parameter {
@@ -36,17 +41,6 @@ impl Parse for Parameter {
                 return None;
             }
         };
-        toml.check_unwanted(
-            &[
-                "const",
-                "nullable",
-                "length_of",
-                "name",
-                "pattern",
-                "string_type",
-            ],
-            &format!("function parameter {}", object_name),
-        );
 
         let constant = toml
             .lookup("const")
@@ -76,16 +70,57 @@ impl Parse for Parameter {
             },
         };
 
+        let variant_type = toml
+            .lookup("variant_type")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let value_type = toml
+            .lookup("value_type")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        if variant_type.is_some() && value_type.is_some() {
+            error!(
+                "\"variant_type\" and \"value_type\" can't be passed at the same time for \
+                 parameter {} of object {}",
+                ident, object_name
+            );
+        }
+
         Some(Parameter {
             ident,
             constant,
             nullable,
             length_of,
             string_type,
+            variant_type,
+            value_type,
         })
     }
 }
 
+impl ToToml for Parameter {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        self.ident.to_toml(&mut table);
+        table.insert_flag("const", self.constant);
+        table.insert_opt("nullable", self.nullable.map(|nullable| nullable.0));
+        table.insert_opt(
+            "length_of",
+            self.length_of
+                .as_deref()
+                .map(|s| if s.is_empty() { "return" } else { s }),
+        );
+        table.insert_opt(
+            "string_type",
+            self.string_type
+                .map(|t| enum_value(schema::STRING_TYPES, &t, |s| s.parse().ok())),
+        );
+        table.insert_opt("variant_type", self.variant_type.as_deref());
+        table.insert_opt("value_type", self.value_type.as_deref());
+        Value::Table(table)
+    }
+}
+
 impl AsRef<Ident> for Parameter {
     fn as_ref(&self) -> &Ident {
         &self.ident
@@ -102,6 +137,8 @@ pub struct Return {
     pub use_return_for_result: Option<bool>,
     pub string_type: Option<StringType>,
     pub type_name: Option<String>,
+    pub variant_type: Option<String>,
+    pub value_type: Option<String>,
 }
 
 impl Return {
@@ -114,21 +151,12 @@ impl Return {
                 use_return_for_result: None,
                 string_type: None,
                 type_name: None,
+                variant_type: None,
+                value_type: None,
             };
         }
 
         let v = toml.unwrap();
-        v.check_unwanted(
-            &[
-                "nullable",
-                "bool_return_is_error",
-                "nullable_return_is_error",
-                "use_return_for_result",
-                "string_type",
-                "type",
-            ],
-            "return",
-        );
 
         let nullable = v.lookup("nullable").and_then(Value::as_bool).map(Nullable);
         let bool_return_is_error = v
@@ -163,6 +191,22 @@ impl Return {
             );
         }
 
+        let variant_type = v
+            .lookup("variant_type")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let value_type = v
+            .lookup("value_type")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        if variant_type.is_some() && value_type.is_some() {
+            error!(
+                "\"variant_type\" and \"value_type\" can't be passed at the same time for \
+                 return of object {}",
+                object_name
+            );
+        }
+
         Return {
             nullable,
             bool_return_is_error,
@@ -170,10 +214,34 @@ impl Return {
             use_return_for_result,
             string_type,
             type_name,
+            variant_type,
+            value_type,
         }
     }
 }
 
+impl ToToml for Return {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        table.insert_opt("nullable", self.nullable.map(|nullable| nullable.0));
+        table.insert_opt("bool_return_is_error", self.bool_return_is_error.as_deref());
+        table.insert_opt(
+            "nullable_return_is_error",
+            self.nullable_return_is_error.as_deref(),
+        );
+        table.insert_opt("use_return_for_result", self.use_return_for_result);
+        table.insert_opt(
+            "string_type",
+            self.string_type
+                .map(|t| enum_value(schema::STRING_TYPES, &t, |s| s.parse().ok())),
+        );
+        table.insert_opt("type", self.type_name.as_deref());
+        table.insert_opt("variant_type", self.variant_type.as_deref());
+        table.insert_opt("value_type", self.value_type.as_deref());
+        Value::Table(table)
+    }
+}
+
 fn check_rename(rename: &Option<String>, object_name: &str, function_name: &Ident) -> bool {
     if let Some(ref rename) = rename {
         for c in &["\t", "\n", " "] {
@@ -202,9 +270,15 @@ pub struct Function {
     pub disable_length_detect: bool,
     pub doc_trait_name: Option<String>,
     pub no_future: bool,
+    pub finish_func: Option<String>,
+    pub iterator: bool,
+    pub iterator_length: Option<String>,
+    pub stream: bool,
     pub unsafe_: bool,
     pub rename: Option<String>,
     pub assertion: Option<SafetyAssertionMode>,
+    pub out_struct: Option<bool>,
+    pub out_struct_name: Option<String>,
 }
 
 impl Parse for Function {
@@ -219,27 +293,6 @@ impl Parse for Function {
                 return None;
             }
         };
-        toml.check_unwanted(
-            &[
-                "ignore",
-                "manual",
-                "version",
-                "cfg_condition",
-                "parameter",
-                "return",
-                "name",
-                "doc_hidden",
-                "is_windows_utf8",
-                "disable_length_detect",
-                "pattern",
-                "doc_trait_name",
-                "no_future",
-                "unsafe",
-                "rename",
-                "assertion",
-            ],
-            &format!("function {}", object_name),
-        );
 
         let status = {
             if toml
@@ -288,6 +341,23 @@ impl Parse for Function {
             .lookup("no_future")
             .and_then(Value::as_bool)
             .unwrap_or(false);
+        let finish_func = toml
+            .lookup("finish_func")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let iterator_length = toml
+            .lookup("iterator_length")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let iterator = iterator_length.is_some()
+            || toml
+                .lookup("iterator")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+        let stream = toml
+            .lookup("stream")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
         let unsafe_ = toml
             .lookup("unsafe")
             .and_then(Value::as_bool)
@@ -309,6 +379,14 @@ impl Parse for Function {
             error!("{}", err);
         }
         let assertion = assertion.ok().flatten();
+        let out_struct_name = toml
+            .lookup("out_struct_name")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let out_struct = toml
+            .lookup("out_struct")
+            .and_then(Value::as_bool)
+            .or_else(|| out_struct_name.as_ref().map(|_| true));
 
         Some(Function {
             ident,
@@ -322,13 +400,56 @@ impl Parse for Function {
             disable_length_detect,
             doc_trait_name,
             no_future,
+            finish_func,
+            iterator,
+            iterator_length,
+            stream,
             unsafe_,
             rename,
             assertion,
+            out_struct,
+            out_struct_name,
         })
     }
 }
 
+impl ToToml for Function {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        self.ident.to_toml(&mut table);
+        table.insert_status(self.status);
+        table.insert_opt("version", self.version.map(|v| v.to_string()));
+        table.insert_opt("cfg_condition", self.cfg_condition.as_deref());
+        table.insert_flag("doc_hidden", self.doc_hidden);
+        table.insert_opt("doc_trait_name", self.doc_trait_name.as_deref());
+        table.insert_flag("is_windows_utf8", self.is_windows_utf8);
+        table.insert_flag("disable_length_detect", self.disable_length_detect);
+        table.insert_flag("no_future", self.no_future);
+        table.insert_opt("finish_func", self.finish_func.as_deref());
+        // implied by `iterator_length`
+        table.insert_flag("iterator", self.iterator && self.iterator_length.is_none());
+        table.insert_opt("iterator_length", self.iterator_length.as_deref());
+        table.insert_flag("stream", self.stream);
+        table.insert_flag("unsafe", self.unsafe_);
+        table.insert_opt("rename", self.rename.as_deref());
+        table.insert_opt(
+            "assertion",
+            self.assertion
+                .map(|a| enum_value(schema::ASSERTION_MODES, &a, |s| s.parse().ok())),
+        );
+        // `out_struct_name` implies `out_struct = true`
+        table.insert_opt(
+            "out_struct",
+            self.out_struct
+                .filter(|&out_struct| !out_struct || self.out_struct_name.is_none()),
+        );
+        table.insert_opt("out_struct_name", self.out_struct_name.as_deref());
+        table.insert_items("parameter", &self.parameters);
+        table.insert_table("return", self.ret.to_toml());
+        Value::Table(table)
+    }
+}
+
 impl Functionlike for Function {
     type Parameter = self::Parameter;
 
@@ -442,6 +563,32 @@ cfg_condition = 'unix'
         assert_eq!(f.cfg_condition, Some("unix".to_string()));
     }
 
+    #[test]
+    fn function_parse_finish_func() {
+        let toml = toml(
+            r#"
+name = "copy_async"
+finish_func = "copy_done"
+"#,
+        );
+        let f = Function::parse(&toml, "a").unwrap();
+        assert_eq!(f.finish_func, Some("copy_done".to_string()));
+    }
+
+    #[test]
+    fn function_parse_iterator() {
+        let toml = toml(
+            r#"
+name = "get_item"
+iterator_length = "get_n_items"
+"#,
+        );
+        let f = Function::parse(&toml, "a").unwrap();
+        assert!(f.iterator);
+        assert_eq!(f.iterator_length, Some("get_n_items".to_string()));
+        assert!(!f.stream);
+    }
+
     #[test]
     fn function_parse_return_nullable_default1() {
         let toml = toml(
@@ -528,6 +675,58 @@ nullable = true
         assert_eq!(f.ret.nullable, Some(Nullable(true)));
     }
 
+    #[test]
+    fn function_parse_typed_variants_and_values() {
+        let toml = toml(
+            r#"
+name = "func1"
+[[parameter]]
+name = "par1"
+variant_type = "a{sv}"
+[[parameter]]
+name = "par2"
+value_type = "i32"
+[return]
+variant_type = "(ss)"
+"#,
+        );
+        let f = Function::parse(&toml, "a").unwrap();
+        assert_eq!(f.parameters[0].variant_type.as_deref(), Some("a{sv}"));
+        assert_eq!(f.parameters[0].value_type, None);
+        assert_eq!(f.parameters[1].value_type.as_deref(), Some("i32"));
+        assert_eq!(f.ret.variant_type.as_deref(), Some("(ss)"));
+        assert_eq!(f.ret.value_type, None);
+    }
+
+    #[test]
+    fn function_parse_out_struct() {
+        let f = Function::parse(&toml(r#"name = "func1""#), "a").unwrap();
+        assert_eq!(f.out_struct, None);
+        let f = Function::parse(
+            &toml(
+                r#"
+name = "func1"
+out_struct = false
+"#,
+            ),
+            "a",
+        )
+        .unwrap();
+        assert_eq!(f.out_struct, Some(false));
+        let f = Function::parse(
+            &toml(
+                r#"
+name = "func1"
+out_struct_name = "Geometry"
+"#,
+            ),
+            "a",
+        )
+        .unwrap();
+        assert_eq!(f.out_struct, Some(true));
+        assert_eq!(f.out_struct_name.as_deref(), Some("Geometry"));
+    }
+
     #[test]
     fn functions_parse_empty_for_none() {
         let fns = Functions::parse(None, "a");
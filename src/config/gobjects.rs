@@ -2,6 +2,7 @@ use super::{
     child_properties::ChildProperties,
     constants::Constants,
     derives::Derives,
+    fields::Fields,
     functions::Functions,
     members::Members,
     properties::Properties,
@@ -11,14 +12,15 @@ use crate::{
     analysis::{conversion_type, ref_mode},
     config::{
         error::TomlHelper,
-        parsable::{Parsable, Parse},
+        parsable::{Parsable, Parse, TableExt, ToToml},
+        schema::{self, enum_value},
     },
-    library::{self, Library, TypeId, MAIN_NAMESPACE},
+    library::{self, Library, TypeId},
     version::Version,
 };
 use log::warn;
 use std::{collections::BTreeMap, str::FromStr};
-use toml::Value;
+use toml::{value::Table, Value};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum GStatus {
@@ -73,6 +75,8 @@ pub struct GObject {
     pub cfg_condition: Option<String>,
     pub type_id: Option<TypeId>,
     pub final_type: Option<bool>,
+    pub direct_record: Option<bool>,
+    pub error_quark_function: Option<String>,
     pub trait_name: Option<String>,
     pub child_properties: Option<ChildProperties>,
     pub concurrency: library::Concurrency,
@@ -88,6 +92,8 @@ pub struct GObject {
     pub builder_postprocess: Option<String>,
     pub init_function_expression: Option<String>,
     pub clear_function_expression: Option<String>,
+    pub generate_field_accessors: bool,
+    pub fields: Fields,
 }
 
 impl Default for GObject {
@@ -106,6 +112,8 @@ impl Default for GObject {
             cfg_condition: None,
             type_id: None,
             final_type: None,
+            direct_record: None,
+            error_quark_function: None,
             trait_name: None,
             child_properties: None,
             concurrency: Default::default(),
@@ -121,10 +129,111 @@ impl Default for GObject {
             builder_postprocess: None,
             init_function_expression: None,
             clear_function_expression: None,
+            generate_field_accessors: false,
+            fields: Fields::new(),
         }
     }
 }
 
+impl GObject {
+    /// Serializes the object, the values that are inherited from the options
+    /// are only written if they differ from them.
+    pub fn to_toml(
+        &self,
+        concurrency: library::Concurrency,
+        generate_display_trait: bool,
+        trust_return_value_nullability: bool,
+    ) -> Value {
+        let mut table = Table::new();
+        table.insert("name".into(), self.name.clone().into());
+        table.insert(
+            "status".into(),
+            enum_value(schema::STATUSES, &self.status, |s| s.parse().ok()).into(),
+        );
+        table.insert_opt("module_name", self.module_name.as_deref());
+        table.insert_opt("version", self.version.map(|v| v.to_string()));
+        table.insert_opt("cfg_condition", self.cfg_condition.as_deref());
+        if self.concurrency != concurrency {
+            table.insert(
+                "concurrency".into(),
+                enum_value(schema::CONCURRENCIES, &self.concurrency, |s| s.parse().ok()).into(),
+            );
+        }
+        table.insert_opt(
+            "ref_mode",
+            self.ref_mode
+                .map(|r| enum_value(schema::REF_MODES, &r, ref_mode_from_str)),
+        );
+        table.insert_opt(
+            "conversion_type",
+            self.conversion_type
+                .map(|c| enum_value(schema::CONVERSION_TYPES, &c, conversion_type_from_str)),
+        );
+        table.insert_opt("final_type", self.final_type);
+        table.insert_opt("direct_record", self.direct_record);
+        table.insert_opt("error_quark_function", self.error_quark_function.as_deref());
+        table.insert_opt("trait_name", self.trait_name.as_deref());
+        table.insert_flag("must_use", self.must_use);
+        if self.generate_display_trait != generate_display_trait {
+            table.insert(
+                "generate_display_trait".into(),
+                self.generate_display_trait.into(),
+            );
+        }
+        if self.trust_return_value_nullability != trust_return_value_nullability {
+            table.insert(
+                "trust_return_value_nullability".into(),
+                self.trust_return_value_nullability.into(),
+            );
+        }
+        if !self.manual_traits.is_empty() {
+            table.insert("manual_traits".into(), self.manual_traits.clone().into());
+        }
+        table.insert_opt("align", self.align);
+        table.insert_flag("generate_builder", self.generate_builder);
+        table.insert_flag("ignore_builder", self.ignore_builder);
+        table.insert_opt("builder_postprocess", self.builder_postprocess.as_deref());
+        table.insert_opt(
+            "init_function_expression",
+            self.init_function_expression.as_deref(),
+        );
+        table.insert_opt(
+            "clear_function_expression",
+            self.clear_function_expression.as_deref(),
+        );
+        table.insert_flag("generate_field_accessors", self.generate_field_accessors);
+        if let Some(ref child_properties) = self.child_properties {
+            child_properties.to_toml(&mut table);
+        }
+        table.insert_items("function", &self.functions);
+        table.insert_items("constant", &self.constants);
+        if !self.signals.is_empty() {
+            table.insert(
+                "signal".into(),
+                self.signals
+                    .iter()
+                    .map(|signal| signal.to_toml(concurrency))
+                    .collect::<Vec<_>>()
+                    .into(),
+            );
+        }
+        table.insert_items("property", &self.properties);
+        table.insert_items("member", &self.members);
+        table.insert_items("field", &self.fields);
+        if let Some(ref derives) = self.derives {
+            table.insert(
+                "derive".into(),
+                derives
+                    .iter()
+                    .map(ToToml::to_toml)
+                    .collect::<Vec<_>>()
+                    .into(),
+            );
+        }
+        Value::Table(table)
+    }
+}
+
 //TODO: ?change to HashMap<String, GStatus>
 pub type GObjects = BTreeMap<String, GObject>;
 
@@ -185,42 +294,6 @@ fn parse_object(
         .as_str()
         .unwrap()
         .into();
-    // Also checks for ChildProperties
-    toml_object.check_unwanted(
-        &[
-            "name",
-            "status",
-            "function",
-            "constant",
-            "signal",
-            "member",
-            "property",
-            "derive",
-            "module_name",
-            "version",
-            "concurrency",
-            "ref_mode",
-            "conversion_type",
-            "child_prop",
-            "child_name",
-            "child_type",
-            "final_type",
-            "trait",
-            "trait_name",
-            "cfg_condition",
-            "must_use",
-            "generate_display_trait",
-            "trust_return_value_nullability",
-            "manual_traits",
-            "align",
-            "generate_builder",
-            "ignore_builder",
-            "builder_postprocess",
-            "init_function_expression",
-            "clear_function_expression",
-        ],
-        &format!("object {}", name),
-    );
 
     let status = match toml_object.lookup("status") {
         Some(value) => {
@@ -267,6 +340,11 @@ fn parse_object(
         .lookup("final_type")
         .and_then(Value::as_bool)
         .or_else(|| generate_trait.map(|t| !t));
+    let direct_record = toml_object.lookup("direct_record").and_then(Value::as_bool);
+    let error_quark_function = toml_object
+        .lookup("error_quark_function")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned);
     let trait_name = toml_object
         .lookup("trait_name")
         .and_then(Value::as_str)
@@ -339,6 +417,11 @@ fn parse_object(
         .lookup("clear_function_expression")
         .and_then(Value::as_str)
         .map(ToOwned::to_owned);
+    let generate_field_accessors = toml_object
+        .lookup("generate_field_accessors")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let fields = Fields::parse(toml_object.lookup("field"), &name);
 
     if (init_function_expression.is_some() && clear_function_expression.is_none())
         || (init_function_expression.is_none() && clear_function_expression.is_some())
@@ -359,6 +442,13 @@ fn parse_object(
         );
     }
 
+    if !generate_field_accessors && !fields.is_empty() {
+        warn!(
+            "field configuration used without `generate_field_accessors` for object {}",
+            name
+        );
+    }
+
     if generate_trait.is_some() {
         warn!(
             "`trait` configuration is deprecated and replaced by `final_type` for object {}",
@@ -380,6 +470,8 @@ fn parse_object(
         cfg_condition,
         type_id: None,
         final_type,
+        direct_record,
+        error_quark_function,
         trait_name,
         child_properties,
         concurrency,
@@ -395,6 +487,8 @@ fn parse_object(
         init_function_expression,
         clear_function_expression,
         ignore_builder,
+        generate_field_accessors,
+        fields,
     }
 }
 
@@ -450,14 +544,10 @@ fn parse_status_shorthand(
 }
 
 pub fn resolve_type_ids(objects: &mut GObjects, library: &Library) {
-    let ns = library.namespace(MAIN_NAMESPACE);
-    let global_functions_name = format!("{}.*", ns.name);
-
     for (name, object) in objects.iter_mut() {
+        // Missing objects are reported by `validate_config`
         let type_id = library.find_type(0, name);
-        if type_id.is_none() && name != &global_functions_name {
-            warn!("Configured object `{}` missing from the library", name);
-        } else if !object.ignore_builder {
+        if !object.ignore_builder {
             if let Some(ref type_id) = type_id {
                 if library.type_(*type_id).is_abstract() {
                     if object.generate_builder {
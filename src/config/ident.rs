@@ -2,7 +2,7 @@ use super::error::TomlHelper;
 use log::error;
 use regex::Regex;
 use std::fmt;
-use toml::Value;
+use toml::{value::Table, Value};
 
 #[derive(Clone, Debug)]
 pub enum Ident {
@@ -63,6 +63,20 @@ impl Ident {
         }
     }
 
+    /// Writes the `name` or `pattern` key read by `Ident::parse`
+    pub fn to_toml(&self, table: &mut Table) {
+        match *self {
+            Ident::Name(ref name) => {
+                table.insert("name".into(), Value::String(name.clone()));
+            }
+            Ident::Pattern(ref regex) => {
+                let pattern = regex.as_str();
+                let pattern = &pattern[1..pattern.len() - 1];
+                table.insert("pattern".into(), Value::String(pattern.into()));
+            }
+        }
+    }
+
     pub fn is_match(&self, name: &str) -> bool {
         use self::Ident::*;
         match *self {
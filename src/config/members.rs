@@ -1,7 +1,12 @@
-use super::{error::TomlHelper, gobjects::GStatus, ident::Ident, parsable::Parse};
+use super::{
+    error::TomlHelper,
+    gobjects::GStatus,
+    ident::Ident,
+    parsable::{Parse, TableExt, ToToml},
+};
 use crate::version::Version;
 use log::error;
-use toml::Value;
+use toml::{value::Table, Value};
 
 #[derive(Clone, Debug)]
 pub struct Member {
@@ -26,11 +31,6 @@ impl Parse for Member {
             }
         };
 
-        toml.check_unwanted(
-            &["alias", "version", "name", "pattern", "ignore", "manual"],
-            &format!("member {}", object_name),
-        );
-
         let alias = toml
             .lookup("alias")
             .and_then(Value::as_bool)
@@ -72,6 +72,21 @@ impl Parse for Member {
     }
 }
 
+impl ToToml for Member {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        self.ident.to_toml(&mut table);
+        table.insert_status(self.status);
+        table.insert_opt("version", self.version.map(|v| v.to_string()));
+        table.insert_opt(
+            "deprecated_version",
+            self.deprecated_version.map(|v| v.to_string()),
+        );
+        table.insert_flag("alias", self.alias);
+        Value::Table(table)
+    }
+}
+
 impl AsRef<Ident> for Member {
     fn as_ref(&self) -> &Ident {
         &self.ident
@@ -2,9 +2,11 @@ mod child_properties;
 #[allow(clippy::module_inception)]
 pub mod config;
 pub mod constants;
+pub mod data_model;
 pub mod derives;
 pub mod error;
 mod external_libraries;
+pub mod fields;
 pub mod functions;
 pub mod gobjects;
 pub mod ident;
@@ -14,9 +16,12 @@ pub mod parameter_matchable;
 pub mod parsable;
 pub mod properties;
 pub mod property_generate_flags;
+pub mod schema;
 pub mod signals;
 pub mod string_type;
+mod validation;
 pub mod work_mode;
+mod workspace;
 
 pub use self::{
     child_properties::{ChildProperties, ChildProperty},
@@ -24,6 +29,9 @@ pub use self::{
     external_libraries::ExternalLibrary,
     gobjects::GObject,
     property_generate_flags::PropertyGenerateFlags,
+    schema::json_schema,
     string_type::StringType,
+    validation::validate_config,
     work_mode::WorkMode,
+    workspace::read_workspace,
 };
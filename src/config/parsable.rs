@@ -1,4 +1,5 @@
-use toml::Value;
+use super::gobjects::GStatus;
+use toml::{value::Table, Value};
 
 pub trait Parse: Sized {
     fn parse(toml: &Value, name: &str) -> Option<Self>;
@@ -26,3 +27,52 @@ impl<T: Parse> Parsable for Vec<T> {
         v
     }
 }
+
+/// Serialization to the TOML read by `Parse`, leaving out default values
+pub trait ToToml {
+    fn to_toml(&self) -> Value;
+}
+
+/// Helpers to only write the keys that aren't set to their default
+pub trait TableExt {
+    fn insert_opt<V: Into<Value>>(&mut self, key: &str, value: Option<V>);
+    fn insert_flag(&mut self, key: &str, value: bool);
+    fn insert_items<T: ToToml>(&mut self, key: &str, items: &[T]);
+    /// Inserts `value` unless it's an empty table
+    fn insert_table(&mut self, key: &str, value: Value);
+    /// Writes `ignore` or `manual` for the status of items of objects
+    fn insert_status(&mut self, status: GStatus);
+}
+
+impl TableExt for Table {
+    fn insert_opt<V: Into<Value>>(&mut self, key: &str, value: Option<V>) {
+        if let Some(value) = value {
+            self.insert(key.into(), value.into());
+        }
+    }
+    fn insert_flag(&mut self, key: &str, value: bool) {
+        if value {
+            self.insert(key.into(), Value::Boolean(true));
+        }
+    }
+    fn insert_items<T: ToToml>(&mut self, key: &str, items: &[T]) {
+        if !items.is_empty() {
+            self.insert(
+                key.into(),
+                Value::Array(items.iter().map(ToToml::to_toml).collect()),
+            );
+        }
+    }
+    fn insert_table(&mut self, key: &str, value: Value) {
+        match value.as_table() {
+            Some(table) if table.is_empty() => (),
+            _ => {
+                self.insert(key.into(), value);
+            }
+        }
+    }
+    fn insert_status(&mut self, status: GStatus) {
+        self.insert_flag("ignore", status == GStatus::Ignore);
+        self.insert_flag("manual", status == GStatus::Manual);
+    }
+}
@@ -1,10 +1,13 @@
 use super::{
-    error::TomlHelper, gobjects::GStatus, ident::Ident, parsable::Parse,
+    error::TomlHelper,
+    gobjects::GStatus,
+    ident::Ident,
+    parsable::{Parse, TableExt, ToToml},
     property_generate_flags::PropertyGenerateFlags,
 };
 use crate::version::Version;
 use log::error;
-use toml::Value;
+use toml::{value::Table, Value};
 
 #[derive(Clone, Debug)]
 pub struct Property {
@@ -28,19 +31,6 @@ impl Parse for Property {
             }
         };
 
-        toml.check_unwanted(
-            &[
-                "ignore",
-                "manual",
-                "version",
-                "name",
-                "pattern",
-                "generate",
-                "doc_trait_name",
-            ],
-            &format!("property {}", object_name),
-        );
-
         let status = {
             if toml
                 .lookup("ignore")
@@ -82,6 +72,21 @@ impl Parse for Property {
     }
 }
 
+impl ToToml for Property {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        self.ident.to_toml(&mut table);
+        table.insert_status(self.status);
+        table.insert_opt("version", self.version.map(|v| v.to_string()));
+        table.insert_opt("doc_trait_name", self.doc_trait_name.as_deref());
+        table.insert_opt(
+            "generate",
+            self.generate.map(PropertyGenerateFlags::to_toml),
+        );
+        Value::Table(table)
+    }
+}
+
 impl AsRef<Ident> for Property {
     fn as_ref(&self) -> &Ident {
         &self.ident
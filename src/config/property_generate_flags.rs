@@ -1,4 +1,4 @@
-use super::error::TomlHelper;
+use super::{error::TomlHelper, schema};
 use bitflags::bitflags;
 use std::str::FromStr;
 
@@ -39,6 +39,16 @@ impl PropertyGenerateFlags {
         }
         Ok(val)
     }
+
+    /// The array of flags read by `parse_flags`
+    pub fn to_toml(self) -> toml::Value {
+        schema::GENERATE_FLAGS
+            .iter()
+            .filter(|flag| matches!(flag.parse(), Ok(flag) if self.contains(flag)))
+            .map(|&flag| toml::Value::from(flag))
+            .collect::<Vec<_>>()
+            .into()
+    }
 }
 #[cfg(test)]
 mod tests {
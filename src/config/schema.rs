@@ -0,0 +1,822 @@
+//! Declarative description of the keys accepted in `Gir.toml`.
+//!
+//! The config files are checked against these lists and the JSON
+//! Schema used by editors is generated from them.
+
+use std::fmt::Write;
+
+/// Type of the value of a key
+#[derive(Debug)]
+pub enum Kind {
+    Bool,
+    Integer,
+    String,
+    /// String that has to be one of the given values
+    Enum(&'static [&'static str]),
+    /// Either a boolean or a string
+    BoolOrString,
+    Array(&'static Kind),
+    Table(&'static [Key]),
+    /// `[[name]]` array of tables
+    TableArray(&'static [Key]),
+    /// Table with arbitrary keys whose values are all of the same kind
+    Map(&'static Kind),
+}
+
+#[derive(Debug)]
+pub struct Key {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub doc: &'static str,
+}
+
+const fn key(name: &'static str, kind: Kind, doc: &'static str) -> Key {
+    Key { name, kind, doc }
+}
+
+pub const CONCURRENCIES: &[&str] = &["none", "send-unique", "send", "send+sync"];
+pub const WORK_MODES: &[&str] = &["normal", "sys", "doc", "not_bound"];
+pub const STATUSES: &[&str] = &["generate", "manual", "ignore"];
+pub const STRING_TYPES: &[&str] = &["utf8", "filename", "os_string"];
+pub const ASSERTION_MODES: &[&str] = &["none", "skip", "in-main-thread"];
+pub const REF_MODES: &[&str] = &["none", "ref", "ref-mut", "ref-immut", "ref-fake"];
+pub const CONVERSION_TYPES: &[&str] = &["direct", "scalar", "pointer", "borrow", "unknown"];
+pub const TRANSFORMATIONS: &[&str] = &["none", "borrow", "treepath"];
+pub const DATA_MODELS: &[&str] = &["LP64", "LLP64", "ILP32"];
+pub const GENERATE_FLAGS: &[&str] = &["get", "set", "notify"];
+
+const STRINGS: Kind = Kind::Array(&Kind::String);
+
+pub const ROOT: &[Key] = &[
+    key(
+        "options",
+        Kind::Table(OPTIONS),
+        "General options of the crate",
+    ),
+    key(
+        "external_libraries",
+        Kind::Map(&Kind::String),
+        "Namespaces of external libraries by crate name",
+    ),
+    key(
+        "crate_name_overrides",
+        Kind::Map(&Kind::String),
+        "Crate names to use instead of the ones derived from the namespaces",
+    ),
+    key(
+        "lib_version_overrides",
+        Kind::TableArray(LIB_VERSION_OVERRIDE),
+        "Library versions checked for feature versions",
+    ),
+    key(
+        "feature_dependencies",
+        Kind::TableArray(FEATURE_DEPENDENCIES),
+        "Extra dependencies of version features",
+    ),
+    key(
+        "object",
+        Kind::TableArray(OBJECT),
+        "Configuration of a type",
+    ),
+];
+
+pub const OPTIONS: &[Key] = &[
+    key("work_mode", Kind::Enum(WORK_MODES), "What to generate"),
+    key("library", Kind::String, "Namespace of the library"),
+    key("version", Kind::String, "Version of the library"),
+    key(
+        "min_cfg_version",
+        Kind::String,
+        "Minimal version of the library supported by the crate",
+    ),
+    key(
+        "target_path",
+        Kind::String,
+        "Path of the crate, relative to the config",
+    ),
+    key(
+        "auto_path",
+        Kind::String,
+        "Path of the generated files, relative to `target_path`",
+    ),
+    key(
+        "doc_target_path",
+        Kind::String,
+        "Path of the documentation file, relative to the config",
+    ),
+    key(
+        "girs_dir",
+        Kind::String,
+        "Directory of the GIR files, relative to the config",
+    ),
+    key(
+        "girs_directories",
+        STRINGS,
+        "Directories searched in order for GIR files, relative to the config",
+    ),
+    key(
+        "gir_cache",
+        Kind::Bool,
+        "Whether parsed GIR files are cached",
+    ),
+    key(
+        "gir_cache_dir",
+        Kind::String,
+        "Directory of the GIR cache, relative to the config",
+    ),
+    key(
+        "external_libraries",
+        STRINGS,
+        "Namespaces of external libraries using the default crate names",
+    ),
+    key(
+        "concurrency",
+        Kind::Enum(CONCURRENCIES),
+        "Default concurrency of the objects",
+    ),
+    key(
+        "generate_display_trait",
+        Kind::Bool,
+        "Default of the objects' `generate_display_trait`",
+    ),
+    key(
+        "trust_return_value_nullability",
+        Kind::Bool,
+        "Trust the nullability annotations of return values",
+    ),
+    key(
+        "generate_safety_asserts",
+        Kind::Bool,
+        "Generate assertions that the library is initialized",
+    ),
+    key(
+        "deprecate_by_min_version",
+        Kind::Bool,
+        "Only mark as deprecated what's deprecated in `min_cfg_version`",
+    ),
+    key(
+        "single_version_file",
+        Kind::BoolOrString,
+        "Write the versions of gir and the GIR files to a single file, or its path",
+    ),
+    key(
+        "disable_format",
+        Kind::Bool,
+        "Don't format the generated files with rustfmt",
+    ),
+    key(
+        "format_in_project",
+        Kind::Bool,
+        "Format the generated files with the edition and `rustfmt.toml` of the project",
+    ),
+    key(
+        "split_build_rs",
+        Kind::Bool,
+        "Generate `build_version.rs` next to a `build.rs` written only once",
+    ),
+    key("extra_versions", STRINGS, "Versions added to the features"),
+    key("docs_rs_features", STRINGS, "Features enabled on docs.rs"),
+    key(
+        "dox_feature_dependencies",
+        STRINGS,
+        "Features enabled by the `dox` feature",
+    ),
+    key(
+        "data_models",
+        Kind::Array(&Kind::Enum(DATA_MODELS)),
+        "C data models of the targets",
+    ),
+    key(
+        "out_struct_threshold",
+        Kind::Integer,
+        "Return a struct from functions with at least this many outputs",
+    ),
+    key("generate", STRINGS, "Objects to generate"),
+    key("manual", STRINGS, "Objects bound manually"),
+    key("ignore", STRINGS, "Objects to ignore"),
+];
+
+pub const LIB_VERSION_OVERRIDE: &[Key] = &[
+    key("version", Kind::String, "Feature version"),
+    key(
+        "lib_version",
+        Kind::String,
+        "Library version to check for it",
+    ),
+];
+
+pub const FEATURE_DEPENDENCIES: &[Key] = &[
+    key("version", Kind::String, "Feature version"),
+    key("dependencies", STRINGS, "Features it enables"),
+];
+
+pub const OBJECT: &[Key] = &[
+    key(
+        "name",
+        Kind::String,
+        "Full name of the type, like `Gtk.Widget`",
+    ),
+    key(
+        "status",
+        Kind::Enum(STATUSES),
+        "Whether the type is generated",
+    ),
+    key("module_name", Kind::String, "Name of the generated file"),
+    key("version", Kind::String, "Version the type was added in"),
+    key(
+        "cfg_condition",
+        Kind::String,
+        "Condition of the `#[cfg]` attribute of the type",
+    ),
+    key(
+        "concurrency",
+        Kind::Enum(CONCURRENCIES),
+        "Traits implemented for use across threads",
+    ),
+    key(
+        "ref_mode",
+        Kind::Enum(REF_MODES),
+        "How a manual type is passed to functions",
+    ),
+    key(
+        "conversion_type",
+        Kind::Enum(CONVERSION_TYPES),
+        "How a manual type is converted to and from C",
+    ),
+    key(
+        "final_type",
+        Kind::Bool,
+        "Type without subclasses, generated without an extension trait",
+    ),
+    key("trait", Kind::Bool, "Deprecated opposite of `final_type`"),
+    key(
+        "direct_record",
+        Kind::Bool,
+        "Wrap the record by value instead of boxing it",
+    ),
+    key(
+        "error_quark_function",
+        Kind::String,
+        "Function returning the error domain of an error enum",
+    ),
+    key("trait_name", Kind::String, "Name of the extension trait"),
+    key("must_use", Kind::Bool, "Add a `#[must_use]` attribute"),
+    key(
+        "generate_display_trait",
+        Kind::Bool,
+        "Implement `Display` for the type",
+    ),
+    key(
+        "trust_return_value_nullability",
+        Kind::Bool,
+        "Trust the nullability annotations of return values",
+    ),
+    key(
+        "manual_traits",
+        STRINGS,
+        "Manual traits linked from the documentation",
+    ),
+    key(
+        "align",
+        Kind::Integer,
+        "Alignment of the record, a power of two",
+    ),
+    key("generate_builder", Kind::Bool, "Generate a builder"),
+    key("ignore_builder", Kind::Bool, "Never generate a builder"),
+    key(
+        "builder_postprocess",
+        Kind::String,
+        "Code run on the built object",
+    ),
+    key(
+        "init_function_expression",
+        Kind::String,
+        "Expression initializing a boxed record",
+    ),
+    key(
+        "clear_function_expression",
+        Kind::String,
+        "Expression clearing a boxed record",
+    ),
+    key(
+        "generate_field_accessors",
+        Kind::Bool,
+        "Generate accessors of the record's fields",
+    ),
+    key(
+        "child_name",
+        Kind::String,
+        "Name of the child parameter of the child properties",
+    ),
+    key("child_type", Kind::String, "Type of the children"),
+    key("child_prop", Kind::TableArray(CHILD_PROP), "Child property"),
+    key("function", Kind::TableArray(FUNCTION), "Function or method"),
+    key("constant", Kind::TableArray(CONSTANT), "Constant"),
+    key("signal", Kind::TableArray(SIGNAL), "Signal"),
+    key("property", Kind::TableArray(PROPERTY), "Property"),
+    key(
+        "member",
+        Kind::TableArray(MEMBER),
+        "Enumeration or flags member",
+    ),
+    key("field", Kind::TableArray(FIELD), "Record field"),
+    key("derive", Kind::TableArray(DERIVE), "Derived traits"),
+];
+
+const NAME: Key = key("name", Kind::String, "Name");
+const PATTERN: Key = key("pattern", Kind::String, "Regular expression matching names");
+const IGNORE: Key = key("ignore", Kind::Bool, "Don't generate it");
+const MANUAL: Key = key("manual", Kind::Bool, "It's bound manually");
+const VERSION: Key = key("version", Kind::String, "Version it was added in");
+const CFG_CONDITION: Key = key(
+    "cfg_condition",
+    Kind::String,
+    "Condition of its `#[cfg]` attribute",
+);
+const DOC_HIDDEN: Key = key("doc_hidden", Kind::Bool, "Hide it from the documentation");
+const DOC_TRAIT_NAME: Key = key("doc_trait_name", Kind::String, "Trait it's documented in");
+const NULLABLE: Key = key("nullable", Kind::Bool, "Whether it can be `None`");
+const STRING_TYPE: Key = key(
+    "string_type",
+    Kind::Enum(STRING_TYPES),
+    "Rust type of a string",
+);
+const VARIANT_TYPE: Key = key(
+    "variant_type",
+    Kind::String,
+    "Variant type string of a `GVariant`",
+);
+const VALUE_TYPE: Key = key(
+    "value_type",
+    Kind::String,
+    "Rust type of the value of a `GValue`",
+);
+const GENERATE: Key = key(
+    "generate",
+    Kind::Array(&Kind::Enum(GENERATE_FLAGS)),
+    "Accessors to generate",
+);
+
+pub const FUNCTION: &[Key] = &[
+    NAME,
+    PATTERN,
+    IGNORE,
+    MANUAL,
+    VERSION,
+    CFG_CONDITION,
+    DOC_HIDDEN,
+    DOC_TRAIT_NAME,
+    key(
+        "is_windows_utf8",
+        Kind::Bool,
+        "Call the `_utf8` variant on Windows",
+    ),
+    key(
+        "disable_length_detect",
+        Kind::Bool,
+        "Don't detect array length parameters",
+    ),
+    key(
+        "no_future",
+        Kind::Bool,
+        "Don't generate a future for the async function",
+    ),
+    key(
+        "finish_func",
+        Kind::String,
+        "Finish function of the async function",
+    ),
+    key("iterator", Kind::Bool, "Return an iterator"),
+    key(
+        "iterator_length",
+        Kind::String,
+        "Function returning the length of the iterator",
+    ),
+    key("stream", Kind::Bool, "Return a stream"),
+    key("unsafe", Kind::Bool, "Mark the function as unsafe"),
+    key("rename", Kind::String, "Name of the generated function"),
+    key(
+        "assertion",
+        Kind::Enum(ASSERTION_MODES),
+        "Safety assertion of the function",
+    ),
+    key(
+        "out_struct",
+        Kind::Bool,
+        "Return a struct instead of a tuple",
+    ),
+    key(
+        "out_struct_name",
+        Kind::String,
+        "Name of the returned struct",
+    ),
+    key("parameter", Kind::TableArray(PARAMETER), "Parameter"),
+    key("return", Kind::Table(RETURN), "Return value"),
+];
+
+pub const PARAMETER: &[Key] = &[
+    NAME,
+    PATTERN,
+    key(
+        "const",
+        Kind::Bool,
+        "The parameter isn't changed by the C function",
+    ),
+    NULLABLE,
+    key(
+        "length_of",
+        Kind::String,
+        "Parameter whose length it is, or `return`",
+    ),
+    STRING_TYPE,
+    VARIANT_TYPE,
+    VALUE_TYPE,
+];
+
+pub const RETURN: &[Key] = &[
+    NULLABLE,
+    key(
+        "bool_return_is_error",
+        Kind::String,
+        "Error message returned when the function returns `false`",
+    ),
+    key(
+        "nullable_return_is_error",
+        Kind::String,
+        "Error message returned when the function returns `NULL`",
+    ),
+    key(
+        "use_return_for_result",
+        Kind::Bool,
+        "Return the value in the `Ok` of the result",
+    ),
+    STRING_TYPE,
+    key("type", Kind::String, "Type of the returned value"),
+    VARIANT_TYPE,
+    VALUE_TYPE,
+];
+
+pub const SIGNAL: &[Key] = &[
+    NAME,
+    PATTERN,
+    IGNORE,
+    MANUAL,
+    VERSION,
+    DOC_HIDDEN,
+    DOC_TRAIT_NAME,
+    key(
+        "inhibit",
+        Kind::Bool,
+        "The handler returns `Inhibit` instead of a boolean",
+    ),
+    key(
+        "concurrency",
+        Kind::Enum(CONCURRENCIES),
+        "Traits required from the handler",
+    ),
+    key("parameter", Kind::TableArray(SIGNAL_PARAMETER), "Parameter"),
+    key("return", Kind::Table(RETURN), "Return value"),
+];
+
+pub const SIGNAL_PARAMETER: &[Key] = &[
+    NAME,
+    PATTERN,
+    NULLABLE,
+    key(
+        "transformation",
+        Kind::Enum(TRANSFORMATIONS),
+        "Conversion of the parameter",
+    ),
+    key("new_name", Kind::String, "Name of the parameter in Rust"),
+];
+
+pub const PROPERTY: &[Key] = &[
+    NAME,
+    PATTERN,
+    IGNORE,
+    MANUAL,
+    VERSION,
+    DOC_TRAIT_NAME,
+    GENERATE,
+];
+
+pub const MEMBER: &[Key] = &[
+    NAME,
+    PATTERN,
+    IGNORE,
+    MANUAL,
+    VERSION,
+    key(
+        "deprecated_version",
+        Kind::String,
+        "Version it was deprecated in",
+    ),
+    key(
+        "alias",
+        Kind::Bool,
+        "Another name of the value of a previous member",
+    ),
+];
+
+pub const CONSTANT: &[Key] = &[NAME, PATTERN, IGNORE, MANUAL, VERSION, CFG_CONDITION];
+
+pub const FIELD: &[Key] = &[NAME, PATTERN, IGNORE, MANUAL, VERSION, DOC_HIDDEN, GENERATE];
+
+pub const DERIVE: &[Key] = &[
+    key(
+        "name",
+        Kind::String,
+        "Comma separated names of the derived traits",
+    ),
+    CFG_CONDITION,
+];
+
+pub const CHILD_PROP: &[Key] = &[
+    NAME,
+    key("type", Kind::String, "Type of the child property"),
+    DOC_HIDDEN,
+];
+
+/// The one of `values` that is parsed to `value`, to serialize enums with
+/// the strings accepted by the parsers.
+pub fn enum_value<T: PartialEq>(
+    values: &'static [&'static str],
+    value: &T,
+    parse: impl Fn(&str) -> Option<T>,
+) -> &'static str {
+    values
+        .iter()
+        .find(|s| parse(s).as_ref() == Some(value))
+        .expect("Value missing from the schema")
+}
+
+/// JSON Schema of `Gir.toml`
+pub fn json_schema() -> String {
+    let mut schema = vec![
+        (
+            "$schema",
+            Json::String("http://json-schema.org/draft-07/schema#"),
+        ),
+        ("title", Json::String("Gir.toml")),
+    ];
+    schema.extend(table_schema(ROOT));
+    let mut out = String::new();
+    Json::Object(schema).write(&mut out, 0);
+    out.push('\n');
+    out
+}
+
+fn kind_schema(kind: &Kind) -> Vec<(&'static str, Json)> {
+    match *kind {
+        Kind::Bool => vec![("type", Json::String("boolean"))],
+        Kind::Integer => vec![("type", Json::String("integer"))],
+        Kind::String => vec![("type", Json::String("string"))],
+        Kind::Enum(values) => vec![
+            ("type", Json::String("string")),
+            (
+                "enum",
+                Json::Array(values.iter().map(|value| Json::String(value)).collect()),
+            ),
+        ],
+        Kind::BoolOrString => vec![(
+            "type",
+            Json::Array(vec![Json::String("boolean"), Json::String("string")]),
+        )],
+        Kind::Array(items) => vec![
+            ("type", Json::String("array")),
+            ("items", Json::Object(kind_schema(items))),
+        ],
+        Kind::Table(keys) => table_schema(keys),
+        Kind::TableArray(keys) => vec![
+            ("type", Json::String("array")),
+            ("items", Json::Object(table_schema(keys))),
+        ],
+        Kind::Map(values) => vec![
+            ("type", Json::String("object")),
+            ("additionalProperties", Json::Object(kind_schema(values))),
+        ],
+    }
+}
+
+fn table_schema(keys: &[Key]) -> Vec<(&'static str, Json)> {
+    let properties = keys
+        .iter()
+        .map(|key| {
+            let mut property = vec![("description", Json::String(key.doc))];
+            property.extend(kind_schema(&key.kind));
+            (key.name, Json::Object(property))
+        })
+        .collect();
+    vec![
+        ("type", Json::String("object")),
+        ("properties", Json::Object(properties)),
+        ("additionalProperties", Json::Bool(false)),
+    ]
+}
+
+enum Json {
+    Bool(bool),
+    String(&'static str),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn write(&self, out: &mut String, indent: usize) {
+        match *self {
+            Json::Bool(value) => {
+                let _ = write!(out, "{}", value);
+            }
+            Json::String(value) => write_json_string(out, value),
+            Json::Array(ref items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    item.write(out, indent);
+                }
+                out.push(']');
+            }
+            Json::Object(ref members) => {
+                out.push('{');
+                for (i, (name, value)) in members.iter().enumerate() {
+                    out.push_str(if i > 0 { ",\n" } else { "\n" });
+                    out.push_str(&"  ".repeat(indent + 1));
+                    write_json_string(out, name);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                }
+                out.push('\n');
+                out.push_str(&"  ".repeat(indent));
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::safety_assertion_mode::SafetyAssertionMode,
+        config::{
+            data_model::DataModel, gobjects, gobjects::GStatus, signals::TransformationType,
+            string_type::StringType, WorkMode,
+        },
+        library::Concurrency,
+    };
+    use std::str::FromStr;
+    use toml::{value::Table, Value};
+
+    fn check_parsed<T: FromStr>(values: &[&str]) {
+        for value in values {
+            assert!(T::from_str(value).is_ok(), "`{}` isn't parsed", value);
+        }
+    }
+
+    /// Checks that all the keys of `value` are described in `keys`
+    fn check_keys(value: &Value, keys: &[Key]) {
+        for (name, value) in value.as_table().unwrap() {
+            let key = keys
+                .iter()
+                .find(|key| key.name == name)
+                .unwrap_or_else(|| panic!("`{}` missing from the schema", name));
+            match (&key.kind, value) {
+                (Kind::Table(keys), _) => check_keys(value, keys),
+                (Kind::TableArray(keys), Value::Array(items)) => {
+                    for item in items {
+                        check_keys(item, keys);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn enum_values_are_parsed() {
+        check_parsed::<Concurrency>(CONCURRENCIES);
+        check_parsed::<WorkMode>(WORK_MODES);
+        check_parsed::<GStatus>(STATUSES);
+        check_parsed::<StringType>(STRING_TYPES);
+        check_parsed::<SafetyAssertionMode>(ASSERTION_MODES);
+        check_parsed::<TransformationType>(TRANSFORMATIONS);
+        check_parsed::<DataModel>(DATA_MODELS);
+    }
+
+    #[test]
+    fn object_to_toml_round_trip() {
+        let toml: Value = toml::from_str(
+            r#"
+[[object]]
+name = "Gtk.Widget"
+status = "generate"
+trait = false
+concurrency = "send"
+generate_display_trait = false
+manual_traits = ["WidgetExtManual"]
+child_name = "item"
+    [[object.child_prop]]
+    name = "position"
+    type = "gint"
+    [[object.function]]
+    pattern = "get_.+"
+    ignore = true
+    [[object.function]]
+    name = "list"
+    iterator_length = "get_n_items"
+    iterator = true
+    assertion = "in-main-thread"
+        [[object.function.parameter]]
+        name = "len"
+        length_of = "return"
+        string_type = "os_string"
+        [object.function.return]
+        nullable = true
+    [[object.signal]]
+    name = "draw"
+    inhibit = true
+        [[object.signal.parameter]]
+        name = "path"
+        transformation = "treepath"
+    [[object.property]]
+    name = "events"
+    generate = ["notify", "get"]
+    [[object.member]]
+    name = "2button_press"
+    alias = true
+    [[object.derive]]
+    name = "Hash,Eq"
+"#,
+        )
+        .unwrap();
+        let objects = gobjects::parse_toml(&toml["object"], Concurrency::None, true, false);
+        let serialized = objects["Gtk.Widget"].to_toml(Concurrency::None, true, false);
+        check_keys(&serialized, OBJECT);
+
+        let expected: Value = toml::from_str(
+            r#"
+name = "Gtk.Widget"
+status = "generate"
+concurrency = "send"
+final_type = true
+generate_display_trait = false
+manual_traits = ["WidgetExtManual"]
+child_name = "item"
+child_prop = [{ name = "position", type = "gint" }]
+function = [
+    { pattern = "get_.+", ignore = true },
+    { name = "list", iterator_length = "get_n_items", assertion = "in-main-thread", parameter = [
+        { name = "len", length_of = "return", string_type = "os_string" },
+    ], return = { nullable = true } },
+]
+signal = [{ name = "draw", inhibit = true, parameter = [{ name = "path", transformation = "treepath" }] }]
+property = [{ name = "events", generate = ["get", "notify"] }]
+member = [{ name = "2button_press", alias = true }]
+derive = [{ name = "Hash, Eq" }]
+"#,
+        )
+        .unwrap();
+        assert_eq!(serialized, expected);
+
+        let mut reparsed = Table::new();
+        reparsed.insert("object".into(), vec![serialized.clone()].into());
+        let objects = gobjects::parse_toml(&reparsed["object"], Concurrency::None, true, false);
+        assert_eq!(
+            objects["Gtk.Widget"].to_toml(Concurrency::None, true, false),
+            serialized
+        );
+    }
+
+    #[test]
+    fn json_schema_of_kinds() {
+        let schema = json_schema();
+        assert!(schema.starts_with("{\n  \"$schema\": "));
+        assert!(schema.contains(r#""enum": ["none", "send-unique", "send", "send+sync"]"#));
+        assert!(schema.contains("\"type\": [\"boolean\", \"string\"]"));
+    }
+
+    #[test]
+    fn json_string_escaping() {
+        let mut out = String::new();
+        write_json_string(&mut out, "a \"b\" \\ c\n\t");
+        assert_eq!(out, r#""a \"b\" \\ c\n\u0009""#);
+    }
+}
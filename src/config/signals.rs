@@ -4,7 +4,8 @@ use super::{
     gobjects::GStatus,
     ident::Ident,
     parameter_matchable::Functionlike,
-    parsable::{Parsable, Parse},
+    parsable::{Parsable, Parse, TableExt, ToToml},
+    schema::{self, enum_value},
 };
 use crate::{
     library::{self, Nullable},
@@ -12,9 +13,9 @@ use crate::{
 };
 use log::error;
 use std::str::FromStr;
-use toml::Value;
+use toml::{value::Table, Value};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TransformationType {
     None,
     Borrow, //replace from_glib_none to from_glib_borrow
@@ -54,10 +55,6 @@ impl Parse for Parameter {
                 return None;
             }
         };
-        toml.check_unwanted(
-            &["nullable", "transformation", "new_name", "name", "pattern"],
-            &format!("parameter {}", object_name),
-        );
 
         let nullable = toml
             .lookup("nullable")
@@ -88,6 +85,21 @@ impl Parse for Parameter {
     }
 }
 
+impl ToToml for Parameter {
+    fn to_toml(&self) -> Value {
+        let mut table = Table::new();
+        self.ident.to_toml(&mut table);
+        table.insert_opt("nullable", self.nullable.map(|nullable| nullable.0));
+        table.insert_opt(
+            "transformation",
+            self.transformation
+                .map(|t| enum_value(schema::TRANSFORMATIONS, &t, |s| s.parse().ok())),
+        );
+        table.insert_opt("new_name", self.new_name.as_deref());
+        Value::Table(table)
+    }
+}
+
 impl AsRef<Ident> for Parameter {
     fn as_ref(&self) -> &Ident {
         &self.ident
@@ -125,22 +137,6 @@ impl Signal {
                 return None;
             }
         };
-        toml.check_unwanted(
-            &[
-                "ignore",
-                "manual",
-                "inhibit",
-                "version",
-                "parameter",
-                "return",
-                "doc_hidden",
-                "name",
-                "pattern",
-                "concurrency",
-                "doc_trait_name",
-            ],
-            &format!("signal {}", object_name),
-        );
 
         let status = {
             if toml
@@ -200,6 +196,29 @@ impl Signal {
     }
 }
 
+impl Signal {
+    /// Serializes the signal, its concurrency is only written if it differs
+    /// from the default `concurrency` of the options.
+    pub fn to_toml(&self, concurrency: library::Concurrency) -> Value {
+        let mut table = Table::new();
+        self.ident.to_toml(&mut table);
+        table.insert_status(self.status);
+        table.insert_opt("version", self.version.map(|v| v.to_string()));
+        table.insert_flag("doc_hidden", self.doc_hidden);
+        table.insert_opt("doc_trait_name", self.doc_trait_name.as_deref());
+        table.insert_flag("inhibit", self.inhibit);
+        if self.concurrency != concurrency {
+            table.insert(
+                "concurrency".into(),
+                enum_value(schema::CONCURRENCIES, &self.concurrency, |s| s.parse().ok()).into(),
+            );
+        }
+        table.insert_items("parameter", &self.parameters);
+        table.insert_table("return", self.ret.to_toml());
+        Value::Table(table)
+    }
+}
+
 impl Functionlike for Signal {
     type Parameter = self::Parameter;
 
@@ -0,0 +1,362 @@
+use super::{
+    gobjects::GObject,
+    ident::Ident,
+    schema::{Key, Kind},
+    Config,
+};
+use crate::{
+    library::{self, Library, Type, MAIN_NAMESPACE},
+    nameutil::split_namespace_name,
+};
+
+/// Checks the configured objects and their functions, signals, properties and
+/// members against the library, returns a description of each one that
+/// doesn't exist, after the unknown keys of the config.
+pub fn validate_config(config: &Config, library: &Library) -> Vec<String> {
+    let global_functions_name = format!("{}.*", library.namespace(MAIN_NAMESPACE).name);
+    let mut problems = config.unknown_keys.clone();
+
+    for (name, object) in &config.objects {
+        if *name == global_functions_name {
+            let ns = library.namespace(MAIN_NAMESPACE);
+            check_idents(
+                &mut problems,
+                name,
+                "function",
+                object.functions.iter().map(|f| &f.ident),
+                ns.functions.iter().map(|f| f.name.as_str()),
+            );
+            check_idents(
+                &mut problems,
+                name,
+                "constant",
+                object.constants.iter().map(|c| &c.ident),
+                ns.constants.iter().map(|c| c.name.as_str()),
+            );
+            continue;
+        }
+
+        match library.find_type(0, name) {
+            Some(tid) => check_object(&mut problems, object, library.type_(tid)),
+            None => problems.push(format!(
+                "Configured object `{}` missing from the library{}",
+                name,
+                did_you_mean(name, type_names(library, name).iter().map(String::as_str))
+            )),
+        }
+    }
+
+    problems
+}
+
+fn check_object(problems: &mut Vec<String>, object: &GObject, typ: &Type) {
+    let name = &object.name;
+    // `get_type` is matched by records, enums and flags without being a function
+    let functions = typ
+        .functions()
+        .iter()
+        .map(|f| f.name.as_str())
+        .chain(Some("get_type"));
+    check_idents(
+        problems,
+        name,
+        "function",
+        object.functions.iter().map(|f| &f.ident),
+        functions,
+    );
+
+    let (signals, properties): (&[library::Signal], &[library::Property]) = match *typ {
+        Type::Class(ref class) => (&class.signals, &class.properties),
+        Type::Interface(ref iface) => (&iface.signals, &iface.properties),
+        _ => (&[], &[]),
+    };
+    check_idents(
+        problems,
+        name,
+        "signal",
+        object.signals.iter().map(|s| &s.ident),
+        signals.iter().map(|s| s.name.as_str()),
+    );
+    check_idents(
+        problems,
+        name,
+        "property",
+        object.properties.iter().map(|p| &p.ident),
+        properties.iter().map(|p| p.name.as_str()),
+    );
+
+    let members: &[library::Member] = match *typ {
+        Type::Enumeration(ref enum_) => &enum_.members,
+        Type::Bitfield(ref bitfield) => &bitfield.members,
+        _ => &[],
+    };
+    check_idents(
+        problems,
+        name,
+        "member",
+        object.members.iter().map(|m| &m.ident),
+        members.iter().map(|m| m.name.as_str()),
+    );
+}
+
+/// Reports the names and patterns of `idents` that don't match any of `names`.
+fn check_idents<'a, 'b>(
+    problems: &mut Vec<String>,
+    object_name: &str,
+    what: &str,
+    idents: impl Iterator<Item = &'a Ident>,
+    names: impl Iterator<Item = &'b str> + Clone,
+) {
+    for ident in idents {
+        if names.clone().any(|name| ident.is_match(name)) {
+            continue;
+        }
+        match *ident {
+            Ident::Name(ref name) => problems.push(format!(
+                "`{}`: {} `{}` not found{}",
+                object_name,
+                what,
+                name,
+                did_you_mean(name, names.clone())
+            )),
+            Ident::Pattern(ref regex) => problems.push(format!(
+                "`{}`: {} pattern `{}` doesn't match anything",
+                object_name, what, regex
+            )),
+        }
+    }
+}
+
+/// Reports the keys of the table `value` missing from `keys`, and the ones of
+/// the tables nested in it, `path` naming the table.
+pub fn check_unknown_keys(
+    problems: &mut Vec<String>,
+    value: &toml::Value,
+    keys: &[Key],
+    path: &str,
+) {
+    let table = match value.as_table() {
+        Some(table) => table,
+        None => return,
+    };
+    for (name, value) in table {
+        let key = match keys.iter().find(|key| key.name == name) {
+            Some(key) => key,
+            None => {
+                let suggestion = did_you_mean(name, keys.iter().map(|key| key.name));
+                problems.push(if path.is_empty() {
+                    format!("Unknown key `{}`{}", name, suggestion)
+                } else {
+                    format!("`{}`: unknown key `{}`{}", path, name, suggestion)
+                });
+                continue;
+            }
+        };
+        let path = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{}, {}", path, name)
+        };
+        check_unknown_kind_keys(problems, value, &key.kind, &path);
+    }
+}
+
+fn check_unknown_kind_keys(
+    problems: &mut Vec<String>,
+    value: &toml::Value,
+    kind: &Kind,
+    path: &str,
+) {
+    match *kind {
+        Kind::Table(keys) => check_unknown_keys(problems, value, keys, path),
+        Kind::TableArray(keys) => {
+            for table in value.as_array().into_iter().flatten() {
+                // Tables of objects, functions, etc. are named by what they configure
+                let name = table
+                    .get("name")
+                    .or_else(|| table.get("pattern"))
+                    .and_then(toml::Value::as_str);
+                match name {
+                    Some(name) => {
+                        check_unknown_keys(problems, table, keys, &format!("{} {}", path, name))
+                    }
+                    None => check_unknown_keys(problems, table, keys, path),
+                }
+            }
+        }
+        Kind::Array(kind) => {
+            for value in value.as_array().into_iter().flatten() {
+                check_unknown_kind_keys(problems, value, kind, path);
+            }
+        }
+        Kind::Map(kind) => {
+            for value in value
+                .as_table()
+                .into_iter()
+                .flat_map(|table| table.values())
+            {
+                check_unknown_kind_keys(problems, value, kind, path);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Full names of the types in the namespace of `name`, or of the namespaces
+/// themselves if it doesn't exist.
+fn type_names(library: &Library, name: &str) -> Vec<String> {
+    let (ns_name, type_name) = split_namespace_name(name);
+    let ns_name = ns_name.unwrap_or_default();
+    match library.find_namespace(ns_name) {
+        Some(ns_id) => library
+            .namespace(ns_id)
+            .index
+            .keys()
+            .filter(|type_name| !type_name.starts_with('#'))
+            .map(|type_name| format!("{}.{}", ns_name, type_name))
+            .collect(),
+        None => library
+            .namespaces
+            .iter()
+            .map(|ns| format!("{}.{}", ns.name, type_name))
+            .collect(),
+    }
+}
+
+/// A `, did you mean ...?` suggestion of the most similar of `candidates`,
+/// or nothing if none of them is close enough.
+pub fn did_you_mean<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> String {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| format!(", did you mean `{}`?", candidate))
+        .unwrap_or_default()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + if ca == cb { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("label", "label"), 0);
+        assert_eq!(edit_distance("lable", "label"), 2);
+        assert_eq!(edit_distance("get_labl", "get_label"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let candidates = ["get_label", "set_label", "get_use_underline"];
+        assert_eq!(
+            did_you_mean("get_lable", candidates.iter().cloned()),
+            ", did you mean `get_label`?"
+        );
+        assert_eq!(
+            did_you_mean("set_labl", candidates.iter().cloned()),
+            ", did you mean `set_label`?"
+        );
+        assert_eq!(did_you_mean("new", candidates.iter().cloned()), "");
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let config = Config::from_toml_str(
+            r#"
+[options]
+library = "Foo"
+version = "1.0"
+target_path = "."
+work_mode = "normal"
+min_cfg_versoin = "1.2"
+
+[[object]]
+name = "Foo.*"
+status = "generate"
+    [[object.function]]
+    name = "ini"
+
+[[object]]
+name = "Foo.Point"
+status = "generate"
+    [[object.function]]
+    name = "copy"
+        [object.function.return]
+        nulable = true
+
+[[object]]
+name = "Foo.Colour"
+status = "generate"
+
+[[object]]
+name = "Foo.Color"
+status = "generate"
+    [[object.member]]
+    pattern = "gren.*"
+"#,
+        )
+        .unwrap();
+        let mut library = Library::new("Foo");
+        library
+            .read_str(
+                r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0">
+  <namespace name="Foo" version="1.0" c:identifier-prefixes="Foo" c:symbol-prefixes="foo">
+    <record name="Point" c:type="FooPoint">
+      <field name="x"><type name="gint" c:type="gint"/></field>
+      <method name="copy" c:identifier="foo_point_copy">
+        <return-value transfer-ownership="full">
+          <type name="Point" c:type="FooPoint*"/>
+        </return-value>
+        <parameters>
+          <instance-parameter name="point"><type name="Point" c:type="FooPoint*"/></instance-parameter>
+        </parameters>
+      </method>
+    </record>
+    <enumeration name="Color" c:type="FooColor" c:symbol-prefix="color">
+      <member name="red" value="0" c:identifier="FOO_COLOR_RED"/>
+      <member name="green" value="1" c:identifier="FOO_COLOR_GREEN"/>
+    </enumeration>
+    <function name="init" c:identifier="foo_init">
+      <return-value><type name="none" c:type="void"/></return-value>
+    </function>
+  </namespace>
+</repository>
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            validate_config(&config, &library),
+            [
+                "`options`: unknown key `min_cfg_versoin`, did you mean `min_cfg_version`?",
+                "`object Foo.Point, function copy, return`: unknown key `nulable`, \
+                 did you mean `nullable`?",
+                "`Foo.*`: function `ini` not found, did you mean `init`?",
+                "`Foo.Color`: member pattern `^gren.*$` doesn't match anything",
+                "Configured object `Foo.Colour` missing from the library, did you mean `Foo.Color`?",
+            ]
+        );
+    }
+}
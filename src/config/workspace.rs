@@ -0,0 +1,61 @@
+use super::{config::read_toml, error::TomlHelper};
+use std::path::{Path, PathBuf};
+
+/// Reads the config files listed in a `GirWorkspace.toml`,
+/// relative to its directory.
+pub fn read_workspace(workspace_file: &Path) -> Result<Vec<PathBuf>, String> {
+    let toml = read_toml(workspace_file).map_err(|e| {
+        format!(
+            "Error while reading \"{}\": {}",
+            workspace_file.display(),
+            e
+        )
+    })?;
+    let workspace_dir = workspace_file.parent().unwrap_or_else(|| Path::new(""));
+    read_configs(&toml, workspace_dir)
+}
+
+fn read_configs(toml: &toml::Value, workspace_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let configs = toml
+        .lookup_vec("configs", "No configs in workspace")?
+        .iter()
+        .map(|v| {
+            v.as_str()
+                .map(|config| workspace_dir.join(config))
+                .ok_or_else(|| "configs expected to be array of string".to_owned())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if configs.is_empty() {
+        return Err("No configs in workspace".to_owned());
+    }
+    Ok(configs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toml(input: &str) -> ::toml::Value {
+        let value = ::toml::from_str(input);
+        assert!(value.is_ok());
+        value.unwrap()
+    }
+
+    #[test]
+    fn test_read_configs() {
+        let workspace_dir = Path::new("/tmp/gtk-rs");
+        assert_eq!(
+            read_configs(
+                &toml("configs = [\"glib/Gir.toml\", \"gio/sys/Gir.toml\"]"),
+                workspace_dir
+            ),
+            Ok(vec![
+                PathBuf::from("/tmp/gtk-rs/glib/Gir.toml"),
+                PathBuf::from("/tmp/gtk-rs/gio/sys/Gir.toml"),
+            ])
+        );
+        assert!(read_configs(&toml("configs = []"), workspace_dir).is_err());
+        assert!(read_configs(&toml("configs = [1]"), workspace_dir).is_err());
+        assert!(read_configs(&toml("[options]"), workspace_dir).is_err());
+    }
+}
@@ -43,7 +43,7 @@ struct ReplaceToPriority {
 
 impl FunctionsMutVisitor for ReplaceToPriority {
     fn visit_function_mut(&mut self, func: &mut Function) -> bool {
-        if !func.name.ends_with("_async") {
+        if !func.name.ends_with("_async") && func.finish_func.is_none() {
             return true;
         }
         for par in &mut func.parameters {
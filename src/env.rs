@@ -1,19 +1,25 @@
 use crate::{
     analysis,
     config::{gobjects::GStatus, Config},
+    file_saver::FileStatus,
     library::*,
     version::Version,
 };
-use std::cell::RefCell;
+use std::{
+    path::PathBuf,
+    sync::{Mutex, RwLock},
+};
 
 #[derive(Debug)]
 pub struct Env {
     pub library: Library,
     pub config: Config,
     pub namespaces: analysis::namespaces::Info,
-    pub symbols: RefCell<analysis::symbols::Info>,
+    pub symbols: RwLock<analysis::symbols::Info>,
     pub class_hierarchy: analysis::class_hierarchy::Info,
     pub analysis: analysis::Analysis,
+    /// Files written or removed by the code generation
+    pub generated_files: Mutex<Vec<(PathBuf, FileStatus)>>,
 }
 
 impl Env {
@@ -52,7 +58,87 @@ impl Env {
         }
     }
 
+    /// Number of generated files by status, like `2 created, 1 updated, 10 unchanged`
+    pub fn generated_files_summary(&self) -> String {
+        let generated_files = self.generated_files.lock().unwrap();
+        let count = |status| {
+            generated_files
+                .iter()
+                .filter(|&&(_, file_status)| file_status == status)
+                .count()
+        };
+        let mut summary = format!(
+            "{} created, {} updated, {} unchanged",
+            count(FileStatus::Created),
+            count(FileStatus::Updated),
+            count(FileStatus::Unchanged)
+        );
+        let removed = count(FileStatus::Removed);
+        if removed > 0 {
+            summary.push_str(&format!(", {} removed", removed));
+        }
+        let failed = count(FileStatus::Failed);
+        if failed > 0 {
+            summary.push_str(&format!(", {} failed", failed));
+        }
+        summary
+    }
+
+    /// Fails with the files that couldn't be formatted
+    pub fn check_generated_files(&self) -> Result<(), String> {
+        let failed: Vec<_> = self
+            .generated_files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|&&(_, status)| status == FileStatus::Failed)
+            .map(|(path, _)| path.display().to_string())
+            .collect();
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Failed to format {}", failed.join(", ")))
+        }
+    }
+
     pub fn main_sys_crate_name(&self) -> &str {
         &self.namespaces[MAIN_NAMESPACE].sys_crate_name
     }
 }
+
+#[cfg(test)]
+impl Env {
+    /// Analyzes the library read from the GIR documents `girs` with the config `toml`,
+    /// the way a run of gir does before generating code.
+    ///
+    /// Included namespaces must come before the ones including them.
+    pub fn for_test(girs: &[&str], toml: &str) -> Env {
+        let mut config = Config::from_toml_str(toml).unwrap();
+        let mut library = Library::new(&config.library_name);
+        for gir in girs {
+            library.read_str(gir).unwrap();
+        }
+        library.preprocessing(config.work_mode);
+        crate::update_version::apply_config(&mut library, &config);
+        library.postprocessing(&config);
+        config.resolve_type_ids(&library);
+        crate::update_version::check_function_real_version(&mut library);
+
+        let namespaces = analysis::namespaces::run(&library);
+        let symbols = analysis::symbols::run(&library, &namespaces);
+        let class_hierarchy = analysis::class_hierarchy::run(&library);
+        let mut env = Env {
+            library,
+            config,
+            namespaces,
+            symbols: RwLock::new(symbols),
+            class_hierarchy,
+            analysis: Default::default(),
+            generated_files: Default::default(),
+        };
+        if env.config.work_mode != crate::config::WorkMode::Sys {
+            analysis::run(&mut env);
+        }
+        env
+    }
+}
@@ -1,29 +1,106 @@
-use crate::writer::untabber::Untabber;
+use crate::{env::Env, fmt, writer::untabber::Untabber};
+use log::{error, info};
 use std::{
-    fs::{self, File},
-    io::{BufWriter, Result, Write},
+    ffi::OsStr,
+    fs,
+    io::{Result, Write},
     path::Path,
 };
 
-pub fn save_to_file<P, F>(path: P, make_backup: bool, mut closure: F)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileStatus {
+    Created,
+    Updated,
+    Unchanged,
+    /// Previously generated file that isn't generated anymore
+    Removed,
+    /// Generated file that couldn't be formatted and wasn't written
+    Failed,
+}
+
+/// Writes a generated file and records it in `env.generated_files`.
+///
+/// Rust files are formatted before being written unless formatting is disabled.
+/// Files with unchanged content aren't rewritten to keep their modification time,
+/// files that couldn't be formatted are left as they were.
+pub fn save_to_file<P, F>(path: P, env: &Env, mut closure: F)
 where
     P: AsRef<Path>,
     F: FnMut(&mut dyn Write) -> Result<()>,
 {
     let path = path.as_ref();
+    let mut content = Vec::new();
+    {
+        let mut untabber = Untabber::new(Box::new(&mut content));
+        closure(&mut untabber)
+            .unwrap_or_else(|why| panic!("couldn't write to {:?}: {:?}", path, why));
+    }
     if let Some(parent) = path.parent() {
         let _ = fs::create_dir_all(parent);
     }
+    if !env.config.disable_format && path.extension() == Some(OsStr::new("rs")) {
+        let project =
+            Some(env.config.target_path.as_path()).filter(|_| env.config.format_in_project);
+        match fmt::format(&content, path, project) {
+            Ok(formatted) => content = formatted,
+            Err(why) => {
+                error!("couldn't format {:?}: {}", path, why);
+                env.generated_files
+                    .lock()
+                    .unwrap()
+                    .push((path.to_owned(), FileStatus::Failed));
+                return;
+            }
+        }
+    }
+
+    let status = match fs::read(path) {
+        Ok(ref old_content) if *old_content == content => FileStatus::Unchanged,
+        Ok(_) => FileStatus::Updated,
+        Err(_) => FileStatus::Created,
+    };
+
+    if status != FileStatus::Unchanged {
+        if env.config.make_backup {
+            let _backuped = create_backup(&path)
+                .unwrap_or_else(|why| panic!("couldn't create backup for {:?}: {:?}", path, why));
+        }
+        fs::write(path, content)
+            .unwrap_or_else(|why| panic!("couldn't create {:?}: {}", path, why));
+    }
 
-    if make_backup {
-        let _backuped = create_backup(&path)
-            .unwrap_or_else(|why| panic!("couldn't create backup for {:?}: {:?}", path, why));
+    env.generated_files
+        .lock()
+        .unwrap()
+        .push((path.to_owned(), status));
+}
+
+/// Removes the files generated by gir in `dir` that weren't generated in
+/// this run, like the ones of objects that were removed from the config.
+pub fn remove_stale_files(env: &Env, dir: &Path) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let mut generated_files = env.generated_files.lock().unwrap();
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path.extension() != Some(OsStr::new("rs"))
+            || generated_files.iter().any(|(file, _)| *file == path)
+            || !is_generated_file(&path)
+        {
+            continue;
+        }
+        info!("Removing stale file {:?}", path);
+        fs::remove_file(&path).unwrap_or_else(|why| panic!("couldn't remove {:?}: {}", path, why));
+        generated_files.push((path, FileStatus::Removed));
     }
-    let file =
-        File::create(&path).unwrap_or_else(|why| panic!("couldn't create {:?}: {}", path, why));
-    let writer = BufWriter::new(file);
-    let mut untabber = Untabber::new(Box::new(writer));
-    closure(&mut untabber).unwrap_or_else(|why| panic!("couldn't write to {:?}: {:?}", path, why));
+}
+
+/// Whether the file starts with the comment written by `general::start_comments`
+fn is_generated_file(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.starts_with("// This file was generated by gir "))
+        .unwrap_or(false)
 }
 
 /// Create .bak file
@@ -34,3 +111,164 @@ pub fn create_backup<P: AsRef<Path>>(path: P) -> Result<bool> {
     let new_path = path.as_ref().with_extension("bak");
     fs::rename(path, new_path).map(|_| true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const GLIB_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0">
+  <namespace name="GLib" version="2.0" c:identifier-prefixes="G" c:symbol-prefixes="g">
+  </namespace>
+</repository>
+"#;
+
+    /// Env formatting and generating into the new directory `name` with the `options`.
+    fn env(name: &str, options: &str) -> (Env, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "gir-file-saver-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let toml = format!(
+            "[options]\nlibrary = \"GLib\"\nversion = \"2.0\"\n\
+             target_path = \".\"\nwork_mode = \"normal\"\n{}",
+            options
+        );
+        let mut env = Env::for_test(&[GLIB_GIR], &toml);
+        env.config.disable_format = false;
+        env.config.target_path = dir.clone();
+        env.config.auto_path = dir.join("auto");
+        (env, dir)
+    }
+
+    fn statuses(env: &Env) -> Vec<(PathBuf, FileStatus)> {
+        env.generated_files.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn formatted_in_project() {
+        if !fmt::check_fmt() {
+            return;
+        }
+        let (env, dir) = env("project", "");
+        fs::write(dir.join("rustfmt.toml"), "hard_tabs = true\n").unwrap();
+        let path = dir.join("auto").join("foo.rs");
+        let generate = |w: &mut dyn Write| writeln!(w, "fn foo() {{ let x = 1; }}");
+
+        save_to_file(&path, &env, generate);
+        save_to_file(&path, &env, generate);
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(content, "fn foo() {\n\tlet x = 1;\n}\n");
+        assert_eq!(
+            statuses(&env),
+            [
+                (path.clone(), FileStatus::Created),
+                (path, FileStatus::Unchanged)
+            ]
+        );
+    }
+
+    #[test]
+    fn formatted_independently_of_project() {
+        if !fmt::check_fmt() {
+            return;
+        }
+        let (env, dir) = env("independent", "format_in_project = false\n");
+        fs::write(dir.join("rustfmt.toml"), "hard_tabs = true\n").unwrap();
+        let path = dir.join("auto").join("foo.rs");
+
+        save_to_file(&path, &env, |w| writeln!(w, "fn foo() {{ let x = 1; }}"));
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(content, "fn foo() {\n    let x = 1;\n}\n");
+    }
+
+    #[test]
+    fn format_failure() {
+        if !fmt::check_fmt() {
+            return;
+        }
+        let (env, dir) = env("failure", "");
+        let path = dir.join("auto").join("foo.rs");
+        fs::create_dir_all(dir.join("auto")).unwrap();
+        fs::write(&path, "// old\n").unwrap();
+
+        save_to_file(&path, &env, |w| writeln!(w, "fn foo( {{"));
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(content, "// old\n");
+        assert_eq!(statuses(&env), [(path.clone(), FileStatus::Failed)]);
+        assert_eq!(
+            env.check_generated_files(),
+            Err(format!("Failed to format {}", path.display()))
+        );
+    }
+
+    #[test]
+    fn unchanged_file_not_rewritten() {
+        let (mut env, dir) = env("unchanged", "");
+        env.config.disable_format = true;
+        let path = dir.join("auto").join("foo.rs");
+
+        save_to_file(&path, &env, |w| writeln!(w, "fn foo() {{}}"));
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        save_to_file(&path, &env, |w| writeln!(w, "fn foo() {{}}"));
+        let unchanged = fs::metadata(&path).unwrap().modified().unwrap();
+        save_to_file(&path, &env, |w| writeln!(w, "fn bar() {{}}"));
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(unchanged, modified);
+        assert_eq!(content, "fn bar() {}\n");
+        assert_eq!(
+            statuses(&env),
+            [
+                (path.clone(), FileStatus::Created),
+                (path.clone(), FileStatus::Unchanged),
+                (path, FileStatus::Updated)
+            ]
+        );
+    }
+
+    #[test]
+    fn stale_files_removed() {
+        let (mut env, dir) = env("stale", "");
+        env.config.disable_format = true;
+        let auto = dir.join("auto");
+        fs::create_dir_all(&auto).unwrap();
+        let header = "// This file was generated by gir (abcdef)\n";
+        let stale = auto.join("stale.rs");
+        fs::write(&stale, header).unwrap();
+        let manual = auto.join("manual.rs");
+        fs::write(&manual, "// Hand-written\n").unwrap();
+        let generated = auto.join("generated.rs");
+        fs::write(&generated, header).unwrap();
+
+        save_to_file(&generated, &env, |w| write!(w, "{}", header));
+        remove_stale_files(&env, &auto);
+        let (stale_exists, manual_exists, generated_exists) =
+            (stale.exists(), manual.exists(), generated.exists());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!stale_exists);
+        assert!(manual_exists);
+        assert!(generated_exists);
+        assert_eq!(
+            statuses(&env),
+            [
+                (generated, FileStatus::Unchanged),
+                (stale, FileStatus::Removed)
+            ]
+        );
+    }
+}
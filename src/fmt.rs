@@ -1,10 +1,15 @@
-use log::warn;
+use std::env;
+use std::fs;
+use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
-/// Check if `cargo fmt` available
+/// Edition the generated code is formatted for
+const EDITION: &str = "2018";
+
+/// Check if `rustfmt` available
 pub fn check_fmt() -> bool {
-    let output = Command::new("cargo").arg("fmt").arg("--version").output();
+    let output = Command::new("rustfmt").arg("--version").output();
     if let Ok(output) = output {
         output.status.success()
     } else {
@@ -12,19 +17,58 @@ pub fn check_fmt() -> bool {
     }
 }
 
-/// Run `cargo fmt` on path
-pub fn format(path: &Path) {
-    let output = Command::new("cargo").arg("fmt").current_dir(path).output();
-    match output {
-        Ok(output) if output.status.success() => {}
-        Ok(output) => {
-            warn!(
-                "Failed to format {}:\n{}\n{}",
-                path.display(),
-                String::from_utf8_lossy(&output.stdout),
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-        Err(_) => { /*We checked `cargo` fmt presence in check_fmt, so can ignore errors*/ }
+/// Formats the source of the generated file `path` with `rustfmt`.
+///
+/// With `project`, the edition and `rustfmt.toml` of the crate in it are used
+/// like `cargo fmt` does. Otherwise it's run outside of the project so that the
+/// output doesn't depend on its `rustfmt.toml`.
+pub fn format(source: &[u8], path: &Path, project: Option<&Path>) -> Result<Vec<u8>, String> {
+    let (edition, dir) = match project {
+        // `rustfmt` looks for the `rustfmt.toml` from its current directory
+        // when formatting stdin, like it does from the directory of a file
+        Some(project) => (
+            crate_edition(project),
+            path.parent()
+                .map_or_else(|| project.to_owned(), ToOwned::to_owned),
+        ),
+        None => (EDITION.to_owned(), env::temp_dir()),
+    };
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", &edition, "--emit", "stdout"])
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run rustfmt: {}", e))?;
+    // rustfmt reads all its input before writing anything
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source)
+        .map_err(|e| format!("failed to write to rustfmt: {}", e))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("failed to run rustfmt: {}", e))?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
     }
 }
+
+/// Edition from `Cargo.toml` like `cargo fmt` uses it, defaults to 2015
+fn crate_edition(path: &Path) -> String {
+    fs::read_to_string(path.join("Cargo.toml"))
+        .ok()
+        .and_then(|input| toml::from_str::<toml::Value>(&input).ok())
+        .and_then(|toml| {
+            toml.get("package")?
+                .get("edition")?
+                .as_str()
+                .map(ToOwned::to_owned)
+        })
+        .unwrap_or_else(|| "2015".to_owned())
+}
@@ -29,8 +29,10 @@ mod file_saver;
 pub mod fmt;
 mod git;
 pub mod library;
+mod library_cache;
 mod library_postprocessing;
 mod library_preprocessing;
+mod library_rebind;
 mod nameutil;
 mod parser;
 mod traits;
@@ -46,7 +48,7 @@ pub use crate::{
         run as analysis_run, symbols::run as symbols_run,
     },
     codegen::generate as codegen_generate,
-    config::{Config, WorkMode},
+    config::{json_schema, read_workspace, validate_config, Config, WorkMode},
     env::Env,
     library::Library,
 };
@@ -8,6 +8,7 @@ use std::{
     fmt,
     iter::Iterator,
     ops::{Deref, DerefMut},
+    path::PathBuf,
     str::FromStr,
 };
 
@@ -322,7 +323,7 @@ impl TypeId {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Alias {
     pub name: String,
     pub c_identifier: String,
@@ -332,7 +333,7 @@ pub struct Alias {
     pub doc_deprecated: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Constant {
     pub name: String,
     pub c_identifier: String,
@@ -345,7 +346,7 @@ pub struct Constant {
     pub doc_deprecated: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Member {
     pub name: String,
     pub c_identifier: String,
@@ -353,13 +354,13 @@ pub struct Member {
     pub doc: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ErrorDomain {
     Quark(String),
     Function(String),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Enumeration {
     pub name: String,
     pub c_type: String,
@@ -374,7 +375,7 @@ pub struct Enumeration {
     pub glib_get_type: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Bitfield {
     pub name: String,
     pub c_type: String,
@@ -388,7 +389,7 @@ pub struct Bitfield {
     pub glib_get_type: Option<String>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct Record {
     pub name: String,
     pub c_type: String,
@@ -404,9 +405,14 @@ pub struct Record {
     /// A 'disguised' record is one where the c:type is a typedef that
     /// doesn't look like a pointer, but is internally: typedef struct _X *X;
     pub disguised: bool,
+    /// Record coming from a standalone `<glib:boxed>` element: it only has a GType
+    /// and its C struct is opaque.
+    pub standalone_boxed: bool,
+    /// Plain C struct wrapped by value, see `RecordType::Direct`.
+    pub direct: bool,
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct Field {
     pub name: String,
     pub typ: TypeId,
@@ -417,7 +423,7 @@ pub struct Field {
     pub doc: Option<String>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct Union {
     pub name: String,
     pub c_type: Option<String>,
@@ -428,7 +434,7 @@ pub struct Union {
     pub doc: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Property {
     pub name: String,
     pub readable: bool,
@@ -465,7 +471,7 @@ pub struct Parameter {
     pub destroy: Option<usize>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Function {
     pub name: String,
     pub c_identifier: Option<String>,
@@ -477,9 +483,15 @@ pub struct Function {
     pub deprecated_version: Option<Version>,
     pub doc: Option<String>,
     pub doc_deprecated: Option<String>,
+    /// Name of the function finishing this async function (`glib:finish-func`).
+    pub finish_func: Option<String>,
+    /// Name of the synchronous version of this async function (`glib:sync-func`).
+    pub sync_func: Option<String>,
+    /// Name of the async function this function finishes (`glib:async-func`).
+    pub async_func: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Signal {
     pub name: String,
     pub parameters: Vec<Parameter>,
@@ -491,7 +503,7 @@ pub struct Signal {
     pub doc_deprecated: Option<String>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct Interface {
     pub name: String,
     pub c_type: String,
@@ -509,7 +521,7 @@ pub struct Interface {
     pub doc_deprecated: Option<String>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct Class {
     pub name: String,
     pub c_type: String,
@@ -531,12 +543,30 @@ pub struct Class {
     pub is_abstract: bool,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Custom {
     pub name: String,
     pub conversion_type: ConversionType,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypedKind {
+    /// `GVariant` converted with `ToVariant`/`FromVariant`
+    Variant,
+    /// `GValue` converted with `ToValue`/`FromValue`
+    Value,
+}
+
+/// `GVariant` or `GValue` holding a value of a configured type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Typed {
+    /// The `GLib.Variant` or `GObject.Value` record
+    pub typ: TypeId,
+    pub kind: TypedKind,
+    /// Variant type string like `a{sv}` or Rust type of the value like `i32`
+    pub type_string: String,
+}
+
 macro_rules! impl_lexical_ord {
     () => ();
     ($name:ident => $field:ident, $($more_name:ident => $more_field:ident,)*) => (
@@ -577,7 +607,7 @@ impl_lexical_ord!(
 );
 
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Type {
     Fundamental(Fundamental),
     Alias(Alias),
@@ -589,6 +619,7 @@ pub enum Type {
     Interface(Interface),
     Class(Class),
     Custom(Custom),
+    Typed(Typed),
     Array(TypeId),
     CArray(TypeId),
     FixedArray(TypeId, u16, Option<String>),
@@ -611,6 +642,7 @@ impl fmt::Display for Type {
             Type::Interface(_) => "Interface",
             Type::Class(_) => "Class",
             Type::Custom(_) => "Custom",
+            Type::Typed(_) => "Typed",
             Type::Array(_) => "Array",
             Type::CArray(_) => "CArray",
             Type::FixedArray(_, _, _) => "FixedArray",
@@ -637,6 +669,7 @@ impl Type {
             Array(type_id) => format!("Array {:?}", type_id),
             Class(ref class) => class.name.clone(),
             Custom(ref custom) => custom.name.clone(),
+            Typed(ref typed) => format!("{:?}<{}>", typed.kind, typed.type_string),
             CArray(type_id) => format!("CArray {:?}", type_id),
             FixedArray(type_id, size, _) => format!("FixedArray {:?}; {}", type_id, size),
             PtrArray(type_id) => format!("PtrArray {:?}", type_id),
@@ -662,6 +695,7 @@ impl Type {
             Array(_) => None,
             Class(ref class) => class.deprecated_version,
             Custom(_) => None,
+            Typed(_) => None,
             CArray(_) => None,
             FixedArray(..) => None,
             PtrArray(_) => None,
@@ -750,6 +784,19 @@ impl Type {
         .map(|(name, typ)| library.add_type(INTERNAL_NAMESPACE, &name, typ))
     }
 
+    pub fn typed(library: &mut Library, typ: TypeId, kind: TypedKind, type_string: &str) -> TypeId {
+        let name = format!("{:?}<#{:?}, {}>", kind, typ, type_string);
+        library.add_type(
+            INTERNAL_NAMESPACE,
+            &name,
+            Type::Typed(Typed {
+                typ,
+                kind,
+                type_string: type_string.to_owned(),
+            }),
+        )
+    }
+
     pub fn function(library: &mut Library, func: Function) -> TypeId {
         let mut param_tids: Vec<TypeId> = func.parameters.iter().map(|p| p.typ).collect();
         param_tids.push(func.ret.typ);
@@ -781,6 +828,18 @@ impl Type {
         }
     }
 
+    pub fn functions_mut(&mut self) -> Option<&mut Vec<Function>> {
+        match *self {
+            Type::Enumeration(ref mut e) => Some(&mut e.functions),
+            Type::Bitfield(ref mut b) => Some(&mut b.functions),
+            Type::Record(ref mut r) => Some(&mut r.functions),
+            Type::Union(ref mut u) => Some(&mut u.functions),
+            Type::Interface(ref mut i) => Some(&mut i.functions),
+            Type::Class(ref mut c) => Some(&mut c.functions),
+            _ => None,
+        }
+    }
+
     pub fn is_fundamental(&self) -> bool {
         matches!(*self, Type::Fundamental(_))
     }
@@ -873,6 +932,7 @@ impl_maybe_ref!(
     Fundamental,
     Interface,
     Record,
+    Typed,
     Union,
 );
 
@@ -892,7 +952,7 @@ impl<U> MaybeRefAs for U {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Namespace {
     pub name: String,
     pub types: Vec<Option<Type>>,
@@ -909,6 +969,16 @@ pub struct Namespace {
     pub symbol_prefixes: Vec<String>,
     /// C headers, relative to include directories provided by pkg-config --cflags.
     pub c_includes: Vec<String>,
+    /// Version of the GIR file the namespace was read from
+    pub version: Option<String>,
+    /// GIR file that first included this namespace
+    pub included_by: Option<PathBuf>,
+    /// GIR file the namespace was read from
+    pub gir_file: Option<PathBuf>,
+    /// Version picked as the latest available for an unversioned include
+    pub resolved_version: Option<String>,
+    /// Namespaces included by the GIR file
+    pub includes: Vec<String>,
 }
 
 impl Namespace {
@@ -964,7 +1034,7 @@ pub const INTERNAL_NAMESPACE_NAME: &str = "*";
 pub const INTERNAL_NAMESPACE: u16 = 0;
 pub const MAIN_NAMESPACE: u16 = 1;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Library {
     pub namespaces: Vec<Namespace>,
     pub index: HashMap<String, u16>,
@@ -0,0 +1,700 @@
+//! On-disk cache of parsed GIR libraries.
+//!
+//! A cache entry holds the whole library read for a main namespace, with the
+//! path, modification time and content hash of every GIR file it was read from,
+//! and the version picked for every unversioned include. The entry is reused as
+//! long as the same files are found in the GIR directories, none of them changed
+//! and no newer version of an unversioned include was added.
+
+use crate::{
+    gir_version::VERSION,
+    library::*,
+    parser::{find_file, find_latest_version},
+    version::Version,
+};
+use log::{info, warn};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Bump when the serialized layout of the library changes.
+const FORMAT_VERSION: u32 = 5;
+const MAGIC: &[u8] = b"GIRCACHE";
+
+impl Library {
+    /// Reads the main library `lib` with its includes from `dirs`, going through
+    /// the cache in `cache_dir` if any.
+    pub fn read_cached(
+        main_namespace_name: &str,
+        lib: &str,
+        dirs: &[PathBuf],
+        cache_dir: Option<&Path>,
+    ) -> Result<Library, String> {
+        if let Some(library) = Library::load_cached(lib, dirs, cache_dir) {
+            return Ok(library);
+        }
+
+        let mut library = Library::new(main_namespace_name);
+        library.read_file(dirs, &mut vec![lib.to_owned()])?;
+        library.store_cached(lib, dirs, cache_dir);
+        Ok(library)
+    }
+
+    /// Loads the main library `lib` from the cache in `cache_dir` if it's still current.
+    pub fn load_cached(lib: &str, dirs: &[PathBuf], cache_dir: Option<&Path>) -> Option<Library> {
+        let cache_file = cache_file_name(cache_dir?, lib, dirs);
+        let library = load(&cache_file, dirs)?;
+        info!("Using cached {} from {}", lib, cache_file.display());
+        Some(library)
+    }
+
+    /// Stores the main library `lib` in the cache in `cache_dir` if any.
+    pub fn store_cached(&self, lib: &str, dirs: &[PathBuf], cache_dir: Option<&Path>) {
+        if let Some(cache_dir) = cache_dir {
+            let cache_file = cache_file_name(cache_dir, lib, dirs);
+            if let Err(e) = store(&cache_file, self) {
+                warn!("Failed to write GIR cache {}: {}", cache_file.display(), e);
+            }
+        }
+    }
+}
+
+/// The entry depends on the GIR directories as they decide which files are read.
+fn cache_file_name(cache_dir: &Path, lib: &str, dirs: &[PathBuf]) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    dirs.hash(&mut hasher);
+    cache_dir.join(format!("{}-{:016x}.bin", lib, hasher.finish()))
+}
+
+/// Identifies the content of a GIR file.
+#[derive(Debug, PartialEq)]
+struct Fingerprint {
+    path: PathBuf,
+    mtime: (u64, u32),
+    hash: u64,
+}
+
+impl Fingerprint {
+    fn of(path: &Path) -> Option<Fingerprint> {
+        let content = fs::read(path).ok()?;
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Some(Fingerprint {
+            path: path.to_owned(),
+            mtime: mtime(path)?,
+            hash: hasher.finish(),
+        })
+    }
+
+    /// Only hashes the file again if its modification time changed.
+    fn is_current(&self) -> bool {
+        if mtime(&self.path) == Some(self.mtime) {
+            return true;
+        }
+        matches!(Fingerprint::of(&self.path), Some(f) if f.hash == self.hash)
+    }
+}
+
+fn mtime(path: &Path) -> Option<(u64, u32)> {
+    let time = fs::metadata(path).ok()?.modified().ok()?;
+    let since_epoch = time.duration_since(UNIX_EPOCH).ok()?;
+    Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+}
+
+fn load(cache_file: &Path, dirs: &[PathBuf]) -> Option<Library> {
+    let data = fs::read(cache_file).ok()?;
+    let mut r = Reader::new(&data)?;
+
+    let files: Vec<(String, Fingerprint)> = Cache::read(&mut r)?;
+    let includes: Vec<(String, String)> = Cache::read(&mut r)?;
+    // A file added in an earlier directory would now be picked instead
+    let files_current = files.iter().all(|(lib, fingerprint)| {
+        find_file(dirs, lib).ok().as_ref() == Some(&fingerprint.path) && fingerprint.is_current()
+    });
+    // As would a newer version of an unversioned include
+    let includes_current = includes
+        .iter()
+        .all(|(name, version)| find_latest_version(dirs, name).ok().as_ref() == Some(version));
+    if !files_current || !includes_current {
+        info!("GIR cache {} is outdated", cache_file.display());
+        return None;
+    }
+
+    let library = Cache::read(&mut r);
+    if library.is_none() {
+        warn!("GIR cache {} is corrupted", cache_file.display());
+    }
+    library
+}
+
+fn store(cache_file: &Path, library: &Library) -> Result<(), String> {
+    let mut files = Vec::new();
+    let mut includes = Vec::new();
+    for ns in &library.namespaces {
+        if let Some(path) = &ns.gir_file {
+            // The name the file was looked up by, whatever version it declares
+            let lib = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .ok_or_else(|| format!("Bad GIR file name {}", path.display()))?;
+            let fingerprint =
+                Fingerprint::of(path).ok_or_else(|| format!("Can't read {}", path.display()))?;
+            files.push((lib.to_owned(), fingerprint));
+        }
+        if let Some(version) = &ns.resolved_version {
+            includes.push((ns.name.clone(), version.clone()));
+        }
+    }
+
+    let mut w = Vec::new();
+    w.extend_from_slice(MAGIC);
+    FORMAT_VERSION.write(&mut w);
+    VERSION.to_owned().write(&mut w);
+    files.write(&mut w);
+    includes.write(&mut w);
+    library.write(&mut w);
+
+    if let Some(dir) = cache_file.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    // Written aside then renamed so that concurrent runs never read a partial file
+    let tmp_file = cache_file.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp_file, &w).map_err(|e| e.to_string())?;
+    fs::rename(&tmp_file, cache_file).map_err(|e| e.to_string())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Checks the header, entries from other formats or gir versions are ignored.
+    fn new(data: &'a [u8]) -> Option<Reader<'a>> {
+        if !data.starts_with(MAGIC) {
+            return None;
+        }
+        let mut r = Reader {
+            data: &data[MAGIC.len()..],
+        };
+        if u32::read(&mut r)? != FORMAT_VERSION || String::read(&mut r)? != VERSION {
+            return None;
+        }
+        Some(r)
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+}
+
+/// Compact binary serialization of the library model.
+trait Cache: Sized {
+    fn write(&self, w: &mut Vec<u8>);
+    fn read(r: &mut Reader<'_>) -> Option<Self>;
+}
+
+macro_rules! cache_int {
+    ($($t:ty),*) => {$(
+        impl Cache for $t {
+            fn write(&self, w: &mut Vec<u8>) {
+                w.extend_from_slice(&self.to_le_bytes());
+            }
+            fn read(r: &mut Reader<'_>) -> Option<Self> {
+                const SIZE: usize = std::mem::size_of::<$t>();
+                let mut bytes = [0; SIZE];
+                bytes.copy_from_slice(r.take(SIZE)?);
+                Some(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+cache_int!(u8, u16, u32, u64);
+
+impl Cache for usize {
+    fn write(&self, w: &mut Vec<u8>) {
+        (*self as u64).write(w)
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        Some(u64::read(r)? as usize)
+    }
+}
+
+impl Cache for bool {
+    fn write(&self, w: &mut Vec<u8>) {
+        (*self as u8).write(w)
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        match u8::read(r)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Cache for String {
+    fn write(&self, w: &mut Vec<u8>) {
+        self.len().write(w);
+        w.extend_from_slice(self.as_bytes());
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        let len = usize::read(r)?;
+        String::from_utf8(r.take(len)?.to_vec()).ok()
+    }
+}
+
+impl Cache for PathBuf {
+    fn write(&self, w: &mut Vec<u8>) {
+        self.to_string_lossy().into_owned().write(w)
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        String::read(r).map(PathBuf::from)
+    }
+}
+
+impl<T: Cache> Cache for Option<T> {
+    fn write(&self, w: &mut Vec<u8>) {
+        match self {
+            None => false.write(w),
+            Some(value) => {
+                true.write(w);
+                value.write(w);
+            }
+        }
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        if bool::read(r)? {
+            T::read(r).map(Some)
+        } else {
+            Some(None)
+        }
+    }
+}
+
+impl<T: Cache> Cache for Vec<T> {
+    fn write(&self, w: &mut Vec<u8>) {
+        self.len().write(w);
+        for value in self {
+            value.write(w);
+        }
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        let len = usize::read(r)?;
+        // Don't trust the length for the allocation, the data may be corrupted
+        let mut values = Vec::with_capacity(len.min(r.data.len()));
+        for _ in 0..len {
+            values.push(T::read(r)?);
+        }
+        Some(values)
+    }
+}
+
+impl<A: Cache, B: Cache> Cache for (A, B) {
+    fn write(&self, w: &mut Vec<u8>) {
+        self.0.write(w);
+        self.1.write(w);
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        Some((A::read(r)?, B::read(r)?))
+    }
+}
+
+impl<A: Cache, B: Cache, C: Cache> Cache for (A, B, C) {
+    fn write(&self, w: &mut Vec<u8>) {
+        self.0.write(w);
+        self.1.write(w);
+        self.2.write(w);
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        Some((A::read(r)?, B::read(r)?, C::read(r)?))
+    }
+}
+
+impl<K: Cache + Ord + Clone, V: Cache + Clone> Cache for BTreeMap<K, V> {
+    fn write(&self, w: &mut Vec<u8>) {
+        let entries: Vec<(K, V)> = self.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        entries.write(w)
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        Vec::<(K, V)>::read(r).map(|entries| entries.into_iter().collect())
+    }
+}
+
+impl<K: Cache + Eq + Hash + Clone, V: Cache + Clone> Cache for HashMap<K, V> {
+    fn write(&self, w: &mut Vec<u8>) {
+        let entries: Vec<(K, V)> = self.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        entries.write(w)
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        Vec::<(K, V)>::read(r).map(|entries| entries.into_iter().collect())
+    }
+}
+
+impl<T: Cache + Ord + Clone> Cache for BTreeSet<T> {
+    fn write(&self, w: &mut Vec<u8>) {
+        self.iter().cloned().collect::<Vec<_>>().write(w)
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        Vec::<T>::read(r).map(|values| values.into_iter().collect())
+    }
+}
+
+/// Implements `Cache` for structs, field by field.
+macro_rules! cache_struct {
+    ($($name:ident { $($field:ident),* $(,)? })*) => {$(
+        impl Cache for $name {
+            fn write(&self, w: &mut Vec<u8>) {
+                $(self.$field.write(w);)*
+            }
+            fn read(r: &mut Reader<'_>) -> Option<Self> {
+                Some($name {
+                    $($field: Cache::read(r)?,)*
+                })
+            }
+        }
+    )*};
+}
+
+/// Implements `Cache` for fieldless enums as their variant index.
+macro_rules! cache_enum {
+    ($($name:ident { $($variant:ident),* $(,)? })*) => {$(
+        impl Cache for $name {
+            fn write(&self, w: &mut Vec<u8>) {
+                // Fails to compile when a variant is missing
+                match *self {
+                    $($name::$variant)|* => (),
+                }
+                let variants = [$($name::$variant),*];
+                (variants.iter().position(|v| v == self).unwrap() as u8).write(w)
+            }
+            fn read(r: &mut Reader<'_>) -> Option<Self> {
+                let variants = [$($name::$variant),*];
+                variants.get(u8::read(r)? as usize).cloned()
+            }
+        }
+    )*};
+}
+
+cache_enum! {
+    Transfer { None, Container, Full }
+    ParameterDirection { In, Out, InOut, Return }
+    ParameterScope { None, Call, Async, Notified }
+    FunctionKind { Constructor, Function, Method, Global }
+    Concurrency { None, SendUnique, Send, SendSync }
+    TypedKind { Variant, Value }
+    Fundamental {
+        None, Boolean, Int8, UInt8, Int16, UInt16, Int32, UInt32, Int64, UInt64, Char,
+        UChar, Short, UShort, Int, UInt, Long, ULong, Size, SSize, Float, Double, Pointer,
+        VarArgs, UniChar, Utf8, Filename, Type, IntPtr, UIntPtr, OsString, Unsupported,
+    }
+}
+
+cache_struct! {
+    Fingerprint { path, mtime, hash }
+    Library { namespaces, index }
+    Namespace {
+        name, types, index, glib_name_index, constants, functions, package_name, versions,
+        doc, doc_deprecated, shared_library, identifier_prefixes, symbol_prefixes, c_includes,
+        version, included_by, gir_file, resolved_version, includes,
+    }
+    TypeId { ns_id, id }
+    Alias { name, c_identifier, typ, target_c_type, doc, doc_deprecated }
+    Constant {
+        name, c_identifier, typ, c_type, value, version, deprecated_version, doc,
+        doc_deprecated,
+    }
+    Member { name, c_identifier, value, doc }
+    Enumeration {
+        name, c_type, symbol_prefix, members, functions, version, deprecated_version, doc,
+        doc_deprecated, error_domain, glib_get_type,
+    }
+    Bitfield {
+        name, c_type, symbol_prefix, members, functions, version, deprecated_version, doc,
+        doc_deprecated, glib_get_type,
+    }
+    Record {
+        name, c_type, symbol_prefix, glib_get_type, gtype_struct_for, fields, functions,
+        version, deprecated_version, doc, doc_deprecated, disguised, standalone_boxed, direct,
+    }
+    Field { name, typ, c_type, private, bits, array_length, doc }
+    Union { name, c_type, symbol_prefix, glib_get_type, fields, functions, doc }
+    Property {
+        name, readable, writable, construct, construct_only, typ, c_type, transfer, version,
+        deprecated_version, doc, doc_deprecated,
+    }
+    Parameter {
+        name, typ, c_type, instance_parameter, direction, transfer, caller_allocates, nullable,
+        allow_none, array_length, is_error, doc, scope, closure, destroy,
+    }
+    Function {
+        name, c_identifier, kind, parameters, ret, throws, version, deprecated_version, doc,
+        doc_deprecated, finish_func, sync_func, async_func,
+    }
+    Signal {
+        name, parameters, ret, is_action, version, deprecated_version, doc, doc_deprecated,
+    }
+    Interface {
+        name, c_type, symbol_prefix, type_struct, c_class_type, glib_get_type, functions,
+        signals, properties, prerequisites, version, deprecated_version, doc, doc_deprecated,
+    }
+    Class {
+        name, c_type, symbol_prefix, type_struct, c_class_type, glib_get_type, fields,
+        functions, signals, properties, parent, implements, final_type, version,
+        deprecated_version, doc, doc_deprecated, is_abstract,
+    }
+    Typed { typ, kind, type_string }
+}
+
+impl Cache for Nullable {
+    fn write(&self, w: &mut Vec<u8>) {
+        self.0.write(w)
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        bool::read(r).map(Nullable)
+    }
+}
+
+impl Cache for Version {
+    fn write(&self, w: &mut Vec<u8>) {
+        match *self {
+            Version::Full(major, minor, patch) => {
+                0u8.write(w);
+                (major, minor, patch).write(w);
+            }
+            Version::Short(major) => {
+                1u8.write(w);
+                major.write(w);
+            }
+        }
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        match u8::read(r)? {
+            0 => {
+                let (major, minor, patch) = Cache::read(r)?;
+                Some(Version::Full(major, minor, patch))
+            }
+            1 => u16::read(r).map(Version::Short),
+            _ => None,
+        }
+    }
+}
+
+impl Cache for ErrorDomain {
+    fn write(&self, w: &mut Vec<u8>) {
+        match self {
+            ErrorDomain::Quark(quark) => {
+                0u8.write(w);
+                quark.write(w);
+            }
+            ErrorDomain::Function(function) => {
+                1u8.write(w);
+                function.write(w);
+            }
+        }
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        match u8::read(r)? {
+            0 => String::read(r).map(ErrorDomain::Quark),
+            1 => String::read(r).map(ErrorDomain::Function),
+            _ => None,
+        }
+    }
+}
+
+impl Cache for Type {
+    fn write(&self, w: &mut Vec<u8>) {
+        match self {
+            Type::Fundamental(fundamental) => {
+                0u8.write(w);
+                fundamental.write(w);
+            }
+            Type::Alias(alias) => {
+                1u8.write(w);
+                alias.write(w);
+            }
+            Type::Enumeration(enumeration) => {
+                2u8.write(w);
+                enumeration.write(w);
+            }
+            Type::Bitfield(bitfield) => {
+                3u8.write(w);
+                bitfield.write(w);
+            }
+            Type::Record(record) => {
+                4u8.write(w);
+                record.write(w);
+            }
+            Type::Union(union) => {
+                5u8.write(w);
+                union.write(w);
+            }
+            Type::Function(function) => {
+                6u8.write(w);
+                function.write(w);
+            }
+            Type::Interface(interface) => {
+                7u8.write(w);
+                interface.write(w);
+            }
+            Type::Class(class) => {
+                8u8.write(w);
+                class.write(w);
+            }
+            Type::Typed(typed) => {
+                9u8.write(w);
+                typed.write(w);
+            }
+            Type::Array(tid) => {
+                10u8.write(w);
+                tid.write(w);
+            }
+            Type::CArray(tid) => {
+                11u8.write(w);
+                tid.write(w);
+            }
+            Type::FixedArray(tid, size, c_type) => {
+                12u8.write(w);
+                tid.write(w);
+                size.write(w);
+                c_type.write(w);
+            }
+            Type::PtrArray(tid) => {
+                13u8.write(w);
+                tid.write(w);
+            }
+            Type::HashTable(key, value) => {
+                14u8.write(w);
+                key.write(w);
+                value.write(w);
+            }
+            Type::List(tid) => {
+                15u8.write(w);
+                tid.write(w);
+            }
+            Type::SList(tid) => {
+                16u8.write(w);
+                tid.write(w);
+            }
+            Type::Custom(_) => unreachable!("Custom types are only added after parsing"),
+        }
+    }
+    fn read(r: &mut Reader<'_>) -> Option<Self> {
+        Some(match u8::read(r)? {
+            0 => Type::Fundamental(Cache::read(r)?),
+            1 => Type::Alias(Cache::read(r)?),
+            2 => Type::Enumeration(Cache::read(r)?),
+            3 => Type::Bitfield(Cache::read(r)?),
+            4 => Type::Record(Cache::read(r)?),
+            5 => Type::Union(Cache::read(r)?),
+            6 => Type::Function(Cache::read(r)?),
+            7 => Type::Interface(Cache::read(r)?),
+            8 => Type::Class(Cache::read(r)?),
+            9 => Type::Typed(Cache::read(r)?),
+            10 => Type::Array(Cache::read(r)?),
+            11 => Type::CArray(Cache::read(r)?),
+            12 => Type::FixedArray(Cache::read(r)?, Cache::read(r)?, Cache::read(r)?),
+            13 => Type::PtrArray(Cache::read(r)?),
+            14 => Type::HashTable(Cache::read(r)?, Cache::read(r)?),
+            15 => Type::List(Cache::read(r)?),
+            16 => Type::SList(Cache::read(r)?),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Cache>(value: &T) -> Option<T> {
+        let mut w = Vec::new();
+        value.write(&mut w);
+        let mut r = Reader { data: &w };
+        let value = T::read(&mut r);
+        assert!(r.data.is_empty());
+        value
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let mut library = Library::new("Test");
+        let tid = library.find_type(0, "*.gint").unwrap();
+        library.add_type(
+            MAIN_NAMESPACE,
+            "Size",
+            Type::Alias(Alias {
+                name: "Size".into(),
+                c_identifier: "TestSize".into(),
+                typ: tid,
+                target_c_type: "gint".into(),
+                doc: Some("A size".into()),
+                doc_deprecated: None,
+            }),
+        );
+        library
+            .namespace_mut(MAIN_NAMESPACE)
+            .versions
+            .insert(Version::Full(1, 2, 0));
+
+        let cached = round_trip(&library).unwrap();
+        assert_eq!(cached.namespaces.len(), library.namespaces.len());
+        assert_eq!(cached.index, library.index);
+        let ns = cached.namespace(MAIN_NAMESPACE);
+        assert_eq!(ns.index, library.namespace(MAIN_NAMESPACE).index);
+        assert_eq!(ns.versions, library.namespace(MAIN_NAMESPACE).versions);
+        let size = cached.find_type(MAIN_NAMESPACE, "Size").unwrap();
+        assert_eq!(cached.type_(size), library.type_(size));
+    }
+
+    #[test]
+    fn cache_outdated_by_newer_unversioned_include() {
+        let dir = std::env::temp_dir().join(format!("gir-cache-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let gir = |name: &str, version: &str, include: &str| {
+            let content = format!(
+                r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0">
+  {}
+  <namespace name="{}" version="{}" c:identifier-prefixes="{}" c:symbol-prefixes="{}">
+  </namespace>
+</repository>
+"#,
+                include, name, version, name, name
+            );
+            fs::write(dir.join(format!("{}-{}.gir", name, version)), content).unwrap();
+        };
+        gir("Foo", "1.0", r#"<include name="Bar"/>"#);
+        gir("Bar", "1.0", "");
+
+        let dirs = vec![dir.clone()];
+        let cache_file = dir.join("Foo-1.0.bin");
+        let library = Library::read_cached("Foo", "Foo-1.0", &dirs, None).unwrap();
+        store(&cache_file, &library).unwrap();
+        assert!(load(&cache_file, &dirs).is_some());
+
+        gir("Bar", "2.0", "");
+        assert!(load(&cache_file, &dirs).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cache_rejects_truncated_data() {
+        let mut w = Vec::new();
+        vec!["some".to_owned(), "strings".to_owned()].write(&mut w);
+        w.pop();
+        assert_eq!(Vec::<String>::read(&mut Reader { data: &w }), None);
+        assert_eq!(bool::read(&mut Reader { data: &[2] }), None);
+        assert!(Reader::new(b"GIRCACHX").is_none());
+    }
+}
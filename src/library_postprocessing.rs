@@ -1,12 +1,12 @@
 use crate::{
-    analysis::types::IsIncomplete,
-    config::{gobjects::GObject, Config, WorkMode},
+    analysis::{types::IsIncomplete, variant_type},
+    config::{gobjects::GObject, matchable::Matchable, Config, WorkMode},
     library::*,
     nameutil,
     parser::is_empty_c_type,
-    traits::MaybeRefAs,
+    traits::{MaybeRefAs, ParameterMatchable},
 };
-use log::{error, info};
+use log::{error, info, warn};
 use std::collections::HashMap;
 
 impl Namespace {
@@ -26,6 +26,18 @@ impl Namespace {
 
 type DetectedCTypes = HashMap<TypeId, String>;
 
+/// Configured typed conversion of a `GVariant` or `GValue`:
+/// (type index of the owner or `None` for global functions, function index,
+/// parameter index or `None` for the return value, current type, kind, type string)
+type TypedOverride = (
+    Option<usize>,
+    usize,
+    Option<usize>,
+    TypeId,
+    TypedKind,
+    String,
+);
+
 impl Library {
     pub fn postprocessing(&mut self, config: &Config) {
         self.fix_gtype();
@@ -36,7 +48,11 @@ impl Library {
         self.fix_fields();
         self.make_unrepresentable_types_opaque();
         self.mark_final_types(config);
+        self.mark_direct_records(config);
         self.update_error_domain_functions(config);
+        self.detect_error_domain_functions(config);
+        self.fill_async_functions(config);
+        self.apply_typed_conversions(config);
     }
 
     fn fix_gtype(&mut self) {
@@ -518,6 +534,89 @@ impl Library {
         }
     }
 
+    fn mark_direct_records(&mut self, config: &Config) {
+        // Plain C structs without any memory management functions or GType are
+        // wrapped by value instead of being boxed, unless configured otherwise.
+        // This only depends on the record so that it's wrapped the same way by
+        // the crate of its namespace and the crates using it.
+        let mut direct_records: Vec<TypeId> = Vec::new();
+
+        for (ns_id, ns) in self.namespaces.iter().enumerate() {
+            for (id, type_) in ns.types.iter().enumerate() {
+                let type_ = type_.as_ref().unwrap(); //Always contains something
+
+                if let Type::Record(ref record) = *type_ {
+                    let tid = TypeId {
+                        ns_id: ns_id as u16,
+                        id: id as u32,
+                    };
+
+                    let full_name = tid.full_name(self);
+                    let is_direct = match config
+                        .objects
+                        .get(&*full_name)
+                        .and_then(|obj| obj.direct_record)
+                    {
+                        Some(direct_record) => direct_record,
+                        None => self.is_plain_record(record),
+                    };
+                    if is_direct {
+                        direct_records.push(tid);
+                    }
+                }
+            }
+        }
+
+        for tid in direct_records {
+            if let Type::Record(Record { ref mut direct, .. }) = *self.type_mut(tid) {
+                *direct = true;
+            } else {
+                unreachable!();
+            }
+        }
+    }
+
+    fn is_plain_record(&self, record: &Record) -> bool {
+        record.glib_get_type.is_none()
+            && record.gtype_struct_for.is_none()
+            && !record.standalone_boxed
+            && !record.disguised
+            && !record
+                .functions
+                .iter()
+                .any(|f| matches!(&f.name[..], "copy" | "free" | "destroy" | "ref" | "unref"))
+            && self.has_plain_fields(&record.fields)
+    }
+
+    fn has_plain_fields(&self, fields: &[Field]) -> bool {
+        !fields.is_empty()
+            && fields.iter().all(|field| {
+                field.bits.is_none()
+                    && matches!(field.c_type, Some(ref c) if !c.contains('*'))
+                    && self.is_plain_type(field.typ)
+            })
+    }
+
+    fn is_plain_type(&self, tid: TypeId) -> bool {
+        match *self.type_(tid) {
+            Type::Fundamental(fund) => !matches!(
+                fund,
+                Fundamental::None
+                    | Fundamental::Pointer
+                    | Fundamental::VarArgs
+                    | Fundamental::Utf8
+                    | Fundamental::Filename
+                    | Fundamental::OsString
+                    | Fundamental::Unsupported
+            ),
+            Type::Enumeration(..) | Type::Bitfield(..) => true,
+            Type::FixedArray(inner_tid, ..) => self.is_plain_type(inner_tid),
+            Type::Alias(ref alias) => self.is_plain_type(alias.typ),
+            Type::Record(ref record) => !record.disguised && self.has_plain_fields(&record.fields),
+            _ => false,
+        }
+    }
+
     fn update_error_domain_functions(&mut self, config: &Config) {
         // Find find all error domains that have corresponding functions
         let mut error_domains = vec![];
@@ -644,4 +743,637 @@ impl Library {
             }
         }
     }
+
+    fn detect_error_domain_functions(&mut self, config: &Config) {
+        // Finds the error domains of enumerations without `glib:error-domain` annotation
+        // from the configured quark functions or by matching `*_error_quark` functions
+        // to their names.
+        let ns_id = match self.find_namespace(&config.library_name) {
+            Some(ns_id) => ns_id,
+            None => return,
+        };
+        let ns = self.namespace(ns_id);
+        let has_function = |name: &String| {
+            ns.functions
+                .iter()
+                .any(|f| f.c_identifier.as_ref() == Some(name))
+        };
+
+        // Quark functions of annotated error domains, kept in `Sys` mode
+        let used_functions: Vec<&String> = ns
+            .types
+            .iter()
+            .filter_map(|type_| match *type_ {
+                Some(Type::Enumeration(Enumeration {
+                    error_domain: Some(ErrorDomain::Function(ref function_name)),
+                    ..
+                })) => Some(function_name),
+                _ => None,
+            })
+            .collect();
+
+        let mut error_domains: Vec<(usize, String)> = Vec::new();
+        for (id, type_) in ns.types.iter().enumerate() {
+            if let Some(Type::Enumeration(ref enum_)) = *type_ {
+                let full_name = format!("{}.{}", ns.name, enum_.name);
+                let function_name = match config
+                    .objects
+                    .get(&full_name)
+                    .and_then(|obj| obj.error_quark_function.as_ref())
+                {
+                    Some(function_name) => function_name,
+                    None => continue,
+                };
+                if enum_.error_domain.is_some() {
+                    warn!(
+                        "Ignoring error_quark_function of `{}` which already has an error domain",
+                        full_name
+                    );
+                } else if !has_function(function_name) {
+                    warn!(
+                        "Error quark function `{}` of `{}` not found",
+                        function_name, full_name
+                    );
+                } else {
+                    error_domains.push((id, function_name.clone()));
+                }
+            }
+        }
+
+        for func in &ns.functions {
+            let function_name = match func.c_identifier {
+                Some(ref c_identifier) if c_identifier.ends_with("_error_quark") => c_identifier,
+                _ => continue,
+            };
+            if error_domains.iter().any(|(_, f)| f == function_name)
+                || used_functions.contains(&function_name)
+            {
+                continue;
+            }
+            let domain = function_name.trim_end_matches("_quark").replace('_', "");
+            let enum_id = ns.types.iter().position(|type_| match *type_ {
+                Some(Type::Enumeration(ref enum_)) => {
+                    enum_.error_domain.is_none()
+                        && enum_
+                            .c_type
+                            .trim_end_matches("Enum")
+                            .eq_ignore_ascii_case(&domain)
+                }
+                _ => false,
+            });
+            match enum_id {
+                Some(id) if error_domains.iter().any(|&(i, _)| i == id) => (),
+                Some(id) => error_domains.push((id, function_name.clone())),
+                None => warn!(
+                    "Error quark function `{}` doesn't match any enumeration, \
+                     use error_quark_function to set it",
+                    function_name
+                ),
+            }
+        }
+
+        let ns = self.namespace_mut(ns_id);
+        for (id, function_name) in error_domains {
+            if config.work_mode != WorkMode::Sys {
+                ns.functions
+                    .retain(|f| f.c_identifier.as_ref() != Some(&function_name));
+            }
+            if let Some(Type::Enumeration(ref mut enum_)) = ns.types[id] {
+                enum_.error_domain = Some(ErrorDomain::Function(function_name));
+            }
+        }
+    }
+
+    fn fill_async_functions(&mut self, config: &Config) {
+        // Applies the async/finish pairs given in the config and makes sure that the finish
+        // functions know the async function they belong to.
+        let ns_id = match self.find_namespace(&config.library_name) {
+            Some(ns_id) => ns_id,
+            None => return,
+        };
+        let ns = self.namespace_mut(ns_id);
+        let global_functions_name = format!("{}.*", ns.name);
+        fill_async_function_pairs(
+            config.objects.get(&global_functions_name),
+            &mut ns.functions,
+        );
+        for type_ in ns.types.iter_mut().flatten() {
+            let full_name = format!("{}.{}", ns.name, type_.get_name());
+            match *type_ {
+                Type::Enumeration(Enumeration {
+                    ref mut functions, ..
+                })
+                | Type::Bitfield(Bitfield {
+                    ref mut functions, ..
+                })
+                | Type::Class(Class {
+                    ref mut functions, ..
+                })
+                | Type::Record(Record {
+                    ref mut functions, ..
+                })
+                | Type::Interface(Interface {
+                    ref mut functions, ..
+                }) => fill_async_function_pairs(config.objects.get(&full_name), functions),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Library {
+    fn apply_typed_conversions(&mut self, config: &Config) {
+        // Replaces the `GVariant` and `GValue` parameters and return values configured
+        // with `variant_type` or `value_type` by typed versions of them.
+        if config.work_mode != WorkMode::Normal {
+            return;
+        }
+        let ns_id = match self.find_namespace(&config.library_name) {
+            Some(ns_id) => ns_id,
+            None => return,
+        };
+
+        let mut overrides = Vec::new();
+        let ns = self.namespace(ns_id);
+        let global_functions_name = format!("{}.*", ns.name);
+        self.collect_typed_overrides(
+            config.objects.get(&global_functions_name),
+            None,
+            &ns.functions,
+            &mut overrides,
+        );
+        for (id, type_) in ns.types.iter().enumerate() {
+            if let Some(ref type_) = *type_ {
+                let full_name = format!("{}.{}", ns.name, type_.get_name());
+                self.collect_typed_overrides(
+                    config.objects.get(&full_name),
+                    Some(id),
+                    type_.functions(),
+                    &mut overrides,
+                );
+            }
+        }
+
+        for (owner, func_index, par_index, typ, kind, type_string) in overrides {
+            let typed_tid = Type::typed(self, typ, kind, &type_string);
+            let ns = self.namespace_mut(ns_id);
+            let functions = match owner {
+                Some(id) => ns.types[id].as_mut().and_then(Type::functions_mut).unwrap(),
+                None => &mut ns.functions,
+            };
+            let func = &mut functions[func_index];
+            match par_index {
+                Some(par_index) => func.parameters[par_index].typ = typed_tid,
+                None => func.ret.typ = typed_tid,
+            }
+        }
+    }
+
+    fn collect_typed_overrides(
+        &self,
+        obj: Option<&GObject>,
+        owner: Option<usize>,
+        functions: &[Function],
+        overrides: &mut Vec<TypedOverride>,
+    ) {
+        let obj = match obj {
+            Some(obj) => obj,
+            None => return,
+        };
+        for (func_index, func) in functions.iter().enumerate() {
+            let configured_functions = obj.functions.matched(&func.name);
+            if configured_functions.is_empty() {
+                continue;
+            }
+            for (par_index, par) in func.parameters.iter().enumerate() {
+                let configured_parameters =
+                    configured_functions.matched_parameters(&nameutil::mangle_keywords(&*par.name));
+                let typed = self.typed_override(
+                    &obj.name,
+                    &func.name,
+                    par,
+                    configured_parameters
+                        .iter()
+                        .find_map(|p| p.variant_type.as_ref()),
+                    configured_parameters
+                        .iter()
+                        .find_map(|p| p.value_type.as_ref()),
+                );
+                if let Some((kind, type_string)) = typed {
+                    overrides.push((
+                        owner,
+                        func_index,
+                        Some(par_index),
+                        par.typ,
+                        kind,
+                        type_string,
+                    ));
+                }
+            }
+            let typed = self.typed_override(
+                &obj.name,
+                &func.name,
+                &func.ret,
+                configured_functions
+                    .iter()
+                    .find_map(|f| f.ret.variant_type.as_ref()),
+                configured_functions
+                    .iter()
+                    .find_map(|f| f.ret.value_type.as_ref()),
+            );
+            if let Some((kind, type_string)) = typed {
+                overrides.push((owner, func_index, None, func.ret.typ, kind, type_string));
+            }
+        }
+    }
+
+    fn typed_override(
+        &self,
+        obj_name: &str,
+        func_name: &str,
+        par: &Parameter,
+        variant_type: Option<&String>,
+        value_type: Option<&String>,
+    ) -> Option<(TypedKind, String)> {
+        let (kind, type_string) = match (variant_type, value_type) {
+            (Some(variant_type), _) => (TypedKind::Variant, variant_type),
+            (None, Some(value_type)) => (TypedKind::Value, value_type),
+            (None, None) => return None,
+        };
+        let par_name = if par.direction == ParameterDirection::Return {
+            "return value"
+        } else {
+            &par.name
+        };
+
+        let expected_c_type = match kind {
+            TypedKind::Variant => "GVariant",
+            TypedKind::Value => "GValue",
+        };
+        if !matches!(*self.type_(par.typ), Type::Record(ref r) if r.c_type == expected_c_type) {
+            error!(
+                "Typed conversion configured for `{}` of `{}` in `{}` which is not a `{}`",
+                par_name, func_name, obj_name, expected_c_type
+            );
+            return None;
+        }
+        if par.direction != ParameterDirection::In && par.direction != ParameterDirection::Return {
+            error!(
+                "Typed conversion configured for `{}` of `{}` in `{}` which is neither an input \
+                 parameter nor the return value",
+                par_name, func_name, obj_name
+            );
+            return None;
+        }
+        let checked = match kind {
+            TypedKind::Variant => variant_type::rust_type(type_string, "Variant").map(|_| ()),
+            TypedKind::Value => variant_type::check_value_type(type_string),
+        };
+        if let Err(e) = checked {
+            warn!(
+                "{} for `{}` of `{}` in `{}`, keeping the `{}`",
+                e, par_name, func_name, obj_name, expected_c_type
+            );
+            return None;
+        }
+
+        Some((kind, type_string.clone()))
+    }
+}
+
+fn fill_async_function_pairs(obj: Option<&GObject>, functions: &mut [Function]) {
+    if let Some(obj) = obj {
+        for func in functions.iter_mut() {
+            if let Some(finish_func) = obj
+                .functions
+                .matched(&func.name)
+                .iter()
+                .find_map(|f| f.finish_func.as_ref())
+            {
+                func.finish_func = Some(finish_func.clone());
+            }
+        }
+    }
+
+    for i in 0..functions.len() {
+        let func = &functions[i];
+        let sync_func = match func.sync_func {
+            Some(ref sync_func) => sync_func,
+            None => continue,
+        };
+        // The sync function can't be the async function itself, its finish function nor
+        // another async function, and like a finish function it may link back to its async
+        // function (`glib:async-func`)
+        let valid = functions.iter().any(|f| {
+            f.name == *sync_func
+                && f.name != func.name
+                && Some(&f.name) != func.finish_func.as_ref()
+                && f.finish_func.is_none()
+                && f.async_func.as_ref().map_or(true, |a| *a == func.name)
+        });
+        if !valid {
+            warn!(
+                "Sync function `{}` of async function `{}` not found or conflicting, ignoring it",
+                sync_func, func.name
+            );
+            functions[i].sync_func = None;
+            continue;
+        }
+        // Async functions not named after their sync function (like `foo_begin` for `foo`)
+        // are finished by the function named after the sync one
+        if func.finish_func.is_none() {
+            let finish_func = format!("{}_finish", sync_func.trim_end_matches("_sync"));
+            if functions.iter().any(|f| f.name == finish_func) {
+                functions[i].finish_func = Some(finish_func);
+            }
+        }
+    }
+
+    let pairs = functions
+        .iter()
+        .filter_map(|func| {
+            func.finish_func
+                .as_ref()
+                .map(|finish_func| (func.name.clone(), finish_func.clone()))
+        })
+        .collect::<Vec<_>>();
+    // Finish functions living on another type or in the global namespace are resolved (and
+    // reported if missing) by the analysis.
+    for (async_func, finish_func) in pairs {
+        if let Some(func) = functions.iter_mut().find(|func| func.name == finish_func) {
+            if func.async_func.is_none() {
+                func.async_func = Some(async_func);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn functions(content: &str) -> Vec<Function> {
+        let gir = format!(
+            r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <namespace name="Foo" version="1.0" c:identifier-prefixes="Foo" c:symbol-prefixes="foo">
+    {}
+  </namespace>
+</repository>
+"#,
+            content
+        );
+        let mut library = Library::new("Foo");
+        library.read_str(&gir).unwrap();
+        let mut functions = library.namespace(MAIN_NAMESPACE).functions.clone();
+        fill_async_function_pairs(None, &mut functions);
+        functions
+    }
+
+    fn function(name: &str, attrs: &str) -> String {
+        format!(
+            r#"<function name="{}" c:identifier="foo_{}" {}>
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+    </function>"#,
+            name, name, attrs
+        )
+    }
+
+    fn bar_gir(point: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0">
+  <namespace name="Bar" version="1.0" c:identifier-prefixes="Bar" c:symbol-prefixes="bar">
+    <record name="Point" c:type="BarPoint">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+      {}
+    </record>
+    <record name="Handle" c:type="BarHandle">
+      <field name="data" writable="1"><type name="gpointer" c:type="gpointer"/></field>
+    </record>
+  </namespace>
+</repository>
+"#,
+            point
+        )
+    }
+
+    const FOO_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0">
+  <include name="Bar" version="1.0"/>
+  <namespace name="Foo" version="1.0" c:identifier-prefixes="Foo" c:symbol-prefixes="foo">
+  </namespace>
+</repository>
+"#;
+
+    /// Returns if `Bar.Point` and `Bar.Handle` are direct records, with `Bar` read on
+    /// its own and as an include of `Foo`.
+    fn direct_records(point: &str, options: &str) -> Vec<(bool, bool)> {
+        let bar = bar_gir(point);
+        ["Bar", "Foo"]
+            .iter()
+            .map(|&main| {
+                let config = Config::from_toml_str(&format!(
+                    "[options]\nlibrary = \"{}\"\nversion = \"1.0\"\n\
+                     target_path = \".\"\nwork_mode = \"normal\"\n{}",
+                    main, options
+                ))
+                .unwrap();
+                let mut library = Library::new(main);
+                library.read_str(&bar).unwrap();
+                if main == "Foo" {
+                    library.read_str(FOO_GIR).unwrap();
+                }
+                library.postprocessing(&config);
+                let is_direct = |name| match *library.type_(library.find_type(0, name).unwrap()) {
+                    Type::Record(ref record) => record.direct,
+                    _ => unreachable!(),
+                };
+                (is_direct("Bar.Point"), is_direct("Bar.Handle"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn direct_records_in_every_namespace() {
+        assert_eq!(direct_records("", ""), [(true, false), (true, false)]);
+
+        // Records with memory management functions are boxed
+        let copy = r#"<method name="copy" c:identifier="bar_point_copy">
+        <return-value transfer-ownership="full"><type name="Point" c:type="BarPoint*"/></return-value>
+        <parameters>
+          <instance-parameter name="point" transfer-ownership="none">
+            <type name="Point" c:type="BarPoint*"/>
+          </instance-parameter>
+        </parameters>
+      </method>"#;
+        assert_eq!(direct_records(copy, ""), [(false, false), (false, false)]);
+
+        let config = "[[object]]\nname = \"Bar.Point\"\nstatus = \"generate\"\n\
+                      direct_record = false\n\
+                      [[object]]\nname = \"Bar.Handle\"\nstatus = \"generate\"\n\
+                      direct_record = true\n";
+        assert_eq!(direct_records("", config), [(false, true), (false, true)]);
+    }
+
+    const ERRORS_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <namespace name="Foo" version="1.0" c:identifier-prefixes="Foo" c:symbol-prefixes="foo">
+    <enumeration name="ParseError" c:type="FooParseError">
+      <member name="failed" value="0" c:identifier="FOO_PARSE_ERROR_FAILED"/>
+    </enumeration>
+    <enumeration name="IOErrorEnum" c:type="FooIOErrorEnum">
+      <member name="failed" value="0" c:identifier="FOO_IO_ERROR_FAILED"/>
+    </enumeration>
+    <enumeration name="Status" c:type="FooStatus">
+      <member name="failed" value="0" c:identifier="FOO_STATUS_FAILED"/>
+    </enumeration>
+    <enumeration name="KeyError" c:type="FooKeyError" glib:error-domain="foo-key-error-quark">
+      <member name="failed" value="0" c:identifier="FOO_KEY_ERROR_FAILED"/>
+    </enumeration>
+    <function name="parse_error_quark" c:identifier="foo_parse_error_quark">
+      <return-value transfer-ownership="none"><type name="guint32" c:type="GQuark"/></return-value>
+    </function>
+    <function name="io_error_quark" c:identifier="foo_io_error_quark">
+      <return-value transfer-ownership="none"><type name="guint32" c:type="GQuark"/></return-value>
+    </function>
+    <function name="status_domain" c:identifier="foo_status_domain">
+      <return-value transfer-ownership="none"><type name="guint32" c:type="GQuark"/></return-value>
+    </function>
+    <function name="key_error_quark" c:identifier="foo_key_error_quark">
+      <return-value transfer-ownership="none"><type name="guint32" c:type="GQuark"/></return-value>
+    </function>
+    <function name="other_error_quark" c:identifier="foo_other_error_quark">
+      <return-value transfer-ownership="none"><type name="guint32" c:type="GQuark"/></return-value>
+    </function>
+  </namespace>
+</repository>
+"#;
+
+    /// Returns the quark functions found for the enumerations of `ERRORS_GIR` and the
+    /// global functions left.
+    fn error_domains(work_mode: &str, objects: &str) -> (Vec<(String, String)>, Vec<String>) {
+        let config = Config::from_toml_str(&format!(
+            "[options]\nlibrary = \"Foo\"\nversion = \"1.0\"\n\
+             target_path = \".\"\nwork_mode = \"{}\"\n{}",
+            work_mode, objects
+        ))
+        .unwrap();
+        let mut library = Library::new("Foo");
+        library.read_str(ERRORS_GIR).unwrap();
+        library.update_error_domain_functions(&config);
+        library.detect_error_domain_functions(&config);
+
+        let ns = library.namespace(MAIN_NAMESPACE);
+        let domains = ns
+            .types
+            .iter()
+            .filter_map(|type_| match *type_ {
+                Some(Type::Enumeration(Enumeration {
+                    ref name,
+                    error_domain: Some(ErrorDomain::Function(ref function)),
+                    ..
+                })) => Some((name.clone(), function.clone())),
+                _ => None,
+            })
+            .collect();
+        let functions = ns
+            .functions
+            .iter()
+            .map(|f| f.c_identifier.clone().unwrap())
+            .collect();
+        (domains, functions)
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|&(a, b)| (a.to_owned(), b.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn error_domain_from_function_name() {
+        let (domains, functions) = error_domains("normal", "");
+        assert_eq!(
+            domains,
+            pairs(&[
+                ("ParseError", "foo_parse_error_quark"),
+                ("IOErrorEnum", "foo_io_error_quark"),
+                ("KeyError", "foo_key_error_quark"),
+            ])
+        );
+        // Matching functions become the error domain of their enumeration, the one
+        // not matching any enumeration is kept
+        assert_eq!(functions, ["foo_status_domain", "foo_other_error_quark"]);
+
+        let (domains, functions) = error_domains("sys", "");
+        assert_eq!(domains.len(), 3);
+        assert_eq!(functions.len(), 5);
+    }
+
+    #[test]
+    fn error_domain_from_config() {
+        let objects = "[[object]]\nname = \"Foo.Status\"\nstatus = \"generate\"\n\
+                       error_quark_function = \"foo_status_domain\"\n\
+                       [[object]]\nname = \"Foo.KeyError\"\nstatus = \"generate\"\n\
+                       error_quark_function = \"foo_other_error_quark\"\n";
+        let (domains, functions) = error_domains("normal", objects);
+        // The annotated domain of `KeyError` takes precedence over the config
+        assert_eq!(
+            domains,
+            pairs(&[
+                ("ParseError", "foo_parse_error_quark"),
+                ("IOErrorEnum", "foo_io_error_quark"),
+                ("Status", "foo_status_domain"),
+                ("KeyError", "foo_key_error_quark"),
+            ])
+        );
+        assert_eq!(functions, ["foo_other_error_quark"]);
+    }
+
+    fn find<'a>(functions: &'a [Function], name: &str) -> &'a Function {
+        functions.iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn sync_func_pairing() {
+        let functions = functions(&format!(
+            "{}{}{}",
+            function("load_begin", r#"glib:sync-func="load""#),
+            function("load", r#"glib:async-func="load_begin""#),
+            function("load_finish", "")
+        ));
+        let load_begin = find(&functions, "load_begin");
+        assert_eq!(load_begin.sync_func.as_deref(), Some("load"));
+        assert_eq!(load_begin.finish_func.as_deref(), Some("load_finish"));
+        assert_eq!(
+            find(&functions, "load_finish").async_func.as_deref(),
+            Some("load_begin")
+        );
+    }
+
+    #[test]
+    fn conflicting_sync_func() {
+        let functions = functions(&format!(
+            "{}{}{}{}",
+            function(
+                "copy_async",
+                r#"glib:finish-func="copy_finish" glib:sync-func="copy_finish""#
+            ),
+            function("copy_finish", ""),
+            function("move_async", r#"glib:sync-func="move""#),
+            function("move", r#"glib:async-func="copy_async""#)
+        ));
+        let copy_async = find(&functions, "copy_async");
+        assert_eq!(copy_async.sync_func, None);
+        assert_eq!(copy_async.finish_func.as_deref(), Some("copy_finish"));
+        assert_eq!(find(&functions, "move_async").sync_func, None);
+    }
 }
@@ -0,0 +1,346 @@
+//! Rebinding of a library to another main namespace.
+//!
+//! A workspace reads every GIR file once into a shared library, then each crate
+//! gets a library holding only its namespace and includes, laid out as if they
+//! were read on their own.
+
+use crate::library::*;
+use std::{collections::HashMap, iter};
+
+impl Library {
+    /// Builds the library of the namespace `name` with its includes, `name`
+    /// being the main namespace.
+    ///
+    /// Returns `None` if `name` wasn't read from a GIR file.
+    pub fn rebind(&self, name: &str) -> Option<Library> {
+        let main = self.find_namespace(name)?;
+        self.namespace(main).gir_file.as_ref()?;
+
+        let mut rebinder = Rebinder {
+            source: self,
+            library: Library::new(name),
+            ns_ids: HashMap::new(),
+            internal_ids: HashMap::new(),
+            pending: Vec::new(),
+        };
+        rebinder
+            .ns_ids
+            .insert(INTERNAL_NAMESPACE, INTERNAL_NAMESPACE);
+        rebinder.ns_ids.insert(main, MAIN_NAMESPACE);
+        // Includes are added in the order the parser reads them
+        let mut order = Vec::new();
+        self.add_includes(main, &mut vec![main], &mut order);
+        for ns_id in order {
+            rebinder.ns_id(ns_id);
+        }
+
+        rebinder.copy_namespace(main);
+        // Namespaces only referenced by stub types are added while copying
+        let mut copied = 0;
+        while let Some(&ns_id) = rebinder.pending.get(copied) {
+            rebinder.copy_namespace(ns_id);
+            copied += 1;
+        }
+
+        let ns = rebinder.library.namespace_mut(MAIN_NAMESPACE);
+        ns.included_by = None;
+        ns.resolved_version = None;
+        Some(rebinder.library)
+    }
+
+    fn add_includes(&self, ns_id: u16, visited: &mut Vec<u16>, order: &mut Vec<u16>) {
+        for name in &self.namespace(ns_id).includes {
+            if let Some(include) = self.find_namespace(name) {
+                if !visited.contains(&include) {
+                    visited.push(include);
+                    // A namespace is only added after its own includes
+                    self.add_includes(include, visited, order);
+                    order.push(include);
+                }
+            }
+        }
+    }
+}
+
+struct Rebinder<'a> {
+    source: &'a Library,
+    library: Library,
+    /// Namespace ids in `source` to namespace ids in `library`
+    ns_ids: HashMap<u16, u16>,
+    /// Ids of internal types in `source` to the ones in `library`
+    internal_ids: HashMap<u32, TypeId>,
+    /// Namespaces to copy, in the order they were added to `library`
+    pending: Vec<u16>,
+}
+
+impl<'a> Rebinder<'a> {
+    fn ns_id(&mut self, ns_id: u16) -> u16 {
+        if let Some(&id) = self.ns_ids.get(&ns_id) {
+            return id;
+        }
+        let id = self
+            .library
+            .add_namespace(&self.source.namespace(ns_id).name);
+        self.ns_ids.insert(ns_id, id);
+        self.pending.push(ns_id);
+        id
+    }
+
+    fn type_id(&mut self, tid: TypeId) -> TypeId {
+        if tid.ns_id == INTERNAL_NAMESPACE {
+            self.internal_type_id(tid)
+        } else {
+            TypeId {
+                ns_id: self.ns_id(tid.ns_id),
+                id: tid.id,
+            }
+        }
+    }
+
+    /// Internal types are named after the ids of their inner types, so they're
+    /// added again with the rebound ids.
+    fn internal_type_id(&mut self, tid: TypeId) -> TypeId {
+        if let Some(&id) = self.internal_ids.get(&tid.id) {
+            return id;
+        }
+        let mut typ = self.source.type_(tid).clone();
+        visit_type_ids(&mut typ, &mut |tid| *tid = self.type_id(*tid));
+        let library = &mut self.library;
+        let id = match typ {
+            // Added in the same order by `Library::new`
+            Type::Fundamental(_) => tid,
+            Type::CArray(inner) => Type::c_array(library, inner, None, None),
+            Type::FixedArray(inner, size, c_type) => {
+                Type::c_array(library, inner, Some(size), c_type)
+            }
+            Type::Array(inner) => Type::container(library, "GLib.Array", vec![inner]).unwrap(),
+            Type::PtrArray(inner) => {
+                Type::container(library, "GLib.PtrArray", vec![inner]).unwrap()
+            }
+            Type::HashTable(key, value) => {
+                Type::container(library, "GLib.HashTable", vec![key, value]).unwrap()
+            }
+            Type::List(inner) => Type::container(library, "GLib.List", vec![inner]).unwrap(),
+            Type::SList(inner) => Type::container(library, "GLib.SList", vec![inner]).unwrap(),
+            Type::Typed(typed) => Type::typed(library, typed.typ, typed.kind, &typed.type_string),
+            Type::Function(func) => Type::function(library, func),
+            typ => {
+                let source = self.source.namespace(INTERNAL_NAMESPACE);
+                let name = source
+                    .index
+                    .iter()
+                    .find(|&(_, &id)| id == tid.id)
+                    .map(|(name, _)| name.clone())
+                    .unwrap();
+                library.add_type(INTERNAL_NAMESPACE, &name, typ)
+            }
+        };
+        self.internal_ids.insert(tid.id, id);
+        id
+    }
+
+    fn copy_namespace(&mut self, ns_id: u16) {
+        let mut ns = self.source.namespace(ns_id).clone();
+        for typ in ns.types.iter_mut().flatten() {
+            visit_type_ids(typ, &mut |tid| *tid = self.type_id(*tid));
+        }
+        for constant in &mut ns.constants {
+            constant.typ = self.type_id(constant.typ);
+        }
+        for func in &mut ns.functions {
+            visit_function(func, &mut |tid| *tid = self.type_id(*tid));
+        }
+        let id = self.ns_id(ns_id);
+        *self.library.namespace_mut(id) = ns;
+    }
+}
+
+fn visit_type_ids(typ: &mut Type, f: &mut dyn FnMut(&mut TypeId)) {
+    match typ {
+        Type::Fundamental(_) | Type::Custom(_) => {}
+        Type::Alias(alias) => f(&mut alias.typ),
+        Type::Enumeration(enum_) => visit_functions(&mut enum_.functions, f),
+        Type::Bitfield(bitfield) => visit_functions(&mut bitfield.functions, f),
+        Type::Record(record) => {
+            visit_fields(&mut record.fields, f);
+            visit_functions(&mut record.functions, f);
+        }
+        Type::Union(union) => {
+            visit_fields(&mut union.fields, f);
+            visit_functions(&mut union.functions, f);
+        }
+        Type::Function(func) => visit_function(func, f),
+        Type::Interface(interface) => {
+            visit_functions(&mut interface.functions, f);
+            visit_signals(&mut interface.signals, f);
+            visit_properties(&mut interface.properties, f);
+            interface.prerequisites.iter_mut().for_each(f);
+        }
+        Type::Class(class) => {
+            visit_fields(&mut class.fields, f);
+            visit_functions(&mut class.functions, f);
+            visit_signals(&mut class.signals, f);
+            visit_properties(&mut class.properties, f);
+            class.parent.iter_mut().for_each(&mut *f);
+            class.implements.iter_mut().for_each(f);
+        }
+        Type::Typed(typed) => f(&mut typed.typ),
+        Type::Array(inner)
+        | Type::CArray(inner)
+        | Type::FixedArray(inner, _, _)
+        | Type::PtrArray(inner)
+        | Type::List(inner)
+        | Type::SList(inner) => f(inner),
+        Type::HashTable(key, value) => {
+            f(key);
+            f(value);
+        }
+    }
+}
+
+fn visit_function(func: &mut Function, f: &mut dyn FnMut(&mut TypeId)) {
+    for par in func.parameters.iter_mut().chain(iter::once(&mut func.ret)) {
+        f(&mut par.typ);
+    }
+}
+
+fn visit_functions(functions: &mut [Function], f: &mut dyn FnMut(&mut TypeId)) {
+    for func in functions {
+        visit_function(func, f);
+    }
+}
+
+fn visit_signals(signals: &mut [Signal], f: &mut dyn FnMut(&mut TypeId)) {
+    for signal in signals {
+        for par in signal
+            .parameters
+            .iter_mut()
+            .chain(iter::once(&mut signal.ret))
+        {
+            f(&mut par.typ);
+        }
+    }
+}
+
+fn visit_fields(fields: &mut [Field], f: &mut dyn FnMut(&mut TypeId)) {
+    for field in fields {
+        f(&mut field.typ);
+    }
+}
+
+fn visit_properties(properties: &mut [Property], f: &mut dyn FnMut(&mut TypeId)) {
+    for prop in properties {
+        f(&mut prop.typ);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::PathBuf};
+
+    fn gir(name: &str, include: &str, content: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0">
+  {}
+  <namespace name="{}" version="1.0" c:identifier-prefixes="{}" c:symbol-prefixes="{}">
+    {}
+  </namespace>
+</repository>
+"#,
+            include,
+            name,
+            name,
+            name.to_lowercase(),
+            content
+        )
+    }
+
+    fn read(dirs: &[PathBuf], name: &str) -> Library {
+        let mut library = Library::new(name);
+        library
+            .read_file(dirs, &mut vec![format!("{}-1.0", name)])
+            .unwrap();
+        library
+    }
+
+    fn array_parameter(library: &Library, ns_id: u16, func: &str) -> String {
+        let func = library
+            .namespace(ns_id)
+            .functions
+            .iter()
+            .find(|f| f.name == func)
+            .unwrap();
+        match library.type_(func.parameters[0].typ) {
+            Type::CArray(inner) => inner.full_name(library),
+            typ => panic!("Unexpected parameter type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn rebind_included_namespace() {
+        let dir = std::env::temp_dir().join(format!("gir-rebind-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let point = r#"<record name="Point" c:type="BarPoint">
+      <field name="x"><type name="gint" c:type="gint"/></field>
+    </record>"#;
+        let function = |ns: &str| {
+            format!(
+                r#"<function name="take" c:identifier="{}_take">
+      <return-value><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="points">
+          <array c:type="BarPoint*"><type name="Bar.Point" c:type="BarPoint"/></array>
+        </parameter>
+      </parameters>
+    </function>"#,
+                ns
+            )
+        };
+        fs::write(
+            dir.join("Bar-1.0.gir"),
+            gir("Bar", "", &format!("{}\n{}", point, function("bar"))),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("Foo-1.0.gir"),
+            gir(
+                "Foo",
+                r#"<include name="Bar" version="1.0"/>"#,
+                &function("foo"),
+            ),
+        )
+        .unwrap();
+
+        let dirs = vec![dir.clone()];
+        let foo = read(&dirs, "Foo");
+        let bar = foo.rebind("Bar").unwrap();
+        let standalone = read(&dirs, "Bar");
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names = |library: &Library| {
+            library
+                .namespaces
+                .iter()
+                .map(|ns| ns.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&foo), ["*", "Foo", "Bar"]);
+        assert_eq!(names(&bar), names(&standalone));
+        assert_eq!(
+            bar.namespace(MAIN_NAMESPACE).index,
+            standalone.namespace(MAIN_NAMESPACE).index
+        );
+        assert_eq!(array_parameter(&bar, MAIN_NAMESPACE, "take"), "Bar.Point");
+        assert!(bar.namespace(MAIN_NAMESPACE).included_by.is_none());
+
+        let foo_again = foo.rebind("Foo").unwrap();
+        assert_eq!(names(&foo_again), names(&foo));
+        assert_eq!(
+            array_parameter(&foo_again, MAIN_NAMESPACE, "take"),
+            "Bar.Point"
+        );
+    }
+}
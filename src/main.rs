@@ -1,11 +1,19 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
-use std::{cell::RefCell, str::FromStr};
+use std::{
+    collections::VecDeque,
+    panic::{self, AssertUnwindSafe},
+    str::FromStr,
+    sync::{Mutex, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
 
 use getopts::Options;
 use hprof::Profiler;
-use libgir::{self as gir, Config, Library, WorkMode};
+use libgir::{self as gir, read_workspace, Config, Library, WorkMode};
+use log::warn;
 
 fn print_usage(program: &str, opts: Options) {
     let brief = format!(
@@ -26,10 +34,12 @@ impl<S: AsRef<str>> OptionStr for Option<S> {
     }
 }
 
-#[allow(clippy::large_enum_variant)]
 enum RunKind {
-    Config(Config),
+    Configs { configs: Vec<Config>, jobs: usize },
+    ValidateConfigs(Vec<Config>),
+    NormalizeConfig(Box<Config>),
     CheckGirFile(String),
+    JsonSchema,
 }
 
 fn build_config() -> Result<RunKind, String> {
@@ -37,14 +47,31 @@ fn build_config() -> Result<RunKind, String> {
     let program = args[0].clone();
 
     let mut options = Options::new();
-    options.optopt(
+    options.optmulti(
         "c",
         "config",
-        "Config file path (default: Gir.toml)",
+        "Config file path (default: Gir.toml), can be repeated to generate several crates",
         "CONFIG",
     );
+    options.optopt(
+        "w",
+        "workspace",
+        "Workspace file listing the config files of several crates",
+        "WORKSPACE",
+    );
+    options.optopt(
+        "j",
+        "jobs",
+        "Number of crates generated in parallel (default: number of CPUs)",
+        "N",
+    );
     options.optflag("h", "help", "Show this message");
-    options.optopt("d", "gir-directory", "Directory for girs", "GIRSPATH");
+    options.optmulti(
+        "d",
+        "gir-directory",
+        "Directory for girs, can be repeated to search several in order",
+        "GIRSPATH",
+    );
     options.optopt(
         "m",
         "mode",
@@ -56,6 +83,27 @@ fn build_config() -> Result<RunKind, String> {
     options.optflag("b", "make-backup", "Make backup before generating");
     options.optflag("s", "stats", "Show statistics");
     options.optflag("", "disable-format", "Disable formatting generated code");
+    options.optflag(
+        "",
+        "no-gir-cache",
+        "Don't use the cache of parsed GIR files",
+    );
+    options.optflag(
+        "",
+        "validate-config",
+        "Report the configured objects and functions missing from the library as errors \
+         instead of generating code",
+    );
+    options.optflag(
+        "",
+        "normalize-config",
+        "Print the config as normalized TOML instead of generating code",
+    );
+    options.optflag(
+        "",
+        "json-schema",
+        "Print the JSON Schema of the config files",
+    );
     options.optopt(
         "",
         "check-gir-file",
@@ -69,6 +117,10 @@ fn build_config() -> Result<RunKind, String> {
         return Ok(RunKind::CheckGirFile(check_gir_file));
     }
 
+    if matches.opt_present("json-schema") {
+        return Ok(RunKind::JsonSchema);
+    }
+
     if matches.opt_present("h") {
         print_usage(&program, options);
         process::exit(0);
@@ -85,19 +137,64 @@ fn build_config() -> Result<RunKind, String> {
         },
     };
 
-    Config::new(
-        matches.opt_str("c").as_str_ref(),
-        work_mode,
-        matches.opt_str("d").as_str_ref(),
-        matches.free.get(0).as_str_ref(),
-        matches.free.get(1).as_str_ref(),
-        matches.opt_str("o").as_str_ref(),
-        matches.opt_str("doc-target-path").as_str_ref(),
-        matches.opt_present("b"),
-        matches.opt_present("s"),
-        matches.opt_present("disable-format"),
-    )
-    .map(RunKind::Config)
+    let jobs = match matches.opt_str("j") {
+        Some(s) => match s.parse() {
+            Ok(jobs) if jobs > 0 => jobs,
+            _ => return Err(format!("Invalid number of jobs `{}`", s)),
+        },
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
+    let mut config_files = matches.opt_strs("c");
+    if let Some(workspace_file) = matches.opt_str("w") {
+        for config_file in read_workspace(Path::new(&workspace_file))? {
+            config_files.push(
+                config_file
+                    .to_str()
+                    .ok_or_else(|| format!("Invalid config path {}", config_file.display()))?
+                    .to_owned(),
+            );
+        }
+    }
+    if config_files.len() > 1
+        && (!matches.free.is_empty() || matches.opt_present("o") || matches.opt_present("p"))
+    {
+        return Err("Library, version and target paths can't be given for several configs".into());
+    }
+    if config_files.len() > 1 && matches.opt_present("normalize-config") {
+        return Err("Only one config can be normalized at a time".into());
+    }
+    if config_files.is_empty() {
+        config_files.push(String::new());
+    }
+
+    config_files
+        .iter()
+        .map(|config_file| {
+            Config::new(
+                Some(config_file.as_str()),
+                work_mode,
+                &matches.opt_strs("d"),
+                matches.free.get(0).as_str_ref(),
+                matches.free.get(1).as_str_ref(),
+                matches.opt_str("o").as_str_ref(),
+                matches.opt_str("doc-target-path").as_str_ref(),
+                matches.opt_present("b"),
+                matches.opt_present("s"),
+                matches.opt_present("disable-format"),
+                matches.opt_present("no-gir-cache"),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|configs| {
+            if matches.opt_present("validate-config") {
+                RunKind::ValidateConfigs(configs)
+            } else if matches.opt_present("normalize-config") {
+                RunKind::NormalizeConfig(Box::new(configs.into_iter().next().unwrap()))
+            } else {
+                RunKind::Configs { configs, jobs }
+            }
+        })
 }
 
 #[cfg_attr(test, allow(dead_code))]
@@ -126,7 +223,7 @@ fn run_check(check_gir_file: &str) -> Result<(), String> {
         check_gir_file
     ))?;
 
-    library.read_file(&parent, &mut vec![lib_name.to_owned()])
+    library.read_file(&[parent.to_owned()], &mut vec![lib_name.to_owned()])
 }
 
 fn do_main() -> Result<(), String> {
@@ -135,26 +232,68 @@ fn do_main() -> Result<(), String> {
     }
     env_logger::init();
 
-    let mut cfg = match build_config() {
+    let (configs, jobs) = match build_config() {
         Ok(RunKind::CheckGirFile(check_gir_file)) => return run_check(&check_gir_file),
-        Ok(RunKind::Config(cfg)) => cfg,
+        Ok(RunKind::Configs { configs, jobs }) => (configs, jobs),
+        Ok(RunKind::ValidateConfigs(configs)) => return validate_configs(configs),
+        Ok(RunKind::NormalizeConfig(cfg)) => {
+            print!("{}", cfg.to_toml_string()?);
+            return Ok(());
+        }
+        Ok(RunKind::JsonSchema) => {
+            print!("{}", gir::json_schema());
+            return Ok(());
+        }
         Err(err) => return Err(err),
     };
-    cfg.check_disable_format();
+    for cfg in &configs {
+        cfg.check_format()?;
+    }
+
+    if configs.len() > 1 {
+        return run_workspace(configs, jobs);
+    }
+    let cfg = configs.into_iter().next().unwrap();
 
     let statistics = Profiler::new("Gir");
     statistics.start_frame();
 
     let watcher_total = statistics.enter("Total");
 
-    let mut library = {
+    let library = {
         let _watcher = statistics.enter("Loading");
 
-        let mut library = Library::new(&cfg.library_name);
-        library.read_file(&cfg.girs_dir, &mut vec![cfg.library_full_name()])?;
-        library
+        load_library(&cfg)?
     };
 
+    let env = generate(cfg, library, &statistics);
+
+    drop(watcher_total);
+    statistics.end_frame();
+
+    if env.config.show_statistics {
+        statistics.print_timing();
+    }
+    if env.config.work_mode == WorkMode::DisplayNotBound {
+        env.library.show_non_bound_types(&env);
+    } else {
+        println!("Generated files: {}", env.generated_files_summary());
+    }
+
+    env.check_generated_files()
+}
+
+fn load_library(cfg: &Config) -> Result<Library, String> {
+    Library::read_cached(
+        &cfg.library_name,
+        &cfg.library_full_name(),
+        &cfg.girs_dirs,
+        cfg.gir_cache_dir.as_deref(),
+    )
+}
+
+/// Applies the config to the library, returns the problems of the config.
+fn prepare_library(cfg: &mut Config, library: &mut Library, statistics: &Profiler) -> Vec<String> {
     {
         let _watcher = statistics.enter("Preprocessing");
         library.preprocessing(cfg.work_mode);
@@ -162,17 +301,32 @@ fn do_main() -> Result<(), String> {
 
     {
         let _watcher = statistics.enter("Update library by config");
-        gir::update_version::apply_config(&mut library, &cfg);
+        gir::update_version::apply_config(library, cfg);
     }
 
+    // Before postprocessing removes functions like the error domain quarks
+    let problems = {
+        let _watcher = statistics.enter("Validating config");
+        gir::validate_config(cfg, library)
+    };
+
     {
         let _watcher = statistics.enter("Postprocessing");
-        library.postprocessing(&cfg);
+        library.postprocessing(cfg);
     }
 
     {
         let _watcher = statistics.enter("Resolving type ids");
-        cfg.resolve_type_ids(&library);
+        cfg.resolve_type_ids(library);
+    }
+
+    problems
+}
+
+/// Runs everything after loading the library for one crate.
+fn generate(mut cfg: Config, mut library: Library, statistics: &Profiler) -> gir::Env {
+    for problem in prepare_library(&mut cfg, &mut library, statistics) {
+        warn!("{}", problem);
     }
 
     {
@@ -191,9 +345,10 @@ fn do_main() -> Result<(), String> {
             library,
             config: cfg,
             namespaces,
-            symbols: RefCell::new(symbols),
+            symbols: RwLock::new(symbols),
             class_hierarchy,
             analysis: Default::default(),
+            generated_files: Default::default(),
         }
     };
 
@@ -207,20 +362,205 @@ fn do_main() -> Result<(), String> {
         gir::codegen_generate(&env);
     }
 
-    if !env.config.disable_format && env.config.work_mode.is_generate_rust_files() {
-        let _watcher = statistics.enter("Formatting");
-        gir::fmt::format(&env.config.target_path);
+    env
+}
+
+struct CrateRun {
+    name: String,
+    /// Time and generated files summary of a successful run
+    result: Result<(Duration, String), String>,
+}
+
+/// Where the library of a crate in a workspace comes from.
+enum LibrarySource {
+    /// Loaded from the GIR cache
+    Cached(Library),
+    /// Rebound from the shared library with this index
+    Shared(usize),
+}
+
+/// Generates several crates, reading every GIR file only once.
+///
+/// The GIRs are read into libraries shared by the crates. The crates are then
+/// generated in `jobs` threads, each rebinding a shared library to its own namespace.
+fn run_workspace(configs: Vec<Config>, jobs: usize) -> Result<(), String> {
+    let mut shared = Vec::new();
+    let sources: Vec<Result<LibrarySource, String>> = configs
+        .iter()
+        .map(|cfg| {
+            let lib = cfg.library_full_name();
+            match Library::load_cached(&lib, &cfg.girs_dirs, cfg.gir_cache_dir.as_deref()) {
+                Some(library) => Ok(LibrarySource::Cached(library)),
+                None => read_shared(&mut shared, cfg).map(LibrarySource::Shared),
+            }
+        })
+        .collect();
+
+    let names: Vec<String> = configs.iter().map(crate_run_name).collect();
+    let jobs = jobs.min(configs.len());
+    let queue = Mutex::new(
+        configs
+            .into_iter()
+            .zip(sources)
+            .enumerate()
+            .collect::<VecDeque<_>>(),
+    );
+    let runs = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let (index, (cfg, source)) = match queue.lock().unwrap().pop_front() {
+                    Some(next) => next,
+                    None => break,
+                };
+                let library = source.and_then(|source| match source {
+                    LibrarySource::Cached(library) => Ok(library),
+                    LibrarySource::Shared(shared_index) => {
+                        rebind_shared(&shared[shared_index].1, &cfg)
+                    }
+                });
+                let result = library.and_then(|library| run_crate(cfg, library));
+                runs.lock().unwrap().push((index, result));
+            });
+        }
+    });
+
+    let mut runs = runs.into_inner().unwrap();
+    runs.sort_by_key(|&(index, _)| index);
+    let runs: Vec<CrateRun> = runs
+        .into_iter()
+        .map(|(index, result)| CrateRun {
+            name: names[index].clone(),
+            result,
+        })
+        .collect();
+    print_summary(&runs)
+}
+
+/// Reads the GIR of `cfg` into one of the `shared` libraries, returning its index.
+fn read_shared(shared: &mut Vec<(Vec<PathBuf>, Library)>, cfg: &Config) -> Result<usize, String> {
+    let lib = cfg.library_full_name();
+    for (index, (dirs, library)) in shared.iter_mut().enumerate() {
+        if *dirs != cfg.girs_dirs {
+            continue;
+        }
+        match library
+            .find_namespace(&cfg.library_name)
+            .and_then(|ns_id| library.namespace(ns_id).gir_file.as_ref())
+        {
+            Some(path) if path.file_stem() == Some(lib.as_ref()) => return Ok(index),
+            // Another version of the library
+            Some(_) => continue,
+            None => {}
+        }
+        // Left untouched if the includes conflict with the versions already read
+        let mut extended = library.clone();
+        if extended.read_file(dirs, &mut vec![lib.clone()]).is_ok() {
+            *library = extended;
+            return Ok(index);
+        }
     }
 
-    drop(watcher_total);
-    statistics.end_frame();
+    let mut library = Library::new(&cfg.library_name);
+    library.read_file(&cfg.girs_dirs, &mut vec![lib])?;
+    shared.push((cfg.girs_dirs.clone(), library));
+    Ok(shared.len() - 1)
+}
 
-    if env.config.show_statistics {
-        statistics.print_timing();
+fn rebind_shared(shared: &Library, cfg: &Config) -> Result<Library, String> {
+    let lib = cfg.library_full_name();
+    let library = shared
+        .rebind(&cfg.library_name)
+        .ok_or_else(|| format!("{} wasn't read", lib))?;
+    library.store_cached(&lib, &cfg.girs_dirs, cfg.gir_cache_dir.as_deref());
+    Ok(library)
+}
+
+/// Reports the problems of the configs as errors instead of generating code.
+fn validate_configs(configs: Vec<Config>) -> Result<(), String> {
+    let mut problems = 0;
+    for mut cfg in configs {
+        cfg.use_crate_name_overrides();
+        let mut library = load_library(&cfg)?;
+        for problem in prepare_library(&mut cfg, &mut library, &Profiler::new("Gir")) {
+            eprintln!("{}: {}", crate_run_name(&cfg), problem);
+            problems += 1;
+        }
     }
-    if env.config.work_mode == WorkMode::DisplayNotBound {
-        env.library.show_non_bound_types(&env);
+    if problems == 0 {
+        Ok(())
+    } else {
+        Err(format!("Found {} problems in the config", problems))
     }
+}
 
-    Ok(())
+fn crate_run_name(cfg: &Config) -> String {
+    format!(
+        "{} ({:?}) in {}",
+        cfg.library_full_name(),
+        cfg.work_mode,
+        cfg.target_path.display()
+    )
+}
+
+fn run_crate(cfg: Config, library: Library) -> Result<(Duration, String), String> {
+    // The config was read in the main thread
+    cfg.use_crate_name_overrides();
+    let start = Instant::now();
+
+    let run = panic::catch_unwind(AssertUnwindSafe(|| {
+        let statistics = Profiler::new("Gir");
+        statistics.start_frame();
+        let env = {
+            let _watcher = statistics.enter("Total");
+            generate(cfg, library, &statistics)
+        };
+        statistics.end_frame();
+
+        // Keep the output of a crate together
+        let _stdout = std::io::stdout().lock();
+        if env.config.show_statistics {
+            statistics.print_timing();
+        }
+        if env.config.work_mode == WorkMode::DisplayNotBound {
+            env.library.show_non_bound_types(&env);
+        }
+        env.check_generated_files()?;
+        Ok(env.generated_files_summary())
+    }));
+
+    match run {
+        Ok(Ok(files_summary)) => Ok((start.elapsed(), files_summary)),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err("panicked".to_owned()),
+    }
+}
+
+fn print_summary(runs: &[CrateRun]) -> Result<(), String> {
+    println!("Summary:");
+    let mut failed = 0;
+    for run in runs {
+        match run.result {
+            Ok((time, ref files_summary)) => println!(
+                "  {}: done in {:.2}s, {}",
+                run.name,
+                time.as_secs_f64(),
+                files_summary
+            ),
+            Err(ref e) => {
+                failed += 1;
+                println!("  {}: failed: {}", run.name, e);
+            }
+        }
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to generate {} of {} crates",
+            failed,
+            runs.len()
+        ))
+    }
 }
@@ -1,27 +1,18 @@
 use crate::case::*;
 use once_cell::sync::Lazy;
-use std::{borrow::Cow, collections::HashMap, path::*};
+use std::{borrow::Cow, cell::RefCell, collections::HashMap, path::*};
 
-static mut CRATE_NAME_OVERRIDES: Option<HashMap<String, String>> = None;
+thread_local! {
+    // Per thread as several crates can be generated in parallel
+    static CRATE_NAME_OVERRIDES: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
 
 pub(crate) fn set_crate_name_overrides(overrides: HashMap<String, String>) {
-    unsafe {
-        if CRATE_NAME_OVERRIDES.is_some() {
-            panic!("Crate name overrides already set;");
-        }
-        CRATE_NAME_OVERRIDES = Some(overrides);
-    }
+    CRATE_NAME_OVERRIDES.with(|cell| *cell.borrow_mut() = overrides);
 }
 
 fn get_crate_name_override(crate_name: &str) -> Option<String> {
-    unsafe {
-        if let Some(ref overrides) = CRATE_NAME_OVERRIDES {
-            if let Some(crate_name) = overrides.get(crate_name) {
-                return Some(crate_name.clone());
-            }
-        }
-        None
-    }
+    CRATE_NAME_OVERRIDES.with(|cell| cell.borrow().get(crate_name).cloned())
 }
 
 pub fn split_namespace_name(name: &str) -> (Option<&str>, &str) {
@@ -3,8 +3,9 @@ use crate::{
     version::Version,
     xmlparser::{Element, XmlParser},
 };
-use log::{trace, warn};
+use log::{info, trace, warn};
 use std::{
+    env, fs,
     mem::replace,
     path::{Path, PathBuf},
     str::FromStr,
@@ -17,47 +18,51 @@ pub fn is_empty_c_type(c_type: &str) -> bool {
 }
 
 impl Library {
-    pub fn read_file(&mut self, dir: &Path, libs: &mut Vec<String>) -> Result<(), String> {
-        let file_name = make_file_name(dir, &libs[libs.len() - 1]);
+    pub fn read_file(&mut self, dirs: &[PathBuf], libs: &mut Vec<String>) -> Result<(), String> {
+        let lib = &libs[libs.len() - 1];
+        let file_name = find_file(dirs, lib)?;
+        info!("Reading {} from {}", lib, file_name.display());
         let mut p = XmlParser::from_path(&file_name)?;
         p.document(|p, _| {
             p.element_with_name("repository", |parser, _elem| {
-                self.read_repository(dir, parser, libs)
+                self.read_repository(dirs, &file_name, parser, libs)
+            })
+        })
+    }
+
+    /// Reads a GIR document from memory, without resolving its includes.
+    #[cfg(test)]
+    pub fn read_str(&mut self, xml: &str) -> Result<(), String> {
+        let mut p = XmlParser::new(xml.as_bytes())?;
+        p.document(|p, _| {
+            p.element_with_name("repository", |parser, _elem| {
+                self.read_repository(&[], Path::new("test.gir"), parser, &mut Vec::new())
             })
         })
     }
 
     fn read_repository(
         &mut self,
-        dir: &Path,
+        dirs: &[PathBuf],
+        file_name: &Path,
         parser: &mut XmlParser<'_>,
         libs: &mut Vec<String>,
     ) -> Result<(), String> {
         let mut package = None;
         let mut includes = Vec::new();
+        let mut gir_includes = Vec::new();
         parser.elements(|parser, elem| match elem.name() {
-            "include" => {
-                match (elem.attr("name"), elem.attr("version")) {
-                    (Some(name), Some(ver)) => {
-                        if self.find_namespace(name).is_none() {
-                            let lib = format!("{}-{}", name, ver);
-                            if libs.iter().any(|x| *x == lib) {
-                                return Err(format!(
-                                    "`{}` includes itself (full path:`{}`)!",
-                                    lib,
-                                    libs.join("::")
-                                ));
-                            }
-                            libs.push(lib);
-                            self.read_file(dir, libs)?;
-                            libs.pop();
-                        }
-                    }
-                    (Some(name), None) => includes.push(name.to_owned()),
-                    _ => {}
+            "include" if elem.prefix() == Some("c") => {
+                if let Some(name) = elem.attr("name") {
+                    includes.push(name.to_owned());
                 }
                 Ok(())
             }
+            "include" => {
+                let name = elem.attr_required("name")?;
+                gir_includes.push(name.to_owned());
+                self.read_include(dirs, file_name, libs, name, elem.attr("version"))
+            }
             "package" => {
                 // Take the first package element and ignore any other ones.
                 if package.is_none() {
@@ -69,8 +74,10 @@ impl Library {
             "namespace" => self.read_namespace(
                 parser,
                 elem,
+                file_name,
                 package.take(),
                 replace(&mut includes, Vec::new()),
+                replace(&mut gir_includes, Vec::new()),
             ),
             "attribute" => parser.ignore_element(),
             _ => Err(parser.unexpected_element(elem)),
@@ -78,12 +85,79 @@ impl Library {
         Ok(())
     }
 
+    /// Reads the GIR of an included namespace unless it's already loaded.
+    fn read_include(
+        &mut self,
+        dirs: &[PathBuf],
+        file_name: &Path,
+        libs: &mut Vec<String>,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<(), String> {
+        if let Some(ns_id) = self.find_namespace(name) {
+            let ns = self.namespace(ns_id);
+            return match (version, &ns.version) {
+                (Some(version), Some(loaded)) if version != loaded => Err(format!(
+                    "Conflicting versions of {}: `{}` includes {}-{} but `{}` includes {}-{}",
+                    name,
+                    file_name.display(),
+                    name,
+                    version,
+                    ns.included_by.as_ref().map_or_else(
+                        || "the configuration".to_owned(),
+                        |p| p.display().to_string()
+                    ),
+                    name,
+                    loaded
+                )),
+                _ => Ok(()),
+            };
+        }
+
+        let resolved = version.is_none();
+        let version = match version {
+            Some(version) => version.to_owned(),
+            None => {
+                let version = find_latest_version(dirs, name)?;
+                info!(
+                    "Using {}-{} for unversioned include in `{}`",
+                    name,
+                    version,
+                    file_name.display()
+                );
+                version
+            }
+        };
+        let lib = format!("{}-{}", name, version);
+        if libs.iter().any(|x| *x == lib) {
+            return Err(format!(
+                "`{}` includes itself (full path:`{}`)!",
+                lib,
+                libs.join("::")
+            ));
+        }
+        libs.push(lib);
+        self.read_file(dirs, libs)?;
+        libs.pop();
+
+        if let Some(ns_id) = self.find_namespace(name) {
+            let ns = self.namespace_mut(ns_id);
+            ns.included_by = Some(file_name.to_owned());
+            if resolved {
+                ns.resolved_version = Some(version);
+            }
+        }
+        Ok(())
+    }
+
     fn read_namespace(
         &mut self,
         parser: &mut XmlParser<'_>,
         elem: &Element,
+        file_name: &Path,
         package: Option<String>,
         c_includes: Vec<String>,
+        includes: Vec<String>,
     ) -> Result<(), String> {
         let ns_name = elem.attr_required("name")?;
         let ns_id = self.add_namespace(ns_name);
@@ -92,6 +166,9 @@ impl Library {
             let ns = self.namespace_mut(ns_id);
             ns.package_name = package;
             ns.c_includes = c_includes;
+            ns.includes = includes;
+            ns.version = elem.attr("version").map(ToOwned::to_owned);
+            ns.gir_file = Some(file_name.to_owned());
             if let Some(s) = elem.attr("shared-library") {
                 ns.shared_library = s.split(',').map(String::from).collect();
             }
@@ -114,6 +191,7 @@ impl Library {
             match elem.name() {
                 "class" => self.read_class(parser, ns_id, elem),
                 "record" => self.read_record_start(parser, ns_id, elem),
+                "boxed" => self.read_boxed(parser, ns_id, elem),
                 "union" => self.read_named_union(parser, ns_id, elem),
                 "interface" => self.read_interface(parser, ns_id, elem),
                 "callback" => self.read_named_callback(parser, ns_id, elem),
@@ -353,12 +431,71 @@ impl Library {
             doc,
             doc_deprecated,
             disguised,
+            standalone_boxed: false,
+            direct: false,
             symbol_prefix,
         });
 
         Ok(Some(typ))
     }
 
+    /// Reads a standalone `<glib:boxed>` element, that is a boxed type that only has a
+    /// GType and no C struct definition. It's stored as an opaque record without fields.
+    fn read_boxed(
+        &mut self,
+        parser: &mut XmlParser<'_>,
+        ns_id: u16,
+        elem: &Element,
+    ) -> Result<(), String> {
+        let record_name = elem.attr_required("name")?;
+        let c_type = self.read_object_c_type(parser, elem)?;
+        let symbol_prefix = elem.attr("symbol-prefix").map(ToOwned::to_owned);
+        let get_type = elem.attr_required("get-type")?;
+        let version = self.read_version(parser, ns_id, elem)?;
+        let deprecated_version = self.read_deprecated_version(parser, ns_id, elem)?;
+
+        let mut fns = Vec::new();
+        let mut doc = None;
+        let mut doc_deprecated = None;
+
+        parser.elements(|parser, elem| match elem.name() {
+            "constructor" | "function" | "method" => {
+                self.read_function_to_vec(parser, ns_id, elem, &mut fns)
+            }
+            "doc" => parser.text().map(|t| doc = Some(t)),
+            "doc-deprecated" => parser.text().map(|t| doc_deprecated = Some(t)),
+            "source-position" => parser.ignore_element(),
+            "attribute" => parser.ignore_element(),
+            _ => Err(parser.unexpected_element(elem)),
+        })?;
+
+        if let Some(tid) = self.find_type(ns_id, record_name) {
+            if self.namespace(tid.ns_id).types[tid.id as usize].is_some() {
+                warn!(
+                    "<glib:boxed name={:?}> conflicts with an already defined type, ignoring it",
+                    record_name
+                );
+                return Ok(());
+            }
+        }
+
+        let typ = Type::Record(Record {
+            name: record_name.into(),
+            c_type: c_type.into(),
+            glib_get_type: Some(get_type.into()),
+            functions: fns,
+            version,
+            deprecated_version,
+            doc,
+            doc_deprecated,
+            symbol_prefix,
+            standalone_boxed: true,
+            ..Record::default()
+        });
+        self.add_type(ns_id, record_name, typ);
+        Ok(())
+    }
+
     fn read_named_union(
         &mut self,
         parser: &mut XmlParser<'_>,
@@ -941,6 +1078,9 @@ impl Library {
                 deprecated_version,
                 doc,
                 doc_deprecated,
+                finish_func: elem.attr("finish-func").map(ToOwned::to_owned),
+                sync_func: elem.attr("sync-func").map(ToOwned::to_owned),
+                async_func: elem.attr("async-func").map(ToOwned::to_owned),
             })
         } else {
             Err(parser.fail_with_position(
@@ -1352,9 +1492,167 @@ impl Library {
     }
 }
 
+/// Looks for the GIR file of `name` in `dirs` first, then in the system directories.
+pub fn find_file(dirs: &[PathBuf], name: &str) -> Result<PathBuf, String> {
+    let search_dirs = search_dirs(dirs);
+    search_dirs
+        .iter()
+        .map(|dir| make_file_name(dir, name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("`{}.gir` not found in {}", name, display_dirs(&search_dirs)))
+}
+
+/// Highest version of the namespace `name` available in any of the search directories.
+pub fn find_latest_version(dirs: &[PathBuf], name: &str) -> Result<String, String> {
+    let search_dirs = search_dirs(dirs);
+    let file_names: Vec<String> = search_dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(Result::ok))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    latest_version(name, file_names.iter().map(String::as_str)).ok_or_else(|| {
+        format!(
+            "No version of {} found in {}",
+            name,
+            display_dirs(&search_dirs)
+        )
+    })
+}
+
+fn latest_version<'a>(name: &str, file_names: impl Iterator<Item = &'a str>) -> Option<String> {
+    let prefix = format!("{}-", name);
+    file_names
+        .filter_map(|file_name| {
+            file_name
+                .strip_prefix(&prefix)?
+                .strip_suffix(".gir")
+                .filter(|version| {
+                    !version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.')
+                })
+        })
+        .max_by_key(|version| version.parse::<Version>().ok())
+        .map(ToOwned::to_owned)
+}
+
+fn search_dirs(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    dirs.iter().cloned().chain(system_gir_dirs()).collect()
+}
+
+fn display_dirs(dirs: &[PathBuf]) -> String {
+    dirs.iter()
+        .map(|dir| dir.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `gir-1.0` directories of the XDG data directories, where GIRs are installed.
+fn system_gir_dirs() -> Vec<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    let data_dirs = env::var_os("XDG_DATA_DIRS")
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+    data_home
+        .into_iter()
+        .chain(env::split_paths(&data_dirs))
+        .map(|dir| dir.join("gir-1.0"))
+        .collect()
+}
+
 fn make_file_name(dir: &Path, name: &str) -> PathBuf {
     let mut path = dir.to_path_buf();
     let name = format!("{}.gir", name);
     path.push(name);
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::record_type::RecordType;
+
+    #[test]
+    fn standalone_boxed() {
+        let mut library = Library::new("Foo");
+        library
+            .read_str(
+                r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0"
+            xmlns:c="http://www.gtk.org/introspection/c/1.0"
+            xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <namespace name="Foo" version="1.0" c:identifier-prefixes="Foo" c:symbol-prefixes="foo">
+    <glib:boxed glib:name="Token" c:symbol-prefix="token"
+                glib:type-name="FooToken" glib:get-type="foo_token_get_type">
+      <method name="copy" c:identifier="foo_token_copy">
+        <return-value transfer-ownership="full">
+          <type name="Token" c:type="FooToken*"/>
+        </return-value>
+        <parameters>
+          <instance-parameter name="token" transfer-ownership="none">
+            <type name="Token" c:type="FooToken*"/>
+          </instance-parameter>
+        </parameters>
+      </method>
+    </glib:boxed>
+    <record name="Point" c:type="FooPoint">
+      <field name="x"><type name="gint" c:type="gint"/></field>
+    </record>
+    <glib:boxed glib:name="Point" glib:type-name="FooPoint"
+                glib:get-type="foo_point_get_type"/>
+  </namespace>
+</repository>
+"#,
+            )
+            .unwrap();
+
+        let tid = library.find_type(MAIN_NAMESPACE, "Token").unwrap();
+        match library.type_(tid) {
+            Type::Record(record) => {
+                assert_eq!(record.c_type, "FooToken");
+                assert_eq!(record.glib_get_type.as_deref(), Some("foo_token_get_type"));
+                assert_eq!(record.symbol_prefix.as_deref(), Some("token"));
+                assert!(record.standalone_boxed);
+                assert!(record.fields.is_empty());
+                assert_eq!(record.functions.len(), 1);
+                // The GType is used even though there's a copy function
+                assert!(RecordType::of(record) == RecordType::AutoBoxed);
+            }
+            typ => panic!("Unexpected type {:?}", typ),
+        }
+
+        // A boxed element for an existing record doesn't replace it
+        let tid = library.find_type(MAIN_NAMESPACE, "Point").unwrap();
+        match library.type_(tid) {
+            Type::Record(record) => {
+                assert!(!record.standalone_boxed);
+                assert_eq!(record.fields.len(), 1);
+            }
+            typ => panic!("Unexpected type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn latest_version_of_namespace() {
+        let file_names = [
+            "Gtk-3.0.gir",
+            "Gtk-4.0.gir",
+            "GtkSource-5.gir",
+            "Gtk-4.0.gir.bak",
+            "Gtk-x11.gir",
+            "Gdk-10.0.gir",
+            "Gdk-9.0.gir",
+        ];
+        assert_eq!(
+            latest_version("Gtk", file_names.iter().cloned()),
+            Some("4.0".into())
+        );
+        assert_eq!(
+            latest_version("Gdk", file_names.iter().cloned()),
+            Some("10.0".into())
+        );
+        assert_eq!(latest_version("Pango", file_names.iter().cloned()), None);
+    }
+}
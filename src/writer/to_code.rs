@@ -4,6 +4,7 @@ use crate::{
     chunk::{Chunk, Param, TupleMode},
     codegen::{translate_from_glib::TranslateFromGlib, translate_to_glib::TranslateToGlib},
     env::Env,
+    library,
 };
 
 pub trait ToCode {
@@ -38,7 +39,11 @@ impl ToCode for Chunk {
                 vec![s]
             }
             FfiCallOutParameter { ref par } => {
-                let s = if par.caller_allocates {
+                let s = if par.caller_allocates
+                    && matches!(*env.type_(par.typ), library::Type::FixedArray(..))
+                {
+                    format!("{}.as_mut_ptr() as *mut _", par.name)
+                } else if par.caller_allocates {
                     format!("{}.to_glib_none_mut().0", par.name)
                 } else if par.is_uninitialized && !par.is_error {
                     format!("{}.as_mut_ptr()", par.name)
@@ -95,6 +100,25 @@ impl ToCode for Chunk {
                 let s = format_block_one_line(prefix, suffix, &chs.to_code(env), "", ", ");
                 vec![s]
             }
+            Struct {
+                ref name,
+                ref fields,
+            } => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(field, ch)| {
+                        let value = format_block_one_line("", "", &ch.to_code(env), "", "");
+                        if &value == field {
+                            value
+                        } else {
+                            format!("{}: {}", field, value)
+                        }
+                    })
+                    .collect();
+                let prefix = format!("{} {{", name);
+                let s = format_block_one_line(&prefix, "}", &fields, " ", ", ");
+                vec![s]
+            }
             FromGlibConversion {
                 ref mode,
                 ref array_length_name,
@@ -72,6 +72,11 @@ impl Element {
         &self.name.local_name
     }
 
+    /// Returns the element namespace prefix, like `c` for `<c:include>`.
+    pub fn prefix(&self) -> Option<&str> {
+        self.name.prefix_ref()
+    }
+
     /// Value of attribute with given name or None if it is not found.
     pub fn attr(&self, name: &str) -> Option<&str> {
         for attr in &self.attributes {
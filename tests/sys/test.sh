@@ -10,9 +10,11 @@ export RUST_LOG="gir=warn"
 cd "`dirname $0`"
 cargo build --release || exit 1
 
+CONFIGS=""
 for TOML in gir-*.toml; do
-	$GIR -c ${TOML} || exit 2
+	CONFIGS="$CONFIGS -c ${TOML}"
 done
+$GIR ${CONFIGS} || exit 2
 
 cd sys_build
 cargo build --features "$FEATURES" || exit 3